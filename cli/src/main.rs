@@ -1,14 +1,22 @@
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
 use std::fs;
-use std::io::{self, Stdout, Write};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Stdout, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use std::time::Duration;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::OnceLock;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind,
+    },
     execute, queue,
     style::{
         Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
@@ -35,22 +43,42 @@ fn ensure_remote_content(status: &mut Option<String>) -> Option<PathBuf> {
 
     if repo_dir.is_dir() {
         if repo_dir.join(".git").is_dir() {
-            if let Err(message) = git_pull(&repo_dir) {
-                status.get_or_insert_with(|| format!("Failed to update content ({})", message));
+            // Another instance holding the lock is already updating the shared cache; read
+            // whatever is checked out rather than racing it.
+            if let Some(_lock) = UpdateLock::acquire(&repo_dir) {
+                let sync =
+                    SyncStatus::of(retry_rate_limited(|| git_pull(&repo_dir), thread::sleep));
+                if let Some(message) = sync.message(last_sync_age(&repo_dir)) {
+                    status.get_or_insert(message);
+                }
             }
         }
         return Some(repo_dir);
     }
 
     if let Some(parent) = repo_dir.parent() {
-        if fs::create_dir_all(parent).is_err() {
-            status.get_or_insert_with(|| "Failed to create cache directory.".to_string());
+        if let Err(err) = create_dirs(parent) {
+            status.get_or_insert_with(|| format!("Failed to create cache directory ({})", err));
             return None;
         }
     }
 
-    match git_clone(&repo_dir) {
+    let Some(_lock) = UpdateLock::acquire(&repo_dir) else {
+        status.get_or_insert_with(|| {
+            "Another johnjeong is fetching content; try again in a moment.".to_string()
+        });
+        return None;
+    };
+    match git_clone_atomic(CONTENT_REPO_URL, &repo_dir, thread::sleep) {
         Ok(()) => Some(repo_dir),
+        Err(message) if rate_limited(&message) => {
+            status.get_or_insert_with(|| {
+                "GitHub rate limited the content fetch; the next launch picks up where it stopped. \
+                 Set GITHUB_TOKEN to raise the limit."
+                    .to_string()
+            });
+            None
+        }
         Err(message) => {
             status.get_or_insert_with(|| format!("Failed to fetch content ({})", message));
             None
@@ -58,6 +86,70 @@ fn ensure_remote_content(status: &mut Option<String>) -> Option<PathBuf> {
     }
 }
 
+/// How the last update of the remote content cache went.
+#[derive(Debug, PartialEq, Eq)]
+enum SyncStatus {
+    Full,
+    /// GitHub kept refusing with 403/429 after the retries; the cache is intact but behind.
+    Partial(String),
+    Offline(String),
+}
+
+impl SyncStatus {
+    fn of(result: Result<(), String>) -> SyncStatus {
+        match result {
+            Ok(()) => SyncStatus::Full,
+            Err(message) if rate_limited(&message) => SyncStatus::Partial(message),
+            Err(message) => SyncStatus::Offline(message),
+        }
+    }
+
+    /// The status line for this sync, given how long ago the cache last synced. A full sync
+    /// says nothing.
+    fn message(&self, age: Option<String>) -> Option<String> {
+        let age = age.unwrap_or_else(|| "at an unknown time".into());
+        match self {
+            SyncStatus::Full => None,
+            SyncStatus::Partial(_) => Some(format!(
+                "Partial sync: GitHub rate limited the update, showing content last synced {}. \
+                 Set GITHUB_TOKEN to raise the limit.",
+                age
+            )),
+            SyncStatus::Offline(message) => {
+                Some(format!("Offline cache, last synced {} ({})", age, message))
+            }
+        }
+    }
+}
+
+const SYNC_RETRIES: u32 = 3;
+
+/// Runs `attempt` again while GitHub answers 403 or 429, sleeping 2, 4, then 8 seconds in
+/// between. Any other failure is returned straight away.
+fn retry_rate_limited(
+    mut attempt: impl FnMut() -> Result<(), String>,
+    mut sleep: impl FnMut(Duration),
+) -> Result<(), String> {
+    let mut delay = Duration::from_secs(2);
+    for _ in 0..SYNC_RETRIES {
+        match attempt() {
+            Err(message) if rate_limited(&message) => {
+                sleep(delay);
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+    attempt()
+}
+
+fn rate_limited(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    ["error: 403", "error: 429", "rate limit"]
+        .iter()
+        .any(|needle| message.contains(needle))
+}
+
 fn content_cache_dir() -> Option<PathBuf> {
     if let Ok(path) = env::var("XDG_CACHE_HOME") {
         return Some(
@@ -81,18 +173,82 @@ fn content_cache_dir() -> Option<PathBuf> {
     None
 }
 
+/// Advisory lock next to the shared content cache so concurrent instances don't run
+/// `git pull` over each other. Locks older than `UPDATE_LOCK_STALE` are assumed abandoned.
+struct UpdateLock {
+    path: PathBuf,
+}
+
+impl UpdateLock {
+    fn acquire(repo_dir: &Path) -> Option<UpdateLock> {
+        let path = repo_dir.with_extension("lock");
+        for _ in 0..2 {
+            match create_file(&path, true) {
+                Ok(mut file) => {
+                    let _ = writeln!(file, "{}", std::process::id());
+                    return Some(UpdateLock { path });
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_none_or(|age| age > UPDATE_LOCK_STALE);
+                    if !stale {
+                        return None;
+                    }
+                    let _ = remove_file(&path);
+                }
+                Err(_) => return None,
+            }
+        }
+        None
+    }
+}
+
+impl Drop for UpdateLock {
+    fn drop(&mut self) {
+        let _ = remove_file(&self.path);
+    }
+}
+
 fn git_available() -> bool {
     Command::new("git").arg("--version").output().is_ok()
 }
 
-fn git_pull(repo_dir: &Path) -> Result<(), String> {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(repo_dir)
-        .arg("pull")
-        .arg("--ff-only")
-        .output()
-        .map_err(|err| err.to_string())?;
+/// `git -C dir`, sending `GITHUB_TOKEN` to github.com when it is set. The header goes in
+/// through `GIT_CONFIG_*` rather than `-c` so the token stays out of the process list.
+fn git_in(dir: &Path, token: Option<&str>) -> Command {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(dir);
+    if let Some(token) = token {
+        let count = env::var("GIT_CONFIG_COUNT")
+            .ok()
+            .and_then(|count| count.parse::<usize>().ok())
+            .unwrap_or(0);
+        let credentials = base64_encode(format!("x-access-token:{}", token).as_bytes());
+        command
+            .env(
+                format!("GIT_CONFIG_KEY_{}", count),
+                "http.https://github.com/.extraheader",
+            )
+            .env(
+                format!("GIT_CONFIG_VALUE_{}", count),
+                format!("AUTHORIZATION: basic {}", credentials),
+            )
+            .env("GIT_CONFIG_COUNT", (count + 1).to_string());
+    }
+    command
+}
+
+fn github_token() -> Option<String> {
+    env::var("GITHUB_TOKEN")
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+}
+
+fn run_git(command: &mut Command) -> Result<(), String> {
+    let output = command.output().map_err(|err| err.to_string())?;
     if output.status.success() {
         Ok(())
     } else {
@@ -100,22 +256,169 @@ fn git_pull(repo_dir: &Path) -> Result<(), String> {
     }
 }
 
-fn git_clone(repo_dir: &Path) -> Result<(), String> {
-    let output = Command::new("git")
-        .arg("clone")
-        .arg("--depth")
-        .arg("1")
-        .arg(CONTENT_REPO_URL)
-        .arg(repo_dir)
-        .output()
-        .map_err(|err| err.to_string())?;
-    if output.status.success() {
-        Ok(())
+fn git_pull(repo_dir: &Path) -> Result<(), String> {
+    ensure_writable(repo_dir).map_err(|err| err.to_string())?;
+    run_git(git_in(repo_dir, github_token().as_deref()).args(["pull", "--ff-only"]))
+}
+
+/// How long ago the cached checkout last fetched from the remote: the mtime of `FETCH_HEAD`,
+/// or of `HEAD` for a checkout that has only ever been cloned.
+fn last_sync_age(repo_dir: &Path) -> Option<String> {
+    let git_dir = repo_dir.join(".git");
+    let elapsed = ["FETCH_HEAD", "HEAD"]
+        .iter()
+        .find_map(|name| {
+            fs::metadata(git_dir.join(name))
+                .and_then(|meta| meta.modified())
+                .ok()
+        })?
+        .elapsed()
+        .ok()?
+        .as_secs();
+    let (count, unit) = match elapsed {
+        0..=59 => return Some("just now".to_string()),
+        60..=3599 => (elapsed / 60, "minute"),
+        3600..=86399 => (elapsed / 3600, "hour"),
+        _ => (elapsed / 86400, "day"),
+    };
+    Some(format!(
+        "{} {}{} ago",
+        count,
+        unit,
+        if count == 1 { "" } else { "s" }
+    ))
+}
+
+/// Fetches into `<name>.partial` next to `repo_dir` and renames it into place, so another
+/// instance never observes a half-written checkout. The staging checkout survives a failed
+/// fetch, so the next launch only downloads what it is still missing instead of starting over.
+/// Callers hold the `UpdateLock`.
+fn git_clone_atomic(url: &str, repo_dir: &Path, sleep: impl FnMut(Duration)) -> Result<(), String> {
+    let file_name = repo_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("content");
+    let staging = repo_dir.with_file_name(format!("{}.partial", file_name));
+    ensure_writable(&staging).map_err(|err| err.to_string())?;
+    let token = github_token();
+    let git = |args: &[&str]| run_git(git_in(&staging, token.as_deref()).args(args));
+
+    if !staging.join(".git").is_dir() {
+        let _ = remove_dir_all(&staging);
+        create_dirs(&staging).map_err(|err| err.to_string())?;
+        git(&["init", "-q"])?;
+        git(&["remote", "add", "origin", url])?;
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        git(&["remote", "set-url", "origin", url])?;
+    }
+    retry_rate_limited(|| git(&["fetch", "--depth", "1", "origin", "HEAD"]), sleep)?;
+    // A local branch tracking the remote's HEAD, so `git pull` works on the result.
+    git(&["checkout", "-q", "-B", "content", "FETCH_HEAD"])?;
+    git(&["config", "branch.content.remote", "origin"])?;
+    git(&["config", "branch.content.merge", "HEAD"])?;
+
+    match rename(&staging, repo_dir) {
+        Ok(()) => Ok(()),
+        Err(_) if repo_dir.join(".git").is_dir() => {
+            let _ = remove_dir_all(&staging);
+            Ok(())
+        }
+        Err(err) => Err(err.to_string()),
     }
 }
 
+/// `--clone <git-url>` and `:clone`: shallow-clones someone's content repo into the data
+/// directory and records it as `[content] dir` in the config, so later launches use it without
+/// any flags. Without `progress`, git draws its progress on the terminal and may prompt for
+/// credentials there; with it, git's progress lines go to `progress` and it never prompts.
+fn clone_content(url: &str, progress: Option<&mut dyn FnMut(&str)>) -> Result<PathBuf, String> {
+    check_capability(Capability::Network)?;
+    check_capability(Capability::WriteState)?;
+    if !git_available() {
+        return Err("git not found; install git to clone content.".to_string());
+    }
+    let target = data_dir()
+        .map(|dir| dir.join("content"))
+        .ok_or("Cannot find a data directory; set XDG_DATA_HOME or HOME.")?;
+    let occupied = fs::read_dir(&target).is_ok_and(|mut entries| entries.next().is_some());
+    if occupied {
+        return Err(format!(
+            "{} already exists and is not empty. Remove it first, or point JOHNJEONG_CONTENT_DIR at it.",
+            target.display()
+        ));
+    }
+    if let Some(parent) = target.parent() {
+        create_dirs(parent)
+            .map_err(|err| format!("Cannot create {} ({})", parent.display(), err))?;
+    }
+    ensure_writable(&target).map_err(|err| err.to_string())?;
+
+    let mut command = Command::new("git");
+    command
+        .args(["clone", "--depth", "1", "--progress"])
+        .arg(url)
+        .arg(&target);
+    let status = match progress {
+        None => command.stdin(Stdio::inherit()).status(),
+        Some(progress) => command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .spawn()
+            .and_then(|mut child| {
+                if let Some(stderr) = child.stderr.take() {
+                    progress_lines(stderr, progress);
+                }
+                child.wait()
+            }),
+    }
+    .map_err(|err| format!("Failed to run git ({})", err))?;
+    if !status.success() {
+        let _ = remove_dir_all(&target);
+        return Err(format!(
+            "git clone failed. Check that {} exists and that you have access to it.",
+            url
+        ));
+    }
+
+    set_config_value("content", "dir", &target.to_string_lossy())
+        .map_err(|err| format!("Cloned, but could not update the config ({})", err))?;
+    Ok(target)
+}
+
+/// Calls `each` with every line git writes, splitting on `\r` as well as `\n` since progress
+/// redraws one line in place.
+fn progress_lines(reader: impl Read, each: &mut dyn FnMut(&str)) {
+    let mut line = Vec::new();
+    for byte in BufReader::new(reader).bytes() {
+        let Ok(byte) = byte else {
+            break;
+        };
+        if byte == b'\r' || byte == b'\n' {
+            let text = String::from_utf8_lossy(&line);
+            if !text.trim().is_empty() {
+                each(text.trim_end());
+            }
+            line.clear();
+        } else {
+            line.push(byte);
+        }
+    }
+    let text = String::from_utf8_lossy(&line);
+    if !text.trim().is_empty() {
+        each(text.trim_end());
+    }
+}
+
+fn data_dir() -> Option<PathBuf> {
+    if let Ok(path) = env::var("XDG_DATA_HOME") {
+        return Some(PathBuf::from(path).join("johnjeong"));
+    }
+    let home = PathBuf::from(env::var("HOME").ok()?);
+    Some(home.join(".local").join("share").join("johnjeong"))
+}
+
 fn render_gallery_preview(
     stdout: &mut Stdout,
     state: &mut AppState,
@@ -125,23 +428,50 @@ fn render_gallery_preview(
     content_width: usize,
     rows: u16,
 ) -> io::Result<()> {
-    let path = Path::new(&post.url);
+    let path = post.file.as_deref().unwrap_or(Path::new(&post.url));
     if !path.is_file() {
         queue!(
             stdout,
             MoveTo(content_x, content_y),
-            SetForegroundColor(Color::DarkGrey),
+            SetForegroundColor(state.theme.dim),
             Print("Image not found."),
             ResetColor
         )?;
         return Ok(());
     }
 
+    let mut content_y = content_y;
+    if let Some(location) = post.fields.get("location") {
+        queue!(
+            stdout,
+            MoveTo(content_x, content_y),
+            SetForegroundColor(state.theme.dim),
+            Print(clamp_text(&format!("📍 {}", location), content_width)),
+            ResetColor
+        )?;
+        content_y += 1;
+    }
+
     let available = rows.saturating_sub(content_y + 2) as u32;
     if content_width == 0 || available == 0 {
         return Ok(());
     }
 
+    let protocol = graphics().0;
+    if protocol == Graphics::Off {
+        queue!(
+            stdout,
+            MoveTo(content_x, content_y),
+            SetForegroundColor(state.theme.dim),
+            Print(clamp_text(
+                "Image previews are off (--graphics=off).",
+                content_width
+            )),
+            ResetColor
+        )?;
+        return Ok(());
+    }
+
     stdout.flush()?;
     let config = ViuConfig {
         x: content_x,
@@ -150,6 +480,8 @@ fn render_gallery_preview(
         height: Some(available),
         transparent: true,
         restore_cursor: true,
+        use_kitty: protocol == Graphics::Kitty,
+        use_iterm: protocol == Graphics::Iterm,
         ..Default::default()
     };
 
@@ -158,7 +490,7 @@ fn render_gallery_preview(
         queue!(
             stdout,
             MoveTo(content_x, content_y),
-            SetForegroundColor(Color::DarkGrey),
+            SetForegroundColor(state.theme.dim),
             Print("Image preview unavailable."),
             ResetColor
         )?;
@@ -172,23 +504,92 @@ struct AboutData {
     links: &'static [Link],
 }
 
+#[derive(Clone)]
 struct Post {
     title: String,
     date: String,
+    tags: Vec<String>,
+    /// Raw frontmatter, for config `sort`/`filter` expressions over arbitrary keys.
+    fields: HashMap<String, String>,
     body: String,
     url: String,
+    /// The URL's last segment: the frontmatter `slug` or the slugified file name, and a gallery
+    /// image's file name without its extension.
+    slug: String,
     sort_key: String,
+    /// Directory of the post's file, for resolving relative image paths.
+    dir: Option<PathBuf>,
+    /// The file the entry came from: the markdown for posts, for `e`; the image for gallery
+    /// entries, made absolute at load. A gallery `url` holds the same path as text, but
+    /// opening and previewing go through this.
+    file: Option<PathBuf>,
+    /// Set when the file wasn't UTF-8.
+    decoding: Option<Decoding>,
+    /// Whitespace-separated words in the body, counted once at load; 0 for gallery images.
+    words: usize,
+    /// An essay whose frontmatter says it isn't `published`. Subcommands leave drafts out unless
+    /// `--drafts` is given; the app keeps them and hides them until `D` shows them.
+    draft: bool,
+}
+
+/// After a draft's title in the app's lists.
+const DRAFT_SUFFIX: &str = " [draft]";
+
+/// Words per minute behind the "min read" estimates.
+const READING_WPM: usize = 200;
+
+impl Post {
+    /// Estimated minutes to read the body, rounded up.
+    fn reading_minutes(&self) -> usize {
+        self.words.div_ceil(READING_WPM)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Decoding {
+    /// Converted to UTF-8 at load time from the named encoding.
+    Transcoded(&'static str),
+    /// Not UTF-8 and no encoding could be told with confidence; the body is a placeholder.
+    Undecodable,
 }
 
 struct ContentTab {
     name: &'static str,
-    description: &'static str,
+    dir: &'static str,
+    web_url: Option<String>,
+    local_dir: Option<PathBuf>,
+    description: String,
+    /// Every post, drafts and all; lookups by slug search this.
     posts: Vec<Post>,
+    /// The `[tabs.<name>]` filter, applied when listing rather than at load so `cat`, `open`,
+    /// `--post`, and `D` still find a post it hides.
+    filter: Option<PostFilter>,
+}
+
+impl ContentTab {
+    /// The posts the tab lists, before the search query and tag filter narrow them.
+    fn listed_posts(&self) -> impl Iterator<Item = &Post> + Clone {
+        self.posts.iter().filter(|post| self.lists(post))
+    }
+
+    fn lists(&self, post: &Post) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(post))
+    }
 }
 
 enum TabData {
     About(AboutData),
     Content(ContentTab),
+    Tags(TagsTab),
+}
+
+/// Every tag across the writing tabs. The list shows tags until one is chosen (`tag_filter`);
+/// then `posts` filtered by that tag is shown exactly like a content tab.
+struct TagsTab {
+    tags: Vec<(String, usize)>,
+    posts: ContentTab,
 }
 
 struct HeaderData {
@@ -199,6 +600,37 @@ struct HeaderData {
 struct AppData {
     header: HeaderData,
     tabs: Vec<TabData>,
+    link_status: HashMap<String, LinkStatus>,
+    /// The gallery was listed by filename and its dates are still being looked up.
+    gallery_dates_pending: bool,
+}
+
+/// Result of the last `--check-links` run for one URL.
+#[derive(Clone)]
+struct LinkStatus {
+    code: u16,
+    checked_at: u64,
+    location: Option<String>,
+}
+
+impl LinkStatus {
+    fn is_dead(&self) -> bool {
+        matches!(self.code, 404 | 410)
+    }
+
+    fn moved_to(&self) -> Option<&str> {
+        if matches!(self.code, 301 | 308) {
+            self.location.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    List,
+    Content,
 }
 
 struct AppState {
@@ -207,7 +639,283 @@ struct AppState {
     list_scroll: usize,
     content_scroll: usize,
     content_scroll_max: usize,
+    content_height: usize,
+    footnote_jumps: Vec<(usize, usize)>,
+    footnote_return: Option<usize>,
+    match_lines: Vec<usize>,
+    query: String,
+    search: Option<SearchBackup>,
+    picker: Option<Picker>,
+    gallery_peek: Option<Picker>,
+    tag_filter: Option<String>,
+    tag_picker: Option<TagPicker>,
+    /// A link waiting for `y` in `--restricted` mode.
+    confirm_url: Option<String>,
+    high_contrast: bool,
+    /// Whether the lists include drafts; `D` toggles it. Shown drafts skip the tab's
+    /// `[tabs.<name>]` filter, so one that hides unpublished posts doesn't hide them from `D`.
+    show_drafts: bool,
+    /// The URL of the post `--post` started on when its tab's filter hides it; it stays listed.
+    revealed: Option<String>,
+    theme: Theme,
+    /// Which pane `j`/`k` and the arrows act on; `w` switches.
+    focus: Focus,
+    /// Highlighted row of the `S` share menu while it is open.
+    share_menu: Option<usize>,
+    link_list: Option<LinkList>,
+    /// The `:` prompt while it is open.
+    command: Option<LineEditor>,
+    /// Commands run from the `:` prompt, oldest first, and which one ↑/↓ has recalled.
+    command_history: Vec<String>,
+    command_recall: Option<usize>,
+    /// `command_problem` for each `:` line the IO worker has checked since the prompt opened.
+    command_checks: HashMap<String, Option<String>>,
+    timer: Option<ReadingTimer>,
+    /// Summary shown once a reading session ends, until any key.
+    timer_done: Option<String>,
+    /// Step of the onboarding tour on screen, from the first run or `:tour`.
+    tour: Option<usize>,
+    reads: ReadLog,
+    /// URL of the post whose changes `d` is showing in place of its body.
+    diff_view: Option<String>,
+    /// Files edited with `e` and when the editor closed; see `OWN_EDIT_GRACE`.
+    own_edits: HashMap<PathBuf, Instant>,
+    /// Watcher changes waiting to go out as one notification.
+    notice: Option<Notice>,
+    /// Clickable regions of the last frame.
+    hits: HitMap,
+    /// First key of a possible two-key sequence (`gg`) and when it was pressed.
+    pending_key: Option<(char, Instant)>,
     status: Option<String>,
+    /// What a running job last reported; shown in place of `status` while set.
+    progress: Option<String>,
+    /// When an action last changed `status`; it clears `STATUS_TIMEOUT` later. Warnings from
+    /// startup, like a missing content directory, have none and stay until replaced.
+    status_at: Option<Instant>,
+    /// Recent status messages, newest last, for the `m` overlay.
+    status_log: VecDeque<(Instant, String)>,
+    /// Scroll offset of the `m` overlay while it is open.
+    status_history: Option<usize>,
+    /// Scroll offset of the `?` overlay while it is open.
+    help: Option<usize>,
+    /// The `Q` overlay: a post's URL and its QR code.
+    qr: Option<(String, QrCode)>,
+    /// Set by a key that needs the terminal to itself; `run_app` runs it after the key.
+    hand_off: Option<HandOff>,
+    /// The file the last save went to, so pressing save again soon after may overwrite it.
+    save_pending: Option<(PathBuf, Instant)>,
+    keys: Keymap,
+}
+
+impl AppState {
+    /// The first tab's first post, nothing open, and no status.
+    fn new(theme: Theme, keys: Keymap) -> AppState {
+        AppState {
+            tab_index: 0,
+            list_index: 0,
+            list_scroll: 0,
+            content_scroll: 0,
+            content_scroll_max: 0,
+            content_height: 0,
+            footnote_jumps: Vec::new(),
+            footnote_return: None,
+            match_lines: Vec::new(),
+            query: String::new(),
+            search: None,
+            picker: None,
+            gallery_peek: None,
+            tag_filter: None,
+            tag_picker: None,
+            confirm_url: None,
+            high_contrast: false,
+            show_drafts: false,
+            revealed: None,
+            theme,
+            focus: Focus::List,
+            share_menu: None,
+            link_list: None,
+            command: None,
+            command_history: Vec::new(),
+            command_recall: None,
+            command_checks: HashMap::new(),
+            timer: None,
+            timer_done: None,
+            tour: None,
+            reads: ReadLog::default(),
+            diff_view: None,
+            own_edits: HashMap::new(),
+            notice: None,
+            hits: HitMap::default(),
+            pending_key: None,
+            status: None,
+            progress: None,
+            status_at: None,
+            status_log: VecDeque::new(),
+            status_history: None,
+            help: None,
+            qr: None,
+            hand_off: None,
+            save_pending: None,
+            keys,
+        }
+    }
+}
+
+/// The `T` overlay: tags used in the current tab with their post counts.
+struct TagPicker {
+    tags: Vec<(String, usize)>,
+    selected: usize,
+    scroll: usize,
+}
+
+/// The `L` overlay: every link and local image in the selected post's body.
+struct LinkList {
+    links: Vec<(String, LinkTarget)>,
+    selected: usize,
+    scroll: usize,
+}
+
+#[derive(Clone, PartialEq)]
+enum LinkTarget {
+    Web(String),
+    /// An image that resolved to a file under the content directory.
+    File(PathBuf),
+}
+
+impl LinkTarget {
+    fn display(&self) -> String {
+        match self {
+            LinkTarget::Web(url) => url.clone(),
+            LinkTarget::File(path) => path.display().to_string(),
+        }
+    }
+}
+
+/// The `p` overlay: a fuzzy-ranked list of every post across the content tabs.
+#[derive(Default)]
+struct Picker {
+    query: LineEditor,
+    selected: usize,
+    scroll: usize,
+}
+
+/// Snapshot taken when the `/` prompt opens, so Esc can put the list back exactly. `editor`
+/// is the prompt itself; `AppState::query` mirrors its text.
+struct SearchBackup {
+    query: String,
+    list_index: usize,
+    list_scroll: usize,
+    content_scroll: usize,
+    editor: LineEditor,
+}
+
+/// The text and cursor of a one-line prompt. Every prompt edits through `handle_key` and
+/// `insert`, so they all share the same keys.
+#[derive(Default)]
+struct LineEditor {
+    text: String,
+    /// Byte offset, always on a char boundary.
+    cursor: usize,
+}
+
+impl LineEditor {
+    fn new(text: &str) -> LineEditor {
+        LineEditor {
+            text: text.to_string(),
+            cursor: text.len(),
+        }
+    }
+
+    /// Applies an editing key: ←/→, Home/End and ctrl-a/ctrl-e move; Backspace/Delete,
+    /// ctrl-w (word before the cursor) and ctrl-u (everything) delete; printable characters
+    /// insert. Returns `false` for keys it doesn't use, which the prompt may handle itself.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Left => self.cursor = self.previous_boundary(),
+            KeyCode::Right => self.cursor = self.next_boundary(),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.len(),
+            KeyCode::Char('e') if ctrl => self.cursor = self.text.len(),
+            KeyCode::Backspace => {
+                let start = self.previous_boundary();
+                self.text.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            KeyCode::Delete => {
+                let end = self.next_boundary();
+                self.text.replace_range(self.cursor..end, "");
+            }
+            KeyCode::Char('w') if ctrl => {
+                let before = self.text[..self.cursor].trim_end();
+                let start = before
+                    .rfind(char::is_whitespace)
+                    .map_or(0, |index| index + 1);
+                self.text.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.text.clear();
+                self.cursor = 0;
+            }
+            KeyCode::Char(ch) if !ctrl => self.insert(ch.encode_utf8(&mut [0; 4])),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Inserts text at the cursor as-is, for typing and bracketed paste. Line breaks and tabs
+    /// become spaces and other control characters are dropped, since prompts are one line.
+    fn insert(&mut self, text: &str) {
+        let clean = text
+            .chars()
+            .filter_map(|ch| match ch {
+                '\n' | '\t' => Some(' '),
+                ch if ch.is_control() => None,
+                ch => Some(ch),
+            })
+            .collect::<String>();
+        self.text.insert_str(self.cursor, &clean);
+        self.cursor += clean.len();
+    }
+
+    /// Tab completion. `source` gets the text before the cursor and returns every completion of
+    /// it, whole; that text grows to what they all share. `false` when it can't grow.
+    fn complete(&mut self, source: impl Fn(&str) -> Vec<String>) -> bool {
+        let candidates = source(&self.text[..self.cursor]);
+        let Some(first) = candidates.first() else {
+            return false;
+        };
+        let shared = candidates[1..].iter().fold(first.len(), |shared, other| {
+            first[..shared]
+                .char_indices()
+                .zip(other.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((index, ch), _)| index + ch.len_utf8())
+        });
+        if shared <= self.cursor || !first.starts_with(&self.text[..self.cursor]) {
+            return false;
+        }
+        self.text.replace_range(..self.cursor, &first[..shared]);
+        self.cursor = shared;
+        true
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |ch| self.cursor + ch.len_utf8())
+    }
 }
 
 const ABOUT_LINKS: [Link; 4] = [
@@ -229,925 +937,15416 @@ const ABOUT_LINKS: [Link; 4] = [
     },
 ];
 const CONTENT_REPO_URL: &str = "https://github.com/ComputelessComputer/part-of-my-brain";
+const UPDATE_LOCK_STALE: Duration = Duration::from_secs(600);
+const IDLE_POLL: Duration = Duration::from_secs(3600);
+const AUTOSAVE_EVERY: usize = 20;
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(10);
+const SESSION_HEARTBEAT: Duration = Duration::from_secs(10);
+const SESSION_LOCK_STALE: Duration = Duration::from_secs(30);
+const CONTENT_DIRS: [&str; 5] = ["essays", "journals", "inspirations", "lessons", "gallery"];
 
 fn main() -> io::Result<()> {
-    let args: Vec<String> = env::args().collect();
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(path) = flag_value(&args, "--config") {
+        let _ = CONFIG_PATH.set(expand_path(path));
+    }
+    // Help and version win over anything else on the line, even arguments that don't parse.
     if args.iter().any(|arg| arg == "--help" || arg == "-h") {
-        print_help();
-        return Ok(());
+        return print_help(
+            args.iter()
+                .find_map(|arg| COMMANDS.iter().find(|spec| spec.name == arg)),
+        );
     }
     if args.iter().any(|arg| arg == "--version" || arg == "-V") {
         println!("johnjeong {}", env!("CARGO_PKG_VERSION"));
         return Ok(());
     }
+    let cli = match Cli::parse(&args) {
+        Ok(cli) => cli,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    let mut settings = match Config::load() {
+        Ok(settings) => settings,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+    let _ = RESTRICTED.set(cli.options.has("--restricted"));
+    let _ = READ_ONLY.set(cli.options.has("--read-only") || settings.read_only);
+    let _ = REDUCED_MOTION.set(settings.reduced_motion);
+    let _ = HYPERLINKS.set(
+        !restricted()
+            && color_enabled()
+            && settings
+                .hyperlinks
+                .unwrap_or_else(terminal_supports_hyperlinks),
+    );
+    let _ = OSC52.set(settings.osc52.unwrap_or_else(terminal_supports_osc52));
+    let _ = BROWSER.set(settings.browser.clone());
+    let _ = NOTIFY.set(settings.notify);
+    if let Some(value) = cli.options.value("--graphics") {
+        match Graphics::parse_override(&value) {
+            Some(choice) => {
+                let _ = GRAPHICS_OVERRIDE.set(choice);
+            }
+            None => {
+                eprintln!(
+                    "Unknown --graphics value \"{}\" (use auto, kitty, iterm, sixel, blocks, or off).",
+                    value
+                );
+                std::process::exit(2);
+            }
+        }
+    }
+    let theme_preset = cli.options.value("--theme");
+    if let Some(name) = theme_preset
+        .as_deref()
+        .filter(|name| Theme::preset(name).is_none())
+    {
+        eprintln!(
+            "Unknown --theme value \"{}\" (use default, light, or mono).",
+            name
+        );
+        std::process::exit(2);
+    }
+    if let Some(dir) = cli.options.value("--content-dir") {
+        match content_dir_flag(&dir) {
+            Ok(path) => settings.content_dir = Some(path),
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(2);
+            }
+        }
+    }
+    settings.drafts = cli.options.has("--drafts");
+    if let Some(command) = cli.command {
+        // `johnjeong export | head` closes the pipe early; that's not an error.
+        return match run_subcommand(command, &settings) {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        };
+    }
+
+    if let Some(url) = cli.options.value("--clone") {
+        match clone_content(&url, None) {
+            Ok(path) => {
+                eprintln!(
+                    "Cloned into {}; it is now your content directory.",
+                    path.display()
+                );
+                settings.content_dir = Some(path);
+            }
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    let (data, status) = build_app_data();
+    // Piped or scripted: a listing instead of raw mode and escape codes in the pipe.
+    if cli.options.has("--plain") || !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        let (data, status) = build_app_data(&settings);
+        return run_plain(&data, status.as_deref());
+    }
+
+    let keys = match Keymap::from_config(config().get("keys")) {
+        Ok(keys) => keys,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+    let (theme, theme_warning) = Theme::from_config(config().get("theme"), theme_preset.as_deref());
+    // The app loads drafts either way and hides them itself, so `D` can show them at any time.
+    let show_drafts = settings.drafts;
+    settings.drafts = true;
+    let (mut data, status) = build_app_data(&settings);
+    let status = status.or(theme_warning);
+    let no_content = !data
+        .tabs
+        .iter()
+        .any(|tab| matches!(tab, TabData::Content(tab) if tab.local_dir.is_some()));
+    let _ = SETTINGS.set(settings.clone());
+    // Every color goes out through crossterm, so this one switch silences all of it.
+    crossterm::style::force_color_output(theme.color);
     let mut state = AppState {
-        tab_index: 0,
-        list_index: 0,
-        list_scroll: 0,
-        content_scroll: 0,
-        content_scroll_max: 0,
+        high_contrast: settings.high_contrast,
+        show_drafts,
         status,
+        ..AppState::new(theme, keys)
     };
+    // Nothing to show: ask where the content is rather than opening on empty tabs.
+    if no_content {
+        state.command = Some(LineEditor::new("content "));
+    }
 
-    let mut stdout = io::stdout();
-    terminal::enable_raw_mode()?;
-    execute!(stdout, EnterAlternateScreen, Hide)?;
-
-    let result = run_app(&mut stdout, &data, &mut state);
+    let (events, inbox) = mpsc::channel();
+    start_io_worker(events.clone());
+    if settings.watch {
+        let dirs = data
+            .tabs
+            .iter()
+            .filter_map(|tab| match tab {
+                TabData::Content(tab) if !is_gallery_tab(tab) => Some(WatchedDir {
+                    dir: tab.local_dir.clone()?,
+                    base_url: tab.web_url.clone()?,
+                }),
+                _ => None,
+            })
+            .collect();
+        start_content_watcher(dirs, events.clone());
+    }
+    let store = check_capability(Capability::WriteState)
+        .ok()
+        .and_then(|()| SessionStore::open());
+    // First run: nothing saved yet and the tour never finished. Without a writable state
+    // directory there is no way to remember it was seen, so it only runs from `:tour`.
+    if store.as_ref().is_some_and(|store| !store.path.exists())
+        && tour_path().is_some_and(|path| !path.exists())
+    {
+        state.tour = Some(0);
+    }
+    let mut autosave = store.map(|store| {
+        let crashed = store.previous_run_crashed();
+        if let Some(session) = store.load() {
+            session.restore(&data, &mut state);
+            if crashed {
+                state.status = Some("Restored autosaved session.".to_string());
+            }
+        }
+        Autosave::start(store, events.clone())
+    });
+    // Asked for on the command line or in the config, so it wins over the restored session.
+    let start_tab = cli.options.value("--tab").or(settings.start_tab.clone());
+    let start_post = cli.options.value("--post");
+    if start_tab.is_some() || start_post.is_some() {
+        // Switching tabs clears the status line; a load problem should still show.
+        let load_status = state.status.take();
+        select_start(
+            &data,
+            &mut state,
+            start_tab.as_deref(),
+            start_post.as_deref(),
+        );
+        state.status = state.status.take().or(load_status);
+    }
+    if data.gallery_dates_pending {
+        let paths = data
+            .tabs
+            .iter()
+            .filter_map(|tab| match tab {
+                TabData::Content(tab) if is_gallery_tab(tab) => Some(tab),
+                _ => None,
+            })
+            .flat_map(|tab| tab.posts.iter().filter_map(|post| post.file.clone()))
+            .collect();
+        backfill_gallery_dates(paths, events.clone());
+        state
+            .status
+            .get_or_insert_with(|| GALLERY_DATES_LOADING.to_string());
+    }
 
-    terminal::disable_raw_mode()?;
-    execute!(stdout, Show, LeaveAlternateScreen)?;
+    graphics();
+    config();
+    let mouse = !cli.options.has("--no-mouse");
+    let alt_screen = !cli.options.has("--no-alt-screen");
+    let mut stdout = io::stdout();
+    // Restore before the default hook prints, so the panic message lands on the normal screen
+    // instead of vanishing with the alternate one.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal(mouse, alt_screen);
+        default_hook(info);
+    }));
+    watch_resume();
+    let result = TerminalGuard::enter(&mut stdout, mouse, alt_screen).and_then(|terminal| {
+        run_app(
+            &mut stdout,
+            &terminal,
+            &mut data,
+            &mut state,
+            &mut autosave,
+            &inbox,
+        )
+    });
+    if let Some(autosave) = autosave {
+        autosave.finish(Session::capture(&data, &state));
+    }
 
+    if !alt_screen && result.is_ok() {
+        print_inline_summary(&data, &state);
+    }
     result
 }
 
-fn build_app_data() -> (AppData, Option<String>) {
-    let header = load_header_data();
-    let mut status = None;
-    let content_root = resolve_content_root(&mut status);
+/// Raw mode, the alternate screen, bracketed paste and mouse capture for as long as the UI runs.
+/// Dropping it restores the terminal, so an early `?` out of `run_app` (or out of a half-finished
+/// `enter`) can't leave the shell unusable; panics are covered by the hook installed in `main`.
+struct TerminalGuard {
+    mouse: bool,
+    alt_screen: bool,
+}
 
-    let essays = content_root
-        .as_ref()
-        .map(|root| root.join("essays"))
-        .and_then(|dir| load_posts(&dir, "https://johnjeong.com/essays", true).ok())
-        .unwrap_or_else(|| {
-            status.get_or_insert_with(|| {
-                "Content directory not found. Set JOHNJEONG_CONTENT_DIR or install git to fetch content."
-                    .to_string()
-            });
-            Vec::new()
-        });
+impl TerminalGuard {
+    fn enter(stdout: &mut Stdout, mouse: bool, alt_screen: bool) -> io::Result<Self> {
+        let guard = TerminalGuard { mouse, alt_screen };
+        guard.setup(stdout)?;
+        Ok(guard)
+    }
 
-    let journals = content_root
-        .as_ref()
-        .map(|root| root.join("journals"))
-        .and_then(|dir| load_posts(&dir, "https://johnjeong.com/journals", false).ok())
-        .unwrap_or_default();
+    fn setup(&self, stdout: &mut Stdout) -> io::Result<()> {
+        terminal::enable_raw_mode()?;
+        if self.alt_screen {
+            execute!(stdout, EnterAlternateScreen)?;
+        } else {
+            reserve_viewport(stdout)?;
+        }
+        execute!(stdout, Hide, EnableBracketedPaste)?;
+        if self.mouse {
+            execute!(stdout, EnableMouseCapture)?;
+        }
+        Ok(())
+    }
 
-    let inspirations = content_root
-        .as_ref()
-        .map(|root| root.join("inspirations"))
-        .and_then(|dir| load_posts(&dir, "https://johnjeong.com/inspirations", false).ok())
-        .unwrap_or_default();
+    /// Sets the terminal up again after the process was stopped and continued. The shell may
+    /// have put the tty back in cooked mode while we were stopped, and crossterm skips
+    /// `enable_raw_mode` when it thinks raw mode is still on, so it's disabled first.
+    fn resume(&self, stdout: &mut Stdout) -> io::Result<()> {
+        terminal::disable_raw_mode()?;
+        self.setup(stdout)
+    }
 
-    let lessons = content_root
-        .as_ref()
-        .map(|root| root.join("lessons"))
-        .and_then(|dir| load_posts(&dir, "https://johnjeong.com/lessons", false).ok())
-        .unwrap_or_default();
+    /// Ctrl-Z: hands the terminal back and stops the whole process group, as the shell's job
+    /// control expects. `kill` returns once we're continued with `fg`.
+    #[cfg(unix)]
+    fn suspend(&self, stdout: &mut Stdout) -> io::Result<()> {
+        restore_terminal(self.mouse, self.alt_screen);
+        // SAFETY: signalling our own process group has no memory-safety preconditions.
+        unsafe {
+            libc::kill(0, libc::SIGTSTP);
+        }
+        RESUMED.store(false, Ordering::SeqCst);
+        self.resume(stdout)
+    }
+
+    /// Gives the terminal back for `run`, like a shell would for a child, then takes it again.
+    /// The outer error is from setting the terminal up again; the inner one is `run`'s.
+    fn run_outside<T>(
+        &self,
+        stdout: &mut Stdout,
+        run: impl FnOnce() -> io::Result<T>,
+    ) -> io::Result<io::Result<T>> {
+        restore_terminal(self.mouse, self.alt_screen);
+        let result = run();
+        self.resume(stdout)?;
+        Ok(result)
+    }
+
+    /// There's no job control to hand off to on Windows, so Ctrl-Z stays a no-op.
+    #[cfg(not(unix))]
+    fn suspend(&self, _stdout: &mut Stdout) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal(self.mouse, self.alt_screen);
+    }
+}
+
+/// Set by the SIGCONT handler; `run_app` sets the terminal up again and redraws when it sees it.
+/// Covers being stopped from outside (`kill -STOP`), where nothing restored the terminal first.
+static RESUMED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+fn watch_resume() {
+    extern "C" fn on_continue(_: libc::c_int) {
+        RESUMED.store(true, Ordering::SeqCst);
+    }
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        libc::signal(
+            libc::SIGCONT,
+            on_continue as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn watch_resume() {}
+
+fn is_suspend_key(event: &AppEvent) -> bool {
+    matches!(
+        event,
+        AppEvent::Key(KeyEvent {
+            code: KeyCode::Char('z'),
+            modifiers,
+            ..
+        }) if modifiers.contains(KeyModifiers::CONTROL)
+    )
+}
+
+/// Undoes the terminal setup in `main`. Also run from the panic hook, so a crash doesn't leave
+/// the shell in raw mode with the mouse captured; errors are ignored since it's best effort.
+fn restore_terminal(mouse: bool, alt_screen: bool) {
+    let mut stdout = io::stdout();
+    if mouse {
+        let _ = execute!(stdout, DisableMouseCapture);
+    }
+    let _ = terminal::disable_raw_mode();
+    let _ = execute!(stdout, DisableBracketedPaste, Show);
+    if alt_screen {
+        let _ = execute!(stdout, LeaveAlternateScreen);
+    }
+}
+
+/// For `--no-alt-screen`: scrolls everything above the cursor into scrollback so the UI can draw
+/// on the whole viewport, anchored at row 0. Newlines rather than `ScrollUp`, since terminals
+/// only keep lines that scroll off by output. Anchoring at the top means a shrinking resize just
+/// redraws from row 0 instead of pushing the shell prompt out of view.
+fn reserve_viewport(stdout: &mut Stdout) -> io::Result<()> {
+    let (_, rows) = terminal::size()?;
+    stdout.write_all("\r\n".repeat(rows.saturating_sub(1) as usize).as_bytes())?;
+    execute!(stdout, MoveTo(0, 0))
+}
+
+/// Leaves the last frame in scrollback after `--no-alt-screen` and prints the selected post below
+/// it, so the title and URL are still there once the shell prompt comes back.
+fn print_inline_summary(data: &AppData, state: &AppState) {
+    let rows = terminal::size().map_or(0, |(_, rows)| rows);
+    let _ = execute!(io::stdout(), MoveTo(0, rows.saturating_sub(1)));
+    println!();
+    if let Some(post) = selected_post(data, state) {
+        println!("{}", post.title);
+        println!("{}", post.url);
+    }
+}
+
+/// Everything the app and the subcommands show. Unpublished essays are only kept with
+/// `config.drafts`.
+fn build_app_data(config: &Config) -> (AppData, Option<String>) {
+    let header = load_header_data(config);
+    let mut status = None;
+    let content_root = resolve_content_root(config, &mut status);
+    let site = &config.site_url;
+    let web_url = |dir: &str| format!("{}/{}", site, dir);
+    let local_dir = |dir: &str| {
+        content_root
+            .as_ref()
+            .map(|root| root.join(dir))
+            .filter(|path| path.is_dir())
+    };
+
+    let mut essays = content_root
+        .as_ref()
+        .map(|root| root.join("essays"))
+        .and_then(|dir| load_posts(&dir, &web_url("essays"), true).ok())
+        .unwrap_or_else(|| {
+            status.get_or_insert_with(|| {
+                "Content directory not found. Set JOHNJEONG_CONTENT_DIR or install git to fetch content."
+                    .to_string()
+            });
+            Vec::new()
+        });
+    if !config.drafts {
+        essays.retain(|post| !post.draft);
+    }
+
+    let journals = content_root
+        .as_ref()
+        .map(|root| root.join("journals"))
+        .and_then(|dir| load_posts(&dir, &web_url("journals"), false).ok())
+        .unwrap_or_default();
+
+    let inspirations = content_root
+        .as_ref()
+        .map(|root| root.join("inspirations"))
+        .and_then(|dir| load_posts(&dir, &web_url("inspirations"), false).ok())
+        .unwrap_or_default();
+
+    let lessons = content_root
+        .as_ref()
+        .map(|root| root.join("lessons"))
+        .and_then(|dir| load_posts(&dir, &web_url("lessons"), false).ok())
+        .unwrap_or_default();
 
-    let gallery = content_root
+    let (gallery, gallery_dates_pending) = content_root
         .as_ref()
         .map(|root| root.join("gallery"))
-        .and_then(|dir| load_gallery(&dir).ok())
+        .and_then(|dir| load_gallery(&dir, config.gallery_dates).ok())
         .unwrap_or_default();
 
-    let tabs = vec![
+    let describe = |dir: &str, fallback: &str| {
+        content_root
+            .as_ref()
+            .and_then(|root| load_tab_description(&root.join(dir)))
+            .unwrap_or_else(|| fallback.to_string())
+    };
+
+    let mut tabs = vec![
         TabData::About(AboutData {
             tagline: "I like simple & intuitive stuff.",
             links: &ABOUT_LINKS,
         }),
         TabData::Content(ContentTab {
             name: "Essays",
-            description: "Long-form writing.",
+            dir: "essays",
+            web_url: Some(web_url("essays")),
+            local_dir: local_dir("essays"),
+            description: describe("essays", "Long-form writing."),
             posts: essays,
+            filter: None,
         }),
         TabData::Content(ContentTab {
             name: "Daily Logs",
-            description: "Daily notes and logs.",
+            dir: "journals",
+            web_url: Some(web_url("journals")),
+            local_dir: local_dir("journals"),
+            description: describe("journals", "Daily notes and logs."),
             posts: journals,
+            filter: None,
         }),
         TabData::Content(ContentTab {
             name: "Inspirations",
-            description: "Talks, podcasts, and ideas that shaped me.",
+            dir: "inspirations",
+            web_url: Some(web_url("inspirations")),
+            local_dir: local_dir("inspirations"),
+            description: describe("inspirations", "Talks, podcasts, and ideas that shaped me."),
             posts: inspirations,
+            filter: None,
         }),
         TabData::Content(ContentTab {
             name: "Lessons",
-            description: "Learning notes and highlights.",
+            dir: "lessons",
+            web_url: Some(web_url("lessons")),
+            local_dir: local_dir("lessons"),
+            description: describe("lessons", "Learning notes and highlights."),
             posts: lessons,
+            filter: None,
         }),
         TabData::Content(ContentTab {
             name: "Gallery",
-            description: "Photos I took.",
+            dir: "gallery",
+            web_url: None,
+            local_dir: local_dir("gallery"),
+            description: describe("gallery", "Photos I took."),
             posts: gallery,
+            filter: None,
         }),
     ];
+    apply_tab_views(&mut tabs, &mut status);
+    tabs.push(TabData::Tags(tags_tab(&tabs)));
 
-    (AppData { header, tabs }, status)
+    let link_status = load_link_status(config.link_max_age_days);
+    (
+        AppData {
+            header,
+            tabs,
+            link_status,
+            gallery_dates_pending,
+        },
+        status,
+    )
 }
 
-fn run_app(stdout: &mut Stdout, data: &AppData, state: &mut AppState) -> io::Result<()> {
-    let mut needs_redraw = true;
-
-    loop {
-        if needs_redraw {
-            render(stdout, data, state)?;
-            needs_redraw = false;
-        }
-
-        if event::poll(Duration::from_millis(200))? {
-            match event::read()? {
-                Event::Key(key) => {
-                    if handle_key(key, data, state)? {
-                        break;
-                    }
-                    needs_redraw = true;
-                }
-                Event::Resize(_, _) => {
-                    needs_redraw = true;
-                }
-                _ => {}
-            }
-        }
+fn tags_tab(tabs: &[TabData]) -> TagsTab {
+    let writing = tabs.iter().filter_map(|tab| match tab {
+        TabData::Content(tab) if !is_gallery_tab(tab) => Some(tab),
+        _ => None,
+    });
+    let tags = tag_counts(writing.clone());
+    let mut posts = writing
+        .flat_map(|tab| tab.listed_posts().filter(|post| !post.tags.is_empty()))
+        .cloned()
+        .collect::<Vec<_>>();
+    posts.sort_by(|a, b| b.sort_key.cmp(&a.sort_key).then_with(|| a.url.cmp(&b.url)));
+    TagsTab {
+        tags,
+        posts: ContentTab {
+            name: "Tags",
+            dir: "",
+            web_url: None,
+            local_dir: None,
+            description: "Essays, logs, inspirations, and lessons by tag.".to_string(),
+            posts,
+            filter: None,
+        },
     }
-
-    Ok(())
 }
 
-fn handle_key(key: KeyEvent, data: &AppData, state: &mut AppState) -> io::Result<bool> {
-    match key.code {
-        KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(true),
-        KeyCode::Char('1') => switch_tab(state, 0, data.tabs.len()),
-        KeyCode::Char('2') => switch_tab(state, 1, data.tabs.len()),
-        KeyCode::Char('3') => switch_tab(state, 2, data.tabs.len()),
-        KeyCode::Char('4') => switch_tab(state, 3, data.tabs.len()),
-        KeyCode::Char('5') => switch_tab(state, 4, data.tabs.len()),
-        KeyCode::Char('6') => switch_tab(state, 5, data.tabs.len()),
-        KeyCode::Char('g') => switch_tab(state, 5, data.tabs.len()),
-        KeyCode::Up | KeyCode::Char('k') => move_selection(data, state, -1),
-        KeyCode::Down | KeyCode::Char('j') => move_selection(data, state, 1),
-        KeyCode::PageUp => scroll_content(state, -10),
-        KeyCode::PageDown => scroll_content(state, 10),
-        KeyCode::Home | KeyCode::Char('t') => state.content_scroll = 0,
-        KeyCode::Char('G') => state.content_scroll = state.content_scroll_max,
-        KeyCode::Char('o') | KeyCode::Enter => open_selected(data, state),
-        _ => {}
-    }
+/// How an option takes its value: `--flag value` or `--flag=value` for `Required`; for
+/// `Optional`, only when the next argument doesn't start with `-`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OptionValue {
+    Switch,
+    Required(&'static str),
+    Optional(&'static str),
+}
 
-    Ok(false)
+struct OptionSpec {
+    flag: &'static str,
+    short: Option<&'static str>,
+    value: OptionValue,
+    help: &'static str,
 }
 
-fn switch_tab(state: &mut AppState, index: usize, total: usize) {
-    if index >= total {
-        return;
-    }
-    state.tab_index = index;
-    state.list_index = 0;
-    state.list_scroll = 0;
-    state.content_scroll = 0;
-    state.content_scroll_max = 0;
-    state.status = None;
+/// A subcommand: its positional arguments as they read in the usage line, how many it takes,
+/// and its own options. Global options are accepted after it too.
+struct CommandSpec {
+    name: &'static str,
+    positionals: &'static str,
+    min_positionals: usize,
+    max_positionals: Option<usize>,
+    options: &'static [OptionSpec],
+    /// Options that must be given, shown without brackets in the usage line.
+    required: &'static [&'static str],
+    help: &'static str,
 }
 
-fn move_selection(data: &AppData, state: &mut AppState, delta: i32) {
-    let max = list_length(data, state.tab_index);
-    if max == 0 {
-        state.list_index = 0;
-        return;
-    }
-    let len = max as i32;
-    let mut next = state.list_index as i32 + delta;
-    if next < 0 {
-        next = len - 1;
-    } else if next >= len {
-        next = 0;
+const fn switch(flag: &'static str, help: &'static str) -> OptionSpec {
+    OptionSpec {
+        flag,
+        short: None,
+        value: OptionValue::Switch,
+        help,
     }
-    state.list_index = next as usize;
-    state.content_scroll = 0;
-    state.content_scroll_max = 0;
 }
 
-fn scroll_content(state: &mut AppState, delta: i32) {
-    let max = state.content_scroll_max as i32;
-    let mut next = state.content_scroll as i32 + delta;
-    if next < 0 {
-        next = 0;
-    } else if next > max {
-        next = max;
+const fn valued(flag: &'static str, value: &'static str, help: &'static str) -> OptionSpec {
+    OptionSpec {
+        flag,
+        short: None,
+        value: OptionValue::Required(value),
+        help,
     }
-    state.content_scroll = next as usize;
 }
 
-fn open_selected(data: &AppData, state: &mut AppState) {
-    match data.tabs.get(state.tab_index) {
-        Some(TabData::About(about)) => {
-            if let Some(link) = about.links.get(state.list_index) {
-                let result = open_url(link.url);
-                state.status = Some(match result {
-                    Ok(()) => format!("Opened {}", link.label),
-                    Err(err) => format!("Failed to open {} ({})", link.label, err),
-                });
-            }
-        }
-        Some(TabData::Content(tab)) => {
-            if let Some(post) = tab.posts.get(state.list_index) {
-                let result = open_url(&post.url);
-                state.status = Some(match result {
-                    Ok(()) => format!("Opened {}", post.title),
-                    Err(err) => format!("Failed to open {} ({})", post.title, err),
-                });
-            }
-        }
-        None => {}
-    }
+const TAB_OPTION: OptionSpec = valued("--tab", "tab", "only look in this tab");
+const JSON_OPTION: OptionSpec = switch("--json", "print JSON instead of tab-separated lines");
+
+/// Options for the app itself, and the older flag-style commands. They're accepted anywhere on
+/// the command line, before or after a subcommand.
+const GLOBAL_OPTIONS: [OptionSpec; 22] = [
+    OptionSpec {
+        flag: "--help",
+        short: Some("-h"),
+        value: OptionValue::Switch,
+        help: "print this help, or a subcommand's, and exit",
+    },
+    OptionSpec {
+        flag: "--version",
+        short: Some("-V"),
+        value: OptionValue::Switch,
+        help: "print the version and exit",
+    },
+    valued("--config", "path", "read settings from another config file"),
+    valued(
+        "--content-dir",
+        "path",
+        "read content from this directory; it must exist (see Content below)",
+    ),
+    switch(
+        "--drafts",
+        "include unpublished essays, marked [draft] in the app (D toggles them)",
+    ),
+    switch(
+        "--restricted",
+        "browse untrusted content: no writes, no file opening, links open only after confirming the full URL",
+    ),
+    switch(
+        "--read-only",
+        "never write anything: no session, snapshots, or caches",
+    ),
+    switch(
+        "--no-mouse",
+        "leave the mouse to the terminal (no wheel scrolling)",
+    ),
+    switch(
+        "--no-alt-screen",
+        "draw in the normal screen and keep the last frame",
+    ),
+    switch(
+        "--plain",
+        "print every post (tab, date, title, url) and exit; the default when stdin or stdout isn't a terminal",
+    ),
+    valued(
+        "--clone",
+        "git-url",
+        "fetch a content repo and make it the default",
+    ),
+    valued(
+        "--graphics",
+        "auto|kitty|iterm|sixel|blocks|off",
+        "how to draw gallery images",
+    ),
+    valued("--theme", "default|light|mono", "start with a built-in theme"),
+    switch("--doctor", "report what was detected and configured, and exit"),
+    switch("--check-links", "check every link in the posts and exit"),
+    OptionSpec {
+        flag: "--verify-urls",
+        short: None,
+        value: OptionValue::Optional("sitemap-url|file"),
+        help: "check post URLs against the site's sitemap and exit",
+    },
+    valued(
+        "--search",
+        "query",
+        "print one line per matching post and exit",
+    ),
+    valued(
+        "--tab",
+        "tab",
+        "start on this tab, by name or number (JOHNJEONG_START_TAB); limits --search, --check-links, and --verify-urls to it",
+    ),
+    valued(
+        "--post",
+        "slug",
+        "start with this post selected, by slug, tab/slug, or unique prefix",
+    ),
+    switch("--json", "print --search results as JSON"),
+    OptionSpec {
+        flag: "--complete-slugs",
+        short: None,
+        value: OptionValue::Optional("tab"),
+        help: "print slugs for shell completion and exit",
+    },
+    OptionSpec {
+        flag: "--completions",
+        short: None,
+        value: OptionValue::Optional("bash|zsh|fish"),
+        help: "print a shell completion script and exit",
+    },
+];
+
+const COMMANDS: [CommandSpec; 11] = [
+    CommandSpec {
+        name: "search",
+        positionals: "<query>...",
+        min_positionals: 1,
+        max_positionals: None,
+        options: &[TAB_OPTION, JSON_OPTION],
+        required: &[],
+        help: "print every line of every post that contains the query",
+    },
+    CommandSpec {
+        name: "list",
+        positionals: "[tab]",
+        min_positionals: 0,
+        max_positionals: Some(1),
+        options: &[JSON_OPTION],
+        required: &[],
+        help: "list the tabs with their post counts, or one tab's posts",
+    },
+    CommandSpec {
+        name: "cat",
+        positionals: "<slug>",
+        min_positionals: 1,
+        max_positionals: Some(1),
+        options: &[TAB_OPTION, switch("--url", "print only the post's URL")],
+        required: &[],
+        help: "print a post found by slug or unique prefix",
+    },
+    CommandSpec {
+        name: "open",
+        positionals: "<slug>",
+        min_positionals: 1,
+        max_positionals: Some(1),
+        options: &[TAB_OPTION],
+        required: &[],
+        help: "open a post found by slug or unique prefix in the browser",
+    },
+    CommandSpec {
+        name: "latest",
+        positionals: "[tab]",
+        min_positionals: 0,
+        max_positionals: Some(1),
+        options: &[switch("--url", "print only the post's URL")],
+        required: &[],
+        help: "print the newest post",
+    },
+    CommandSpec {
+        name: "today",
+        positionals: "",
+        min_positionals: 0,
+        max_positionals: Some(0),
+        options: &[switch(
+            "--edit",
+            "open it in $EDITOR, creating it first if needed",
+        )],
+        required: &[],
+        help: "print today's daily log",
+    },
+    CommandSpec {
+        name: "new",
+        positionals: "<essay|journal|inspiration|lesson> <title>...",
+        min_positionals: 2,
+        max_positionals: None,
+        options: &[switch("--edit", "open the new post in $EDITOR")],
+        required: &[],
+        help: "create an unpublished post from a title",
+    },
+    CommandSpec {
+        name: "lint",
+        positionals: "",
+        min_positionals: 0,
+        max_positionals: Some(0),
+        options: &[],
+        required: &[],
+        help: "check every post's frontmatter and body",
+    },
+    CommandSpec {
+        name: "stats",
+        positionals: "",
+        min_positionals: 0,
+        max_positionals: Some(0),
+        options: &[switch("--json", "print JSON")],
+        required: &[],
+        help: "print post counts, word counts, and dates for each tab",
+    },
+    CommandSpec {
+        name: "export",
+        positionals: "",
+        min_positionals: 0,
+        max_positionals: Some(0),
+        options: &[
+            switch("--json", "print JSON (the only format so far)"),
+            switch("--include-body", "include each post's markdown"),
+        ],
+        required: &["--json"],
+        help: "print every tab's posts",
+    },
+    CommandSpec {
+        name: "feed",
+        positionals: "",
+        min_positionals: 0,
+        max_positionals: Some(0),
+        options: &[
+            valued("--tab", "tab", "the tab to build it from (default essays)"),
+            valued("--out", "file", "write it to a file instead of stdout"),
+            switch("--atom", "build Atom instead of RSS 2.0"),
+        ],
+        required: &[],
+        help: "build an RSS or Atom feed of a tab",
+    },
+];
+
+/// What's left to do once global options are applied. No subcommand starts the app.
+enum Subcommand {
+    Doctor,
+    CompleteSlugs {
+        tab: Option<String>,
+    },
+    Completions {
+        shell: Option<String>,
+    },
+    VerifyUrls {
+        source: Option<String>,
+        tab: Option<String>,
+    },
+    CheckLinks {
+        tab: Option<String>,
+    },
+    /// `--search`: one line per matching post.
+    FindPosts {
+        query: String,
+        tab: Option<String>,
+        json: bool,
+    },
+    Search {
+        query: String,
+        tab: Option<String>,
+        json: bool,
+    },
+    List {
+        tab: Option<String>,
+        json: bool,
+    },
+    Cat {
+        slug: String,
+        tab: Option<String>,
+        url: bool,
+    },
+    Open {
+        slug: String,
+        tab: Option<String>,
+    },
+    Latest {
+        tab: Option<String>,
+        url: bool,
+    },
+    Today {
+        edit: bool,
+    },
+    New {
+        kind: String,
+        title: String,
+        edit: bool,
+    },
+    Lint,
+    Stats {
+        json: bool,
+    },
+    Export {
+        include_body: bool,
+    },
+    Feed {
+        tab: String,
+        out: Option<String>,
+        atom: bool,
+    },
 }
 
-fn render(stdout: &mut Stdout, data: &AppData, state: &mut AppState) -> io::Result<()> {
-    let (cols, rows) = terminal::size()?;
-    let max_width = cols.saturating_sub(4) as usize;
-    queue!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+/// The parsed command line.
+struct Cli {
+    options: ParsedOptions,
+    command: Option<Subcommand>,
+}
 
-    let (header_title, header_subtitle) = header_text(data, state);
-    queue!(
-        stdout,
-        MoveTo(2, 1),
-        SetAttribute(Attribute::Bold),
-        Print(header_title),
-        SetAttribute(Attribute::Reset)
-    )?;
+/// Options as given, keyed by their long flag; a repeated option keeps its last value.
+#[derive(Default)]
+struct ParsedOptions {
+    values: HashMap<&'static str, Option<String>>,
+}
 
-    queue!(
-        stdout,
-        MoveTo(2, 2),
-        SetForegroundColor(Color::DarkGrey),
-        Print(header_subtitle),
-        ResetColor
-    )?;
+impl ParsedOptions {
+    fn has(&self, flag: &str) -> bool {
+        self.values.contains_key(flag)
+    }
 
-    render_nav(stdout, data, state, 4)?;
+    fn value(&self, flag: &str) -> Option<String> {
+        self.values.get(flag).cloned().flatten()
+    }
+}
 
-    let content_top = 6;
-    match data.tabs.get(state.tab_index) {
-        Some(TabData::About(about)) => {
-            render_about(stdout, state, about, max_width, rows, content_top)?
+impl Cli {
+    /// Parses `args` (without the program name). Errors are ready to print as they are.
+    fn parse(args: &[String]) -> Result<Cli, String> {
+        let mut global = ParsedOptions::default();
+        let mut command: Option<(&CommandSpec, ParsedOptions)> = None;
+        let mut positionals = Vec::new();
+        let mut only_positionals = false;
+        let mut iter = args.iter().peekable();
+        while let Some(arg) = iter.next() {
+            if only_positionals || !arg.starts_with('-') || arg == "-" {
+                if command.is_none() {
+                    // `johnjeong essays/why-simple-wins` starts the app on that post.
+                    if arg.contains('/') && !global.has("--post") {
+                        global.values.insert("--post", Some(arg.clone()));
+                        continue;
+                    }
+                    let Some(spec) = COMMANDS.iter().find(|spec| spec.name == arg) else {
+                        return Err(format!(
+                            "Unknown command \"{}\" (see johnjeong --help).",
+                            arg
+                        ));
+                    };
+                    command = Some((spec, ParsedOptions::default()));
+                } else {
+                    positionals.push(arg.clone());
+                }
+                continue;
+            }
+            if arg == "--" {
+                only_positionals = true;
+                continue;
+            }
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let find = |specs: &'static [OptionSpec]| {
+                specs
+                    .iter()
+                    .find(|spec| spec.flag == name || spec.short == Some(name))
+            };
+            // A subcommand's own options come first: `search --tab` is the search's tab.
+            let (spec, target) = match command
+                .as_mut()
+                .and_then(|(command, options)| Some((find(command.options)?, options)))
+            {
+                Some(found) => found,
+                None => match find(&GLOBAL_OPTIONS) {
+                    Some(spec) => (spec, &mut global),
+                    None => {
+                        return Err(match command {
+                            Some((command, _)) => format!(
+                                "Unknown option {} for johnjeong {} (see johnjeong {} --help).",
+                                name, command.name, command.name
+                            ),
+                            None => format!("Unknown option {} (see johnjeong --help).", name),
+                        })
+                    }
+                },
+            };
+            let value = match spec.value {
+                OptionValue::Switch if inline.is_some() => {
+                    return Err(format!("{} doesn't take a value.", spec.flag));
+                }
+                OptionValue::Switch => None,
+                OptionValue::Required(value_name) => {
+                    match inline.or_else(|| iter.next().cloned()) {
+                        Some(value) => Some(value),
+                        None => return Err(format!("{} needs a <{}>.", spec.flag, value_name)),
+                    }
+                }
+                OptionValue::Optional(_) => {
+                    inline.or_else(|| iter.next_if(|next| !next.starts_with('-')).cloned())
+                }
+            };
+            target.values.insert(spec.flag, value);
         }
-        Some(TabData::Content(tab)) => {
-            render_content_tab(stdout, state, tab, max_width, rows, content_top)?
+
+        let command = match command {
+            Some((spec, options)) => {
+                let count = positionals.len();
+                let missing = spec.required.iter().any(|flag| !options.has(flag));
+                if count < spec.min_positionals
+                    || spec.max_positionals.is_some_and(|max| count > max)
+                    || missing
+                {
+                    return Err(format!("Usage: {}", command_usage(spec)));
+                }
+                Some(Cli::subcommand(spec.name, options, positionals))
+            }
+            None => Cli::flag_command(&global),
+        };
+        Ok(Cli {
+            options: global,
+            command,
+        })
+    }
+
+    fn subcommand(name: &str, options: ParsedOptions, positionals: Vec<String>) -> Subcommand {
+        let mut positionals = positionals.into_iter();
+        match name {
+            "search" => Subcommand::Search {
+                query: positionals.collect::<Vec<_>>().join(" "),
+                tab: options.value("--tab"),
+                json: options.has("--json"),
+            },
+            "list" => Subcommand::List {
+                tab: positionals.next(),
+                json: options.has("--json"),
+            },
+            "cat" => Subcommand::Cat {
+                slug: positionals.next().unwrap_or_default(),
+                tab: options.value("--tab"),
+                url: options.has("--url"),
+            },
+            "open" => Subcommand::Open {
+                slug: positionals.next().unwrap_or_default(),
+                tab: options.value("--tab"),
+            },
+            "latest" => Subcommand::Latest {
+                tab: positionals.next(),
+                url: options.has("--url"),
+            },
+            "today" => Subcommand::Today {
+                edit: options.has("--edit"),
+            },
+            "new" => Subcommand::New {
+                kind: positionals.next().unwrap_or_default(),
+                title: positionals.collect::<Vec<_>>().join(" "),
+                edit: options.has("--edit"),
+            },
+            "lint" => Subcommand::Lint,
+            "stats" => Subcommand::Stats {
+                json: options.has("--json"),
+            },
+            "export" => Subcommand::Export {
+                include_body: options.has("--include-body"),
+            },
+            "feed" => Subcommand::Feed {
+                tab: options
+                    .value("--tab")
+                    .unwrap_or_else(|| "essays".to_string()),
+                out: options.value("--out"),
+                atom: options.has("--atom"),
+            },
+            _ => unreachable!("every entry in COMMANDS is handled"),
         }
-        None => {}
     }
 
-    if let Some(status) = &state.status {
-        let text = clamp_text(status, max_width);
-        queue!(
-            stdout,
-            MoveTo(2, rows.saturating_sub(3)),
-            SetForegroundColor(Color::DarkGrey),
-            Print(text),
-            ResetColor
-        )?;
+    /// The flag-style commands, in the order they've always been checked.
+    fn flag_command(options: &ParsedOptions) -> Option<Subcommand> {
+        let tab = options.value("--tab");
+        if options.has("--doctor") {
+            Some(Subcommand::Doctor)
+        } else if options.has("--complete-slugs") {
+            Some(Subcommand::CompleteSlugs {
+                tab: options.value("--complete-slugs"),
+            })
+        } else if options.has("--completions") {
+            Some(Subcommand::Completions {
+                shell: options.value("--completions"),
+            })
+        } else if options.has("--verify-urls") {
+            Some(Subcommand::VerifyUrls {
+                source: options.value("--verify-urls"),
+                tab,
+            })
+        } else if options.has("--check-links") {
+            Some(Subcommand::CheckLinks { tab })
+        } else {
+            options
+                .value("--search")
+                .map(|query| Subcommand::FindPosts {
+                    query,
+                    tab,
+                    json: options.has("--json"),
+                })
+        }
     }
+}
 
-    queue!(
-    stdout,
-    MoveTo(2, rows.saturating_sub(2)),
-    SetForegroundColor(Color::DarkGrey),
-    Print("↑/↓ or j/k move  •  o/enter open  •  pgup/pgdn scroll  •  1-6 tabs (g gallery)  •  q quit"),
-    ResetColor
-  )?;
+/// `johnjeong cat <slug> [--tab <tab>] [--url]`, from the command's spec.
+fn command_usage(spec: &CommandSpec) -> String {
+    let mut usage = format!("johnjeong {}", spec.name);
+    if !spec.positionals.is_empty() {
+        usage.push(' ');
+        usage.push_str(spec.positionals);
+    }
+    for option in spec.options {
+        let option_usage = option_usage(option);
+        if spec.required.contains(&option.flag) {
+            usage.push_str(&format!(" {}", option_usage));
+        } else {
+            usage.push_str(&format!(" [{}]", option_usage));
+        }
+    }
+    usage
+}
 
-    stdout.flush()?;
-    Ok(())
+fn option_usage(option: &OptionSpec) -> String {
+    match option.value {
+        OptionValue::Switch => option.flag.to_string(),
+        OptionValue::Required(value) => format!("{} <{}>", option.flag, value),
+        OptionValue::Optional(value) => format!("{} [{}]", option.flag, value),
+    }
 }
 
-fn render_nav(stdout: &mut Stdout, data: &AppData, state: &AppState, y: u16) -> io::Result<()> {
-    let mut x = 2;
-    for (idx, tab) in data.tabs.iter().enumerate() {
-        let label = tab_label(idx, tab);
-        queue!(stdout, MoveTo(x, y))?;
-        if idx == state.tab_index {
-            queue!(
-                stdout,
-                SetAttribute(Attribute::Underlined),
-                Print(&label),
-                SetAttribute(Attribute::Reset)
-            )?;
+/// Help text for the options in `specs`: names in one column, descriptions wrapped beside them.
+/// A name too long for the column gets its description on the lines below.
+fn write_options(out: &mut impl Write, specs: &[OptionSpec]) -> io::Result<()> {
+    const NAME_COLUMN: usize = 24;
+    const HELP_WIDTH: usize = 70;
+    let names = specs
+        .iter()
+        .map(|spec| match spec.short {
+            Some(short) => format!("{}, {}", short, option_usage(spec)),
+            None => option_usage(spec),
+        })
+        .collect::<Vec<_>>();
+    let width = names
+        .iter()
+        .map(|name| display_width(name))
+        .filter(|width| *width <= NAME_COLUMN)
+        .max()
+        .unwrap_or(0);
+    let indent = " ".repeat(width + 4);
+    for (name, spec) in names.iter().zip(specs) {
+        let mut lines = wrap_help(spec.help, HELP_WIDTH);
+        let name_width = display_width(name);
+        if name_width > width {
+            writeln!(out, "  {}", name)?;
         } else {
-            queue!(stdout, Print(&label))?;
+            let first = lines.next().unwrap_or_default();
+            writeln!(
+                out,
+                "  {}{}  {}",
+                name,
+                " ".repeat(width - name_width),
+                first
+            )?;
+        }
+        for line in lines {
+            writeln!(out, "{}{}", indent, line)?;
         }
-        x += label.len() as u16 + 3;
     }
     Ok(())
 }
-fn render_about(
-    stdout: &mut Stdout,
-    state: &mut AppState,
-    about: &AboutData,
-    max_width: usize,
-    rows: u16,
-    content_top: u16,
-) -> io::Result<()> {
-    let tagline = clamp_text(about.tagline, max_width);
-    queue!(stdout, MoveTo(2, content_top), Print(tagline))?;
 
-    let list_y = content_top + 2;
-    let list_height = rows.saturating_sub(list_y + 3) as usize;
-    state.list_scroll = clamp_scroll(
-        state.list_scroll,
-        state.list_index,
-        list_height,
-        about.links.len(),
-    );
+/// Splits `text` into lines of at most `width` columns at spaces.
+fn wrap_help(text: &str, width: usize) -> impl Iterator<Item = String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if display_width(line) + 1 + display_width(word) <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines.into_iter()
+}
 
-    for (idx, link) in about
-        .links
+/// Runs a subcommand or flag-style command; none of them start the app.
+fn run_subcommand(command: Subcommand, settings: &Config) -> io::Result<()> {
+    let missing_content = |status: Option<String>| -> ! {
+        eprintln!(
+            "{}",
+            status.as_deref().unwrap_or("No content directory found.")
+        );
+        std::process::exit(1);
+    };
+    let content_found = |data: &AppData| {
+        data.tabs.iter().any(|tab| match tab {
+            TabData::Content(tab) => tab.local_dir.is_some(),
+            _ => false,
+        })
+    };
+    match command {
+        Subcommand::Doctor => print_doctor(settings),
+        Subcommand::CompleteSlugs { tab } => print_completion_slugs(settings, tab.as_deref()),
+        Subcommand::Completions { shell } => return print_completion_script(shell.as_deref()),
+        Subcommand::VerifyUrls { source, tab } => {
+            if let Err(message) = check_capability(Capability::Network) {
+                eprintln!("{}", message);
+                std::process::exit(2);
+            }
+            let (data, status) = build_app_data(settings);
+            let matched = run_verify_urls(
+                &data,
+                status.as_deref(),
+                source.as_deref(),
+                &settings.site_url,
+                tab.as_deref(),
+            )?;
+            std::process::exit(if matched { 0 } else { 1 });
+        }
+        Subcommand::CheckLinks { tab } => {
+            if let Err(message) = check_capability(Capability::Network) {
+                eprintln!("{}", message);
+                std::process::exit(2);
+            }
+            let (data, status) = build_app_data(settings);
+            let healthy = run_link_check(&data, status.as_deref(), tab.as_deref())?;
+            std::process::exit(if healthy { 0 } else { 1 });
+        }
+        Subcommand::FindPosts { query, tab, json } => {
+            let (data, status) = build_app_data(settings);
+            let found = run_search(&data, status.as_deref(), &query, tab.as_deref(), json)?;
+            std::process::exit(if found { 0 } else { 1 });
+        }
+        Subcommand::Search { query, tab, json } => {
+            let (data, status) = build_app_data(settings);
+            let found = run_grep(&data, status.as_deref(), &query, tab.as_deref(), json)?;
+            std::process::exit(if found { 0 } else { 1 });
+        }
+        Subcommand::List { tab, json } => {
+            let (data, status) = build_app_data(settings);
+            if let Some(status) = &status {
+                eprintln!("{}", status);
+            }
+            let tab = match tab.as_deref().map(|name| find_tab(&data, name)) {
+                Some(Ok(tab)) => Some(tab),
+                Some(Err(message)) => {
+                    eprintln!("{}", message);
+                    std::process::exit(2);
+                }
+                None => None,
+            };
+            run_list(&data, tab, json)?;
+        }
+        Subcommand::Cat { slug, tab, url } => {
+            let (data, status) = build_app_data(settings);
+            let (_, post) = resolve_slug_or_exit(&data, status.as_deref(), &slug, tab.as_deref());
+            if url {
+                println!("{}", post.url);
+                return Ok(());
+            }
+            return print_post(post);
+        }
+        Subcommand::Open { slug, tab } => {
+            let (data, status) = build_app_data(settings);
+            let (tab, post) = resolve_slug_or_exit(&data, status.as_deref(), &slug, tab.as_deref());
+            // Gallery entries open the image itself, like `o` in the app.
+            let (capability, opened) = match &post.file {
+                Some(path) if is_gallery_tab(tab) => {
+                    (Capability::OpenFile, path.display().to_string())
+                }
+                _ => (Capability::OpenUrl, post.url.clone()),
+            };
+            if let Err(message) = check_capability(capability) {
+                eprintln!("{}", message);
+                std::process::exit(2);
+            }
+            let result = match capability {
+                Capability::OpenFile => open_path(Path::new(&opened)),
+                _ => open_url(&opened),
+            };
+            match result {
+                Ok(opener) => println!("Opened {} with {}", opened, opener),
+                Err(err) => {
+                    eprintln!("Couldn't open {}: {}", opened, err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Subcommand::Latest { tab, url } => {
+            let (data, _) = build_app_data(settings);
+            let tab = tab.as_deref().map(|name| {
+                find_tab(&data, name).unwrap_or_else(|message| {
+                    eprintln!("{}", message);
+                    std::process::exit(2);
+                })
+            });
+            // Nothing at all is printed for an empty tab, so a prompt can just leave it out.
+            let Some((tab, post)) = latest_post(&data, tab) else {
+                std::process::exit(1);
+            };
+            if url {
+                println!("{}", post.url);
+            } else {
+                println!("{}\t{}\t{}", tab.name, format_date(&post.date), post.title);
+            }
+        }
+        Subcommand::Today { edit } => {
+            let mut status = None;
+            let Some(root) = resolve_content_root(settings, &mut status) else {
+                missing_content(status);
+            };
+            let dir = root.join("journals");
+            let today = local_today();
+            let existing = ['-', '_']
+                .into_iter()
+                .map(|separator| dir.join(format!("{}.md", today.day_slug(separator))))
+                .find(|path| path.is_file());
+            if !edit {
+                let Some(path) = existing else {
+                    eprintln!("No daily log for {} yet.", today.day_slug('-'));
+                    std::process::exit(1);
+                };
+                let base_url = format!("{}/journals", settings.site_url);
+                return print_post(&load_post(&path, &base_url, false)?);
+            }
+            if let Err(message) = check_capability(Capability::OpenFile) {
+                eprintln!("{}", message);
+                std::process::exit(2);
+            }
+            let path = match existing {
+                Some(path) => path,
+                None => match create_daily_log(&dir, today) {
+                    Ok(path) => {
+                        eprintln!("Created {}", path.display());
+                        path
+                    }
+                    Err(err) => {
+                        eprintln!("Couldn't create today's log in {}: {}", dir.display(), err);
+                        std::process::exit(1);
+                    }
+                },
+            };
+            if let Err(err) = edit_file(&path) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        Subcommand::New { kind, title, edit } => {
+            let Some(dir) = post_kind_dir(&kind) else {
+                eprintln!(
+                    "Unknown post type \"{}\" (use essay, journal, inspiration, or lesson).",
+                    kind
+                );
+                std::process::exit(2);
+            };
+            let slug = slug_from_title(&title);
+            if slug.is_empty() {
+                eprintln!("Give the post a title with at least one letter or digit.");
+                std::process::exit(2);
+            }
+            let mut status = None;
+            let Some(root) = resolve_content_root(settings, &mut status) else {
+                missing_content(status);
+            };
+            let path = root.join(dir).join(format!("{}.md", slug));
+            if let Err(err) = create_post(&path, &title) {
+                if err.kind() == io::ErrorKind::AlreadyExists {
+                    eprintln!("{} already exists.", path.display());
+                } else {
+                    eprintln!("Couldn't create {}: {}", path.display(), err);
+                }
+                std::process::exit(1);
+            }
+            println!("{}", path.display());
+            if edit {
+                if let Err(message) = check_capability(Capability::OpenFile) {
+                    eprintln!("{}", message);
+                    std::process::exit(2);
+                }
+                if let Err(err) = edit_file(&path) {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Subcommand::Lint => {
+            let mut status = None;
+            let Some(root) = resolve_content_root(settings, &mut status) else {
+                missing_content(status);
+            };
+            let clean = run_lint(&root)?;
+            std::process::exit(if clean { 0 } else { 1 });
+        }
+        Subcommand::Stats { json } => {
+            let (data, status) = build_app_data(settings);
+            if !content_found(&data) {
+                missing_content(status);
+            }
+            run_stats(&data, json)?;
+        }
+        Subcommand::Export { include_body } => {
+            let (data, status) = build_app_data(settings);
+            if !content_found(&data) {
+                missing_content(status);
+            }
+            run_export(&mut io::stdout().lock(), &data, include_body)?;
+        }
+        Subcommand::Feed { tab, out, atom } => {
+            let (data, status) = build_app_data(settings);
+            let tab = match find_tab(&data, &tab) {
+                Ok(tab) => tab,
+                Err(message) => {
+                    eprintln!("{}", message);
+                    std::process::exit(2);
+                }
+            };
+            if tab.local_dir.is_none() {
+                missing_content(status);
+            }
+            let built = match source_date_epoch(|name| env::var(name).ok()) {
+                Ok(built) => built,
+                Err(message) => {
+                    eprintln!("{}", message);
+                    std::process::exit(2);
+                }
+            };
+            let feed = if atom {
+                atom_feed(&data.header, tab, &settings.site_url, built)
+            } else {
+                rss_feed(&data.header, tab, &settings.site_url, built)
+            };
+            match out {
+                Some(path) => {
+                    if let Err(err) = write_atomic(&expand_path(&path), feed.as_bytes()) {
+                        eprintln!("Couldn't write {}: {}", path, err);
+                        std::process::exit(1);
+                    }
+                }
+                None => io::stdout().lock().write_all(feed.as_bytes())?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The value of `flag` given as `--flag value` or `--flag=value`, the two spellings
+/// `Cli::parse` accepts, for reading it before the rest of the line is parsed.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let mut iter = args.iter().take_while(|arg| *arg != "--");
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            return iter.next().map(String::as_str);
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value);
+        }
+    }
+    None
+}
+
+struct SearchHit<'a> {
+    tab: &'a ContentTab,
+    post: &'a Post,
+    score: u32,
+    snippet: Snippet,
+}
+
+/// A single line of context around a match; `match_start..match_end` are byte offsets into
+/// `text`.
+struct Snippet {
+    text: String,
+    match_start: usize,
+    match_end: usize,
+}
+
+impl Snippet {
+    /// The text with its match between `open` and `close`.
+    fn marked(&self, open: &str, close: &str) -> String {
+        format!(
+            "{}{}{}{}{}",
+            &self.text[..self.match_start],
+            open,
+            &self.text[self.match_start..self.match_end],
+            close,
+            &self.text[self.match_end..]
+        )
+    }
+}
+
+const SNIPPET_BEFORE: usize = 30;
+const SNIPPET_AFTER: usize = 60;
+
+/// Ranks posts against every whitespace-separated term of `query`. Title hits outweigh body
+/// hits, and a verbatim phrase hit outweighs scattered terms; ties keep date order.
+fn search_posts<'a>(
+    data: &'a AppData,
+    query: &str,
+    tab_filter: Option<&str>,
+) -> Vec<SearchHit<'a>> {
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let phrase = terms.join(" ");
+
+    let mut hits = Vec::new();
+    for tab in search_tabs(data, tab_filter) {
+        for post in tab.listed_posts() {
+            let body = collapse_whitespace(&post.body);
+            let mut score = 0;
+            for term in &terms {
+                let in_title = find_ignore_case(&post.title, term).is_some();
+                let in_body = find_ignore_case(&body, term).is_some();
+                if !in_title && !in_body {
+                    score = 0;
+                    break;
+                }
+                score += if in_title { 10 } else { 0 } + if in_body { 1 } else { 0 };
+            }
+            if score == 0 {
+                continue;
+            }
+            if find_ignore_case(&post.title, &phrase).is_some() {
+                score += 100;
+            }
+            if find_ignore_case(&body, &phrase).is_some() {
+                score += 20;
+            }
+
+            let snippet = [phrase.as_str()]
+                .iter()
+                .chain(terms.iter())
+                .find_map(|needle| extract_snippet(&body, needle))
+                .unwrap_or_else(|| Snippet {
+                    text: clamp_text(&body, SNIPPET_BEFORE + SNIPPET_AFTER),
+                    match_start: 0,
+                    match_end: 0,
+                });
+            hits.push(SearchHit {
+                tab,
+                post,
+                score,
+                snippet,
+            });
+        }
+    }
+
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+    hits
+}
+
+/// The content tabs `--search` and `search` look through: all of them, or those `tab_filter`
+/// names.
+fn search_tabs<'a>(data: &'a AppData, tab_filter: Option<&str>) -> Vec<&'a ContentTab> {
+    data.tabs
         .iter()
-        .enumerate()
-        .skip(state.list_scroll)
-        .take(list_height)
-    {
-        let is_selected = idx == state.list_index;
-        let y = list_y + (idx - state.list_scroll) as u16;
-        queue!(stdout, MoveTo(4, y))?;
-        if is_selected {
-            queue!(
+        .filter_map(|tab| match tab {
+            TabData::Content(tab) if tab_filter.is_none_or(|filter| tab_matches(tab, filter)) => {
+                Some(tab)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// How a match is marked in `--search` and `search` output: bold yellow on a terminal, and
+/// otherwise characters markdown doesn't use, since the text around it is raw markdown.
+fn match_markers(stdout: &impl IsTerminal) -> (&'static str, &'static str) {
+    if stdout.is_terminal() {
+        ("\x1b[1;33m", "\x1b[0m")
+    } else {
+        ("«", "»")
+    }
+}
+
+/// The JSON fields every search result starts with, without the braces around them.
+fn hit_json_fields(tab: &ContentTab, post: &Post) -> String {
+    format!(
+        "\"tab\":{},\"date\":{},\"title\":{},\"url\":{},\"words\":{},\"minutes\":{}",
+        json_string(tab.name),
+        json_string(&post.date),
+        json_string(&post.title),
+        json_string(&post.url),
+        post.words,
+        post.reading_minutes()
+    )
+}
+
+fn tab_matches(tab: &ContentTab, filter: &str) -> bool {
+    let filter = filter.trim();
+    tab.dir.eq_ignore_ascii_case(filter)
+        || tab.name.eq_ignore_ascii_case(filter)
+        || tab.name.replace(' ', "-").eq_ignore_ascii_case(filter)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Case-insensitive substring search returning the byte range of the first match in
+/// `haystack`. Compares per character so offsets stay valid for non-ASCII text.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<(usize, usize)> {
+    if needle.is_empty() {
+        return None;
+    }
+    for (start, _) in haystack.char_indices() {
+        let mut rest = haystack[start..].char_indices();
+        let mut end = start;
+        let mut matched = true;
+        for expected in needle.chars() {
+            match rest.next() {
+                Some((offset, actual)) if chars_eq_ignore_case(actual, expected) => {
+                    end = start + offset + actual.len_utf8();
+                }
+                _ => {
+                    matched = false;
+                    break;
+                }
+            }
+        }
+        if matched {
+            return Some((start, end));
+        }
+    }
+    None
+}
+
+fn chars_eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Cuts a window of roughly `SNIPPET_BEFORE`/`SNIPPET_AFTER` characters around the first match
+/// of `needle`, trimmed back to word boundaries and marked with `…` where text was dropped.
+fn extract_snippet(text: &str, needle: &str) -> Option<Snippet> {
+    let (match_start, match_end) = find_ignore_case(text, needle)?;
+
+    let mut start = text[..match_start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_BEFORE.saturating_sub(1))
+        .map_or(0, |(index, _)| index);
+    if start > 0 {
+        start = text[start..match_start]
+            .find(' ')
+            .map_or(match_start, |space| start + space + 1);
+    }
+
+    let mut end = text[match_end..]
+        .char_indices()
+        .nth(SNIPPET_AFTER)
+        .map_or(text.len(), |(index, _)| match_end + index);
+    if end < text.len() {
+        end = text[match_end..end]
+            .rfind(' ')
+            .map_or(match_end, |space| match_end + space);
+    }
+
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < text.len() { "…" } else { "" };
+    Some(Snippet {
+        text: format!("{}{}{}", prefix, &text[start..end], suffix),
+        match_start: prefix.len() + match_start - start,
+        match_end: prefix.len() + match_end - start,
+    })
+}
+
+fn run_search(
+    data: &AppData,
+    status: Option<&str>,
+    query: &str,
+    tab_filter: Option<&str>,
+    json: bool,
+) -> io::Result<bool> {
+    if let Some(status) = status {
+        eprintln!("{}", status);
+    }
+    let hits = search_posts(data, query, tab_filter);
+    let mut stdout = io::stdout().lock();
+
+    if json {
+        let items = hits
+            .iter()
+            .map(|hit| {
+                format!(
+                    "{{{},\"snippet\":{},\"score\":{}}}",
+                    hit_json_fields(hit.tab, hit.post),
+                    json_string(&hit.snippet.text),
+                    hit.score
+                )
+            })
+            .collect::<Vec<_>>();
+        writeln!(stdout, "[{}]", items.join(","))?;
+        return Ok(!hits.is_empty());
+    }
+
+    let (open, close) = match_markers(&stdout);
+    for hit in &hits {
+        writeln!(
+            stdout,
+            "{}\t{}\t{}\t{}\t{}",
+            hit.tab.name,
+            format_date(&hit.post.date),
+            hit.post.title,
+            hit.post.url,
+            hit.snippet.marked(open, close)
+        )?;
+    }
+    Ok(!hits.is_empty())
+}
+
+/// Prints the header and every content tab's posts as JSON for `johnjeong export --json`. Keys
+/// come in a fixed order and posts in tab order, one per line, so two exports diff cleanly.
+fn run_export(stdout: &mut impl Write, data: &AppData, include_body: bool) -> io::Result<()> {
+    writeln!(stdout, "{{")?;
+    writeln!(
+        stdout,
+        "  \"header\": {{\"title\": {}, \"subtitle\": {}}},",
+        json_string(&data.header.title),
+        json_string(&data.header.subtitle)
+    )?;
+    writeln!(stdout, "  \"tabs\": [")?;
+    let tabs: Vec<&ContentTab> = data
+        .tabs
+        .iter()
+        .filter_map(|tab| match tab {
+            TabData::Content(tab) => Some(tab),
+            TabData::About(_) | TabData::Tags(_) => None,
+        })
+        .collect();
+    for (index, tab) in tabs.iter().enumerate() {
+        writeln!(
+            stdout,
+            "    {{\"name\": {}, \"dir\": {}, \"posts\": [",
+            json_string(tab.name),
+            json_string(tab.dir)
+        )?;
+        let posts = tab.listed_posts().collect::<Vec<_>>();
+        for (position, post) in posts.iter().enumerate() {
+            let tags = post
+                .tags
+                .iter()
+                .map(|tag| json_string(tag))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let body = if include_body {
+                format!(", \"body\": {}", json_string(&post.body))
+            } else {
+                String::new()
+            };
+            writeln!(
                 stdout,
-                SetForegroundColor(Color::Black),
-                SetBackgroundColor(Color::White)
+                "      {{\"title\": {}, \"date\": {}, \"slug\": {}, \"url\": {}, \"tags\": [{}], \"words\": {}{}}}{}",
+                json_string(&post.title),
+                json_string(&post.date),
+                json_string(&post.slug),
+                json_string(&post.url),
+                tags,
+                post.words,
+                body,
+                if position + 1 < posts.len() { "," } else { "" }
             )?;
-        } else {
-            queue!(stdout, SetForegroundColor(Color::White))?;
         }
-        queue!(
+        writeln!(
             stdout,
-            Print(format!(
-                "{} {}",
-                if is_selected { "›" } else { " " },
-                link.label
-            )),
-            ResetColor
+            "    ]}}{}",
+            if index + 1 < tabs.len() { "," } else { "" }
         )?;
     }
+    writeln!(stdout, "  ]")?;
+    writeln!(stdout, "}}")
+}
 
-    Ok(())
+/// Characters of plain text in a feed item's description when the post has no `description`.
+const FEED_SUMMARY_CHARS: usize = 300;
+
+/// When a feed was built, from `SOURCE_DATE_EPOCH` (seconds since the Unix epoch), so builds of
+/// the same content stay byte-for-byte identical. `None` when it isn't set.
+fn source_date_epoch(env: impl Fn(&str) -> Option<String>) -> Result<Option<PostDate>, String> {
+    match env("SOURCE_DATE_EPOCH") {
+        None => Ok(None),
+        Some(value) => value
+            .trim()
+            .parse::<i64>()
+            .map(|seconds| Some(PostDate::from_unix(seconds)))
+            .map_err(|_| {
+                format!(
+                    "SOURCE_DATE_EPOCH must be a number of seconds, not {:?}.",
+                    value
+                )
+            }),
+    }
 }
 
-fn render_content_tab(
-    stdout: &mut Stdout,
-    state: &mut AppState,
+/// An RSS 2.0 document for `johnjeong feed`. Posts whose date doesn't parse go out without a
+/// `pubDate` rather than with a malformed one, and `lastBuildDate` is only written for a
+/// `built` date.
+fn rss_feed(
+    header: &HeaderData,
     tab: &ContentTab,
-    max_width: usize,
-    rows: u16,
-    content_top: u16,
-) -> io::Result<()> {
-    let list_x = 2;
-    let list_y = content_top + 1;
-    let list_width = ((max_width as f32) * 0.33) as usize;
-    let list_width = list_width.clamp(24, 38);
-    let list_height = rows.saturating_sub(list_y + 3) as usize;
+    site_url: &str,
+    built: Option<PostDate>,
+) -> String {
+    let link = tab.web_url.as_deref().unwrap_or(site_url);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\">\n<channel>\n");
+    xml.push_str(&format!(
+        "  <title>{}</title>\n  <link>{}</link>\n  <description>{}</description>\n",
+        xml_escape(&format!("{} — {}", header.title, tab.name)),
+        xml_escape(link),
+        xml_escape(&tab.description)
+    ));
+    if let Some(built) = built {
+        xml.push_str(&format!(
+            "  <lastBuildDate>{}</lastBuildDate>\n",
+            built.rfc822()
+        ));
+    }
+    for post in tab.listed_posts() {
+        xml.push_str("  <item>\n");
+        xml.push_str(&format!(
+            "    <title>{}</title>\n    <link>{}</link>\n    <guid>{}</guid>\n",
+            xml_escape(&post.title),
+            xml_escape(&post.url),
+            xml_escape(&post.url)
+        ));
+        if let Some(date) = parse_date(&post.date) {
+            xml.push_str(&format!("    <pubDate>{}</pubDate>\n", date.rfc822()));
+        }
+        xml.push_str(&format!(
+            "    <description>{}</description>\n",
+            xml_escape(&feed_summary(post))
+        ));
+        xml.push_str("  </item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
 
-    state.list_scroll = clamp_scroll(
-        state.list_scroll,
-        state.list_index,
-        list_height,
-        tab.posts.len(),
-    );
+/// An Atom document for `johnjeong feed --atom`. The feed's `updated` is the `built` date when
+/// there is one, else the newest date in the tab. Atom requires `updated` on every entry, so
+/// undated posts borrow the feed's.
+fn atom_feed(
+    header: &HeaderData,
+    tab: &ContentTab,
+    site_url: &str,
+    built: Option<PostDate>,
+) -> String {
+    let link = tab.web_url.as_deref().unwrap_or(site_url);
+    let updated = built.unwrap_or_else(|| {
+        tab.listed_posts()
+            .filter_map(|post| parse_date(&post.date))
+            .max()
+            .unwrap_or(PostDate::EPOCH)
+    });
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!(
+        "  <title>{}</title>\n  <link href=\"{}\"/>\n  <id>{}</id>\n  <updated>{}</updated>\n",
+        xml_escape(&format!("{} — {}", header.title, tab.name)),
+        xml_escape(link),
+        xml_escape(link),
+        updated.rfc3339()
+    ));
+    xml.push_str(&format!(
+        "  <author><name>{}</name></author>\n",
+        xml_escape(&header.title)
+    ));
+    for post in tab.listed_posts() {
+        let date = parse_date(&post.date);
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!(
+            "    <title>{}</title>\n    <link href=\"{}\"/>\n    <id>{}</id>\n    <updated>{}</updated>\n",
+            xml_escape(&post.title),
+            xml_escape(&post.url),
+            xml_escape(&post.url),
+            date.unwrap_or(updated).rfc3339()
+        ));
+        if let Some(date) = date {
+            xml.push_str(&format!("    <published>{}</published>\n", date.rfc3339()));
+        }
+        xml.push_str(&format!(
+            "    <summary>{}</summary>\n",
+            xml_escape(&feed_summary(post))
+        ));
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
 
-    queue!(
-        stdout,
-        MoveTo(list_x, list_y - 1),
-        SetForegroundColor(Color::DarkGrey),
-        Print("Posts"),
-        ResetColor
-    )?;
+/// The frontmatter `description`, or the start of the body as plain text, cut at a word.
+fn feed_summary(post: &Post) -> String {
+    if let Some(description) = post
+        .fields
+        .get("description")
+        .filter(|description| !description.is_empty())
+    {
+        return description.clone();
+    }
+    let text = markdown_to_plain(&post.body);
+    if text.chars().count() <= FEED_SUMMARY_CHARS {
+        return text;
+    }
+    let cut: String = text.chars().take(FEED_SUMMARY_CHARS).collect();
+    let cut = match cut.rfind(' ') {
+        Some(space) => &cut[..space],
+        None => cut.as_str(),
+    };
+    format!("{}…", cut.trim_end())
+}
 
-    if tab.posts.is_empty() {
-        queue!(
-            stdout,
-            MoveTo(list_x, list_y),
-            SetForegroundColor(Color::DarkGrey),
-            Print("No posts found."),
-            ResetColor
-        )?;
-    } else {
-        for (idx, post) in tab
-            .posts
-            .iter()
-            .enumerate()
-            .skip(state.list_scroll)
-            .take(list_height)
-        {
-            let is_selected = idx == state.list_index;
-            let y = list_y + (idx - state.list_scroll) as u16;
-            queue!(stdout, MoveTo(list_x, y))?;
-            if is_selected {
-                queue!(
-                    stdout,
-                    SetForegroundColor(Color::Black),
-                    SetBackgroundColor(Color::White)
-                )?;
-            } else {
-                queue!(stdout, SetForegroundColor(Color::White))?;
+/// Markdown flattened to one line of prose: headings, quotes, list markers, emphasis, link
+/// targets, images, footnotes, code blocks, and HTML tags dropped.
+fn markdown_to_plain(body: &str) -> String {
+    let (text, notes) = extract_footnotes(body);
+    let mut links = Vec::new();
+    let mut words = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if is_fence_line(line) {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || table_separator(line).is_some() {
+            continue;
+        }
+        let mut line = line.trim();
+        while let Some(rest) = strip_quote(line) {
+            line = rest.trim_start();
+        }
+        if let Some((_, heading)) = parse_heading(line) {
+            line = heading;
+        }
+        if let Some(marker) = list_marker(line) {
+            line = &line[marker..];
+        }
+        let prose: String = parse_inline(line, &notes, &mut links)
+            .into_iter()
+            .filter(|span| {
+                matches!(
+                    span.style,
+                    SpanStyle::Plain | SpanStyle::Link | SpanStyle::Code
+                )
+            })
+            .map(|span| span.text)
+            .collect();
+        words.extend(
+            strip_html_tags(&prose)
+                .split_whitespace()
+                .map(str::to_string),
+        );
+    }
+    words.join(" ")
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters other than tab and newlines aren't allowed in XML 1.0 at all.
+            ch if (ch as u32) < 0x20 && !matches!(ch, '\t' | '\n' | '\r') => {}
+            ch => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Every post of every content tab, one tab-separated line each (tab, date, title, url), for
+/// `--plain` and for when stdin or stdout isn't a terminal.
+fn run_plain(data: &AppData, status: Option<&str>) -> io::Result<()> {
+    if let Some(status) = status {
+        eprintln!("{}", status);
+    }
+    let mut stdout = io::stdout().lock();
+    for tab in data.tabs.iter().filter_map(|tab| match tab {
+        TabData::Content(tab) => Some(tab),
+        TabData::About(_) | TabData::Tags(_) => None,
+    }) {
+        for post in tab.listed_posts() {
+            let written = writeln!(
+                stdout,
+                "{}\t{}\t{}\t{}",
+                tab.name,
+                format_date(&post.date),
+                post.title,
+                post.url
+            );
+            // `johnjeong | head` closes the pipe early; that's not an error.
+            match written {
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+                written => written?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The content tab a command-line `name` means: its directory or its name, in any case, with
+/// spaces or hyphens (`journals`, `Daily Logs`, `daily-logs`).
+fn find_tab<'a>(data: &'a AppData, name: &str) -> Result<&'a ContentTab, String> {
+    let tabs = data.tabs.iter().filter_map(|tab| match tab {
+        TabData::Content(tab) => Some(tab),
+        TabData::About(_) | TabData::Tags(_) => None,
+    });
+    if let Some(tab) = tabs.clone().find(|tab| tab_matches(tab, name)) {
+        return Ok(tab);
+    }
+    let names = tabs
+        .map(|tab| tab.name.to_lowercase().replace(' ', "-"))
+        .collect::<Vec<_>>();
+    Err(format!(
+        "No tab named \"{}\" (use {}).",
+        name,
+        names.join(", ")
+    ))
+}
+
+/// A post's title, date, and markdown body, for `cat` and `today`. A reader that stops early
+/// (`| head`) isn't an error.
+fn print_post(post: &Post) -> io::Result<()> {
+    let date = match format_date(&post.date) {
+        date if date.is_empty() => String::new(),
+        date => format!("{}\n", date),
+    };
+    match write!(
+        io::stdout().lock(),
+        "{}\n{}\n{}\n",
+        post.title,
+        date,
+        post.body
+    ) {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Creates today's daily log with just `created_at`, named with whichever of `-` or `_` the
+/// dated files already in `dir` use (`-` when there are none).
+fn create_daily_log(dir: &Path, today: PostDate) -> io::Result<PathBuf> {
+    check_capability(Capability::WriteState).map_err(io::Error::other)?;
+    let (mut dashes, mut underscores) = (0, 0);
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if path.extension().and_then(|ext| ext.to_str()) != Some("md") || parse_date(stem).is_none()
+        {
+            continue;
+        }
+        match stem.as_bytes()[4] {
+            b'_' => underscores += 1,
+            _ => dashes += 1,
+        }
+    }
+    let separator = if underscores > dashes { '_' } else { '-' };
+    let path = dir.join(format!("{}.md", today.day_slug(separator)));
+    let mut file = create_file(&path, true)?;
+    write!(file, "---\ncreated_at: {}\n---\n\n", today.day_slug('-'))?;
+    Ok(path)
+}
+
+/// Numbers for one tab in `johnjeong stats`. Word counts are `Post::words`, the same the app
+/// shows as reading time.
+struct TabStats<'a> {
+    name: &'a str,
+    posts: usize,
+    words: usize,
+    median_words: usize,
+    oldest: Option<PostDate>,
+    newest: Option<PostDate>,
+    per_year: Vec<(i64, usize)>,
+    /// Longest run of consecutive days with a post; only worked out for daily logs.
+    streak: Option<usize>,
+}
+
+impl<'a> TabStats<'a> {
+    fn new(tab: &'a ContentTab) -> TabStats<'a> {
+        let mut words = tab
+            .listed_posts()
+            .map(|post| post.words)
+            .collect::<Vec<_>>();
+        words.sort_unstable();
+        let median_words = match words.len() {
+            0 => 0,
+            len if len % 2 == 1 => words[len / 2],
+            len => (words[len / 2 - 1] + words[len / 2]) / 2,
+        };
+        let dates = tab
+            .listed_posts()
+            .filter_map(|post| parse_date(&post.date))
+            .collect::<Vec<_>>();
+        let mut per_year: Vec<(i64, usize)> = Vec::new();
+        for date in &dates {
+            match per_year.iter_mut().find(|(year, _)| *year == date.year) {
+                Some((_, count)) => *count += 1,
+                None => per_year.push((date.year, 1)),
+            }
+        }
+        per_year.sort_unstable();
+        let streak = (tab.dir == "journals").then(|| {
+            let mut days = dates
+                .iter()
+                .map(|date| days_from_civil(date.year, date.month, date.day))
+                .collect::<Vec<_>>();
+            days.sort_unstable();
+            days.dedup();
+            let (mut longest, mut run) = (0, 0);
+            for (index, day) in days.iter().enumerate() {
+                run = if index > 0 && days[index - 1] + 1 == *day {
+                    run + 1
+                } else {
+                    1
+                };
+                longest = longest.max(run);
+            }
+            longest
+        });
+        TabStats {
+            name: tab.name,
+            posts: words.len(),
+            words: words.iter().sum(),
+            median_words,
+            oldest: dates.iter().min().copied(),
+            newest: dates.iter().max().copied(),
+            per_year,
+            streak,
+        }
+    }
+}
+
+/// `johnjeong stats`: counts, words, date range, and posts per year for every content tab.
+fn run_stats(data: &AppData, json: bool) -> io::Result<()> {
+    let stats = data
+        .tabs
+        .iter()
+        .filter_map(|tab| match tab {
+            TabData::Content(tab) => Some(TabStats::new(tab)),
+            TabData::About(_) | TabData::Tags(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let day = |date: Option<PostDate>| date.map(|date| date.day_slug('-'));
+    let mut stdout = io::stdout().lock();
+    if json {
+        let items = stats
+            .iter()
+            .map(|stats| {
+                let per_year = stats
+                    .per_year
+                    .iter()
+                    .map(|(year, count)| format!("\"{}\":{}", year, count))
+                    .collect::<Vec<_>>();
+                let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+                format!(
+                    "{{\"name\":{},\"posts\":{},\"words\":{},\"median_words\":{},\"oldest\":{},\"newest\":{},\"per_year\":{{{}}},\"streak\":{}}}",
+                    json_string(stats.name),
+                    stats.posts,
+                    stats.words,
+                    stats.median_words,
+                    optional(day(stats.oldest).map(|date| json_string(&date))),
+                    optional(day(stats.newest).map(|date| json_string(&date))),
+                    per_year.join(","),
+                    optional(stats.streak.map(|streak| streak.to_string()))
+                )
+            })
+            .collect::<Vec<_>>();
+        return writeln!(stdout, "[{}]", items.join(","));
+    }
+    for (index, stats) in stats.iter().enumerate() {
+        if index > 0 {
+            writeln!(stdout)?;
+        }
+        writeln!(stdout, "{}", stats.name)?;
+        writeln!(stdout, "  posts     {}", group_thousands(stats.posts))?;
+        writeln!(
+            stdout,
+            "  words     {} total, {} median",
+            group_thousands(stats.words),
+            group_thousands(stats.median_words)
+        )?;
+        if let (Some(oldest), Some(newest)) = (day(stats.oldest), day(stats.newest)) {
+            writeln!(stdout, "  dates     {} to {}", oldest, newest)?;
+        }
+        if !stats.per_year.is_empty() {
+            let per_year = stats
+                .per_year
+                .iter()
+                .map(|(year, count)| format!("{} {}", year, count))
+                .collect::<Vec<_>>();
+            writeln!(stdout, "  per year  {}", per_year.join(", "))?;
+        }
+        if let Some(streak) = stats.streak.filter(|streak| *streak > 0) {
+            let unit = if streak == 1 { "day" } else { "days" };
+            writeln!(stdout, "  streak    {} {} in a row", streak, unit)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks every markdown post under `root` for `johnjeong lint` and prints one `path: problem`
+/// line per issue, file by file. `true` when there were none.
+fn run_lint(root: &Path) -> io::Result<bool> {
+    let mut issues: Vec<(PathBuf, String)> = Vec::new();
+    for dir in CONTENT_DIRS.iter().filter(|dir| **dir != "gallery") {
+        let Ok(entries) = fs::read_dir(root.join(dir)) else {
+            continue;
+        };
+        let mut paths = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension().and_then(|ext| ext.to_str()) == Some("md")
+                    && !is_section_index(path)
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+        let mut slugs: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for path in paths {
+            let (contents, decoding) = read_text(&path)?;
+            if decoding == Some(Decoding::Undecodable) {
+                issues.push((
+                    path,
+                    "not UTF-8, and its encoding couldn't be told".to_string(),
+                ));
+                continue;
+            }
+            let (frontmatter, body) = split_frontmatter(&contents);
+            let stem = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("post");
+            slugs
+                .entry(post_slug(stem, &frontmatter))
+                .or_default()
+                .push(path.clone());
+            issues.extend(
+                lint_post(&contents, &frontmatter, body, parse_date(stem).is_some())
+                    .into_iter()
+                    .map(|problem| (path.clone(), problem)),
+            );
+        }
+        for (slug, paths) in slugs.into_iter().filter(|(_, paths)| paths.len() > 1) {
+            for path in &paths {
+                let others = paths
+                    .iter()
+                    .filter(|other| *other != path)
+                    .filter_map(|other| other.file_name())
+                    .map(|name| name.to_string_lossy())
+                    .collect::<Vec<_>>();
+                issues.push((
+                    path.clone(),
+                    format!("slug \"{}\" is also used by {}", slug, others.join(", ")),
+                ));
+            }
+        }
+    }
+    // Stable sort: a file's problems stay in the order they were found.
+    issues.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut stdout = io::stdout().lock();
+    for (path, problem) in &issues {
+        writeln!(stdout, "{}: {}", path.display(), problem)?;
+    }
+    Ok(issues.is_empty())
+}
+
+/// Problems with one post's frontmatter and body, for `johnjeong lint`. A file named by its
+/// date (`2024-06-01.md`, as daily logs are) gets its date and title from the name, so it
+/// doesn't need `created_at` or `title`.
+fn lint_post(
+    contents: &str,
+    frontmatter: &HashMap<String, String>,
+    body: &str,
+    dated_name: bool,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut lines = contents.lines();
+    if lines.next() == Some("---") && !lines.any(|line| line == "---") {
+        // Everything after the opening `---` was read as frontmatter; the rest would only add
+        // noise.
+        problems.push("frontmatter is never closed with ---".to_string());
+        return problems;
+    }
+    match frontmatter.get("created_at").map(|value| value.trim()) {
+        None | Some("") if dated_name => {}
+        None | Some("") => problems.push("missing created_at".to_string()),
+        Some(value) if parse_date(value).is_none() => {
+            problems.push(format!("created_at \"{}\" isn't a date", value))
+        }
+        Some(_) => {}
+    }
+    if !dated_name
+        && frontmatter
+            .get("title")
+            .is_none_or(|title| title.trim().is_empty())
+    {
+        problems.push("missing title".to_string());
+    }
+    if let Some(published) = frontmatter.get("published") {
+        if published != "true" && published != "false" {
+            problems.push(format!("published is \"{}\", not true or false", published));
+        }
+    }
+    if body.trim().is_empty() {
+        problems.push("empty body".to_string());
+    }
+    problems
+}
+
+/// The content directory `johnjeong new` writes a post type to; plurals work too.
+fn post_kind_dir(kind: &str) -> Option<&'static str> {
+    match kind.to_lowercase().trim_end_matches('s') {
+        "essay" => Some("essays"),
+        "journal" | "daily-log" | "log" => Some("journals"),
+        "inspiration" => Some("inspirations"),
+        "lesson" => Some("lessons"),
+        _ => None,
+    }
+}
+
+/// A new unpublished post with its title and today's `created_at`, never over an existing file.
+fn create_post(path: &Path, title: &str) -> io::Result<()> {
+    check_capability(Capability::WriteState).map_err(io::Error::other)?;
+    // The frontmatter reader strips one pair of quotes and nothing more, so no escaping.
+    let quoted = if title.contains('"') {
+        format!("'{}'", title)
+    } else {
+        format!("\"{}\"", title)
+    };
+    let mut file = create_file(path, true)?;
+    write!(
+        file,
+        "---\ntitle: {}\ncreated_at: {}\npublished: false\n---\n\n",
+        quoted,
+        local_today().day_slug('-')
+    )
+}
+
+/// Every post of `tab`, or of all the content tabs, with the tab it's in.
+fn posts_with_tabs<'a>(
+    data: &'a AppData,
+    tab: Option<&'a ContentTab>,
+) -> impl Iterator<Item = (&'a ContentTab, &'a Post)> + Clone {
+    data.tabs
+        .iter()
+        .filter_map(move |candidate| match candidate {
+            TabData::Content(candidate) if tab.is_none_or(|tab| std::ptr::eq(tab, candidate)) => {
+                Some(candidate)
+            }
+            _ => None,
+        })
+        .flat_map(|tab| tab.posts.iter().map(move |post| (tab, post)))
+}
+
+/// Posts whose slug is `query` or, failing an exact match, starts with it, across the content
+/// tabs or just `tab`. One match is the post; none or several come back as the error. A query
+/// like `essays/why-simple-wins`, as shell completion offers it, looks only in that tab.
+fn resolve_slug<'a>(
+    data: &'a AppData,
+    query: &str,
+    tab: Option<&'a ContentTab>,
+) -> Result<(&'a ContentTab, &'a Post), Vec<(&'a ContentTab, &'a Post)>> {
+    let (query, tab) = match query.split_once('/') {
+        Some((name, slug)) => match find_tab(data, name) {
+            Ok(named) if tab.is_none_or(|tab| std::ptr::eq(tab, named)) => (slug, Some(named)),
+            Ok(_) => return Err(Vec::new()),
+            Err(_) => (query, tab),
+        },
+        None => (query, tab),
+    };
+    let posts = posts_with_tabs(data, tab);
+    let exact = posts
+        .clone()
+        .filter(|(_, post)| post.slug == query)
+        .collect::<Vec<_>>();
+    let mut matches = if exact.is_empty() {
+        posts
+            .filter(|(_, post)| post.slug.starts_with(query))
+            .collect::<Vec<_>>()
+    } else {
+        exact
+    };
+    if matches.len() == 1 {
+        Ok(matches.remove(0))
+    } else {
+        Err(matches)
+    }
+}
+
+/// `resolve_slug` for `cat` and `open`: prints why and exits when it doesn't come down to one
+/// post, `1` for no match and `2` for an unknown tab or an ambiguous prefix (listing the
+/// candidates).
+fn resolve_slug_or_exit<'a>(
+    data: &'a AppData,
+    status: Option<&str>,
+    query: &str,
+    tab_filter: Option<&str>,
+) -> (&'a ContentTab, &'a Post) {
+    let tab = tab_filter.map(|name| {
+        find_tab(data, name).unwrap_or_else(|message| {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        })
+    });
+    match resolve_slug(data, query, tab) {
+        Ok(found) => found,
+        Err(candidates) if candidates.is_empty() => {
+            if let Some(status) = status {
+                eprintln!("{}", status);
+            }
+            eprintln!("No post matches \"{}\".", query);
+            std::process::exit(1);
+        }
+        Err(candidates) => {
+            eprintln!("\"{}\" matches {} posts:", query, candidates.len());
+            for (tab, post) in candidates {
+                eprintln!("  {}\t{}\t{}", tab.name, post.slug, post.title);
+            }
+            std::process::exit(2);
+        }
+    }
+}
+
+/// The newest post in `tab`, or across the content tabs. Any post with a date that parses is
+/// newer than one without; `sort_key` only decides between two undated posts.
+fn latest_post<'a>(
+    data: &'a AppData,
+    tab: Option<&'a ContentTab>,
+) -> Option<(&'a ContentTab, &'a Post)> {
+    posts_with_tabs(data, tab)
+        .filter(|(tab, post)| tab.lists(post))
+        .max_by(|(_, a), (_, b)| compare_recency(a, b))
+}
+
+fn compare_recency(a: &Post, b: &Post) -> std::cmp::Ordering {
+    match (parse_date(&a.date), parse_date(&b.date)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Greater,
+        (None, Some(_)) => std::cmp::Ordering::Less,
+        (None, None) => a.sort_key.cmp(&b.sort_key),
+    }
+}
+
+/// `johnjeong list`: each content tab with its post count, or with a tab, that tab's posts as
+/// date, slug, and title.
+fn run_list(data: &AppData, tab: Option<&ContentTab>, json: bool) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    let Some(tab) = tab else {
+        let tabs = data
+            .tabs
+            .iter()
+            .filter_map(|tab| match tab {
+                TabData::Content(tab) => Some(tab),
+                TabData::About(_) | TabData::Tags(_) => None,
+            })
+            .collect::<Vec<_>>();
+        if json {
+            let items = tabs
+                .iter()
+                .map(|tab| {
+                    format!(
+                        "{{\"name\":{},\"dir\":{},\"posts\":{}}}",
+                        json_string(tab.name),
+                        json_string(tab.dir),
+                        tab.listed_posts().count()
+                    )
+                })
+                .collect::<Vec<_>>();
+            return writeln!(stdout, "[{}]", items.join(","));
+        }
+        for tab in tabs {
+            writeln!(stdout, "{}\t{}", tab.name, tab.listed_posts().count())?;
+        }
+        return Ok(());
+    };
+    if json {
+        let items = tab
+            .listed_posts()
+            .map(|post| {
+                format!(
+                    "{{\"date\":{},\"slug\":{},\"title\":{},\"url\":{}}}",
+                    json_string(&post.date),
+                    json_string(&post.slug),
+                    json_string(&post.title),
+                    json_string(&post.url)
+                )
+            })
+            .collect::<Vec<_>>();
+        return writeln!(stdout, "[{}]", items.join(","));
+    }
+    for post in tab.listed_posts() {
+        writeln!(
+            stdout,
+            "{}\t{}\t{}",
+            format_date(&post.date),
+            post.slug,
+            post.title
+        )?;
+    }
+    Ok(())
+}
+
+/// Line-oriented search for `johnjeong search`: every title or body line containing `query`
+/// (case-insensitive) is one hit, reported with its 1-based line and column. Line 0 is the
+/// title. Tabs, JSON fields, and match markers are the same as `--search` (`run_search`).
+fn run_grep(
+    data: &AppData,
+    status: Option<&str>,
+    query: &str,
+    tab_filter: Option<&str>,
+    json: bool,
+) -> io::Result<bool> {
+    if let Some(status) = status {
+        eprintln!("{}", status);
+    }
+
+    let mut hits = Vec::new();
+    for tab in search_tabs(data, tab_filter) {
+        for post in tab.listed_posts() {
+            let lines = std::iter::once(post.title.as_str()).chain(post.body.lines());
+            for (number, line) in lines.enumerate() {
+                let text = line.trim();
+                if let Some((match_start, match_end)) = find_ignore_case(text, query) {
+                    let indent = line.len() - line.trim_start().len();
+                    let column = line[..indent + match_start].chars().count() + 1;
+                    let snippet = Snippet {
+                        text: text.to_string(),
+                        match_start,
+                        match_end,
+                    };
+                    hits.push((tab, post, number, column, snippet));
+                }
+            }
+        }
+    }
+
+    let mut stdout = io::stdout().lock();
+    if json {
+        let items = hits
+            .iter()
+            .map(|(tab, post, line, column, snippet)| {
+                format!(
+                    "{{{},\"line\":{},\"column\":{},\"text\":{}}}",
+                    hit_json_fields(tab, post),
+                    line,
+                    column,
+                    json_string(&snippet.text)
+                )
+            })
+            .collect::<Vec<_>>();
+        writeln!(stdout, "[{}]", items.join(","))?;
+    } else {
+        let (open, close) = match_markers(&stdout);
+        for (tab, post, line, column, snippet) in &hits {
+            writeln!(
+                stdout,
+                "{}\t{}\t{}\t{}:{}\t{}",
+                tab.name,
+                format_date(&post.date),
+                post.title,
+                line,
+                column,
+                snippet.marked(open, close)
+            )?;
+        }
+    }
+    Ok(!hits.is_empty())
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Work that touches the disk, the network, or another process. The thread that reads events
+/// and renders never does any of that once the app is running: it queues a job here and shows
+/// a placeholder ("…") until the job's `AppEvent` comes back through the inbox. Jobs run one at
+/// a time, in order. The one exception is the gallery image preview, which viuer draws
+/// straight to the terminal from the file during render.
+type IoJob = Box<dyn FnOnce() -> Option<AppEvent> + Send>;
+
+static IO_QUEUE: OnceLock<Sender<IoJob>> = OnceLock::new();
+
+/// Where a long job reports how far it has got, ahead of the event it finishes with.
+static PROGRESS: OnceLock<Sender<AppEvent>> = OnceLock::new();
+
+fn start_io_worker(events: Sender<AppEvent>) {
+    let _ = PROGRESS.set(events.clone());
+    let (sender, receiver) = mpsc::channel::<IoJob>();
+    thread::spawn(move || {
+        for job in receiver {
+            if let Some(event) = job() {
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    let _ = IO_QUEUE.set(sender);
+}
+
+/// Runs `job` on the IO worker. Without one (the command-line modes) there is no UI to keep
+/// responsive, so it runs right away and its event is dropped.
+fn queue_io(job: impl FnOnce() -> Option<AppEvent> + Send + 'static) {
+    match IO_QUEUE.get() {
+        Some(queue) => {
+            let _ = queue.send(Box::new(job));
+        }
+        None => {
+            job();
+        }
+    }
+}
+
+/// Shows `message` on the status line until the next report; `None` clears it. Progress isn't
+/// kept in the `m` history.
+fn report_progress(message: Option<String>) {
+    if let Some(progress) = PROGRESS.get() {
+        let _ = progress.send(AppEvent::Progress(message));
+    }
+}
+
+thread_local! {
+    static ON_UI_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as the UI thread for `assert_off_ui_thread` until dropped.
+struct UiThread;
+
+impl UiThread {
+    fn enter() -> UiThread {
+        ON_UI_THREAD.with(|flag| flag.set(true));
+        UiThread
+    }
+}
+
+impl Drop for UiThread {
+    fn drop(&mut self) {
+        ON_UI_THREAD.with(|flag| flag.set(false));
+    }
+}
+
+/// Called first thing by the helpers that do IO. Debug builds panic if the UI thread gets
+/// there, so a blocking call added to a key handler or the render path shows up right away.
+fn assert_off_ui_thread(what: &str) {
+    debug_assert!(
+        !ON_UI_THREAD.with(Cell::get),
+        "{} on the UI thread; send it through queue_io",
+        what
+    );
+}
+
+/// Everything that can change the app between frames. Terminal input, the poll timer, and
+/// worker threads all arrive as events, and `update` is the one place they touch `AppState`.
+enum AppEvent {
+    Key(KeyEvent),
+    Resize,
+    /// The poll timeout passed without input, so time-based work can run.
+    Tick,
+    /// Text from a bracketed paste.
+    Paste(String),
+    /// A mouse wheel notch at `column`: `-1` up, `1` down.
+    Scroll {
+        column: u16,
+        delta: i32,
+    },
+    /// A left click.
+    Click {
+        column: u16,
+        row: u16,
+    },
+    /// A message for the status line from a worker thread.
+    Status(String),
+    /// `(url, sort_key, location)` for every gallery image whose dates were deferred.
+    GalleryDates(Vec<(String, String, Option<String>)>),
+    /// A post's snapshot from its last reading, once the IO worker has read it.
+    Snapshot {
+        url: String,
+        previous: Option<String>,
+    },
+    /// The `L` list for the post at `url`, collected on the IO worker.
+    Links {
+        url: String,
+        links: Vec<(String, LinkTarget)>,
+    },
+    /// A post's file read again after `e`; `None` if it was deleted.
+    Reloaded {
+        path: PathBuf,
+        post: Option<Box<Post>>,
+    },
+    /// Posts the watcher found added, changed, or (`None`) deleted outside the app.
+    ContentChanged(Vec<(PathBuf, Option<Box<Post>>)>),
+    /// `command_problem` for a `:` line.
+    CommandChecked {
+        line: String,
+        problem: Option<String>,
+    },
+    /// Tab completions for the `:` line up to the cursor, `before`.
+    Completions {
+        before: String,
+        completions: Vec<String>,
+    },
+    /// A running job's progress for the status line; `None` when it is done.
+    Progress(Option<String>),
+    /// Everything loaded again from the directory `:content` picked.
+    ContentLoaded {
+        dir: PathBuf,
+        data: Box<AppData>,
+        status: Option<String>,
+    },
+}
+
+/// What the loop should do after `update` handles an event.
+#[derive(Default)]
+struct Outcome {
+    quit: bool,
+    redraw: bool,
+    /// The user did something, which counts toward the next autosave.
+    changed: bool,
+}
+
+fn update(event: AppEvent, data: &mut AppData, state: &mut AppState) -> io::Result<Outcome> {
+    Ok(match event {
+        AppEvent::Key(key) => Outcome {
+            quit: handle_key(key, data, state)?,
+            redraw: true,
+            changed: true,
+        },
+        AppEvent::Resize => Outcome {
+            redraw: true,
+            ..Outcome::default()
+        },
+        AppEvent::Tick => Outcome::default(),
+        AppEvent::Paste(text) => {
+            handle_paste(&text, state);
+            Outcome {
+                redraw: true,
+                changed: true,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::Scroll { column, delta } => {
+            let moved = handle_scroll(data, state, column, delta)?;
+            Outcome {
+                redraw: moved,
+                changed: moved,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::Click { column, row } => {
+            let clicked = handle_click(data, state, column, row);
+            Outcome {
+                redraw: clicked,
+                changed: clicked,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::Status(message) => {
+            state.status = Some(message);
+            Outcome {
+                redraw: true,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::GalleryDates(details) => {
+            apply_gallery_dates(data, state, details);
+            Outcome {
+                redraw: true,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::Snapshot { url, previous } => {
+            state.reads.loaded(url, previous);
+            Outcome {
+                redraw: true,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::Links { url, links } => {
+            show_link_list(data, state, &url, links);
+            Outcome {
+                redraw: true,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::Reloaded { path, post } => {
+            apply_reloaded(data, state, &path, post);
+            Outcome {
+                redraw: true,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::ContentChanged(changes) => {
+            apply_content_changes(data, state, changes);
+            Outcome {
+                redraw: true,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::CommandChecked { line, problem } => {
+            let open = state.command.is_some();
+            if open {
+                state.command_checks.insert(line, problem);
+            }
+            Outcome {
+                redraw: open,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::Completions {
+            before,
+            completions,
+        } => {
+            let completed = state.command.as_mut().is_some_and(|editor| {
+                editor.complete(|typed| {
+                    if typed == before {
+                        completions.clone()
+                    } else {
+                        Vec::new()
+                    }
+                })
+            });
+            check_command_line(state);
+            Outcome {
+                redraw: completed,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::Progress(message) => {
+            state.progress = message;
+            Outcome {
+                redraw: true,
+                ..Outcome::default()
+            }
+        }
+        AppEvent::ContentLoaded {
+            dir,
+            data: loaded,
+            status,
+        } => {
+            *data = *loaded;
+            state.tab_index = state.tab_index.min(data.tabs.len().saturating_sub(1));
+            state.query.clear();
+            state.tag_filter = None;
+            reset_selection(state);
+            state.status =
+                Some(status.unwrap_or_else(|| format!("Reading content from {}", dir.display())));
+            Outcome {
+                redraw: true,
+                ..Outcome::default()
+            }
+        }
+    })
+}
+
+/// Waits up to `timeout` for terminal input worth handling.
+fn next_input(timeout: Duration) -> io::Result<AppEvent> {
+    if !event::poll(timeout)? {
+        return Ok(AppEvent::Tick);
+    }
+    Ok(match event::read()? {
+        Event::Key(key) => AppEvent::Key(key),
+        Event::Resize(_, _) => AppEvent::Resize,
+        Event::Paste(text) => AppEvent::Paste(text),
+        Event::Mouse(mouse) => match mouse.kind {
+            MouseEventKind::ScrollUp => AppEvent::Scroll {
+                column: mouse.column,
+                delta: -1,
+            },
+            MouseEventKind::ScrollDown => AppEvent::Scroll {
+                column: mouse.column,
+                delta: 1,
+            },
+            MouseEventKind::Down(MouseButton::Left) => AppEvent::Click {
+                column: mouse.column,
+                row: mouse.row,
+            },
+            _ => AppEvent::Tick,
+        },
+        _ => AppEvent::Tick,
+    })
+}
+
+fn run_app(
+    stdout: &mut Stdout,
+    terminal: &TerminalGuard,
+    data: &mut AppData,
+    state: &mut AppState,
+    autosave: &mut Option<Autosave>,
+    inbox: &Receiver<AppEvent>,
+) -> io::Result<()> {
+    let _ui_thread = UiThread::enter();
+    let mut needs_redraw = true;
+    log_status(state);
+
+    loop {
+        if needs_redraw {
+            if let Some(post) = selected_post(data, state) {
+                state.reads.visit(post);
+                if let Some(timer) = state.timer.as_mut() {
+                    timer.read(post);
+                }
+            }
+            render(stdout, data, state)?;
+            needs_redraw = false;
+        }
+
+        // With reduced motion nothing animates, so sleep until input arrives or a pending
+        // autosave falls due.
+        let timeout = if reduced_motion() {
+            autosave
+                .as_ref()
+                .and_then(Autosave::due_in)
+                .unwrap_or(IDLE_POLL)
+        } else {
+            Duration::from_millis(200)
+        };
+
+        // A reading timer wakes the loop only when the minute it shows changes.
+        let timeout = match &state.timer {
+            Some(timer) => timeout.min(timer.next_change()),
+            None => timeout,
+        };
+
+        // A lone `g` turns into the gallery shortcut once the `gg` window closes.
+        let timeout = match state.pending_key {
+            Some((_, at)) => timeout.min(KEY_SEQUENCE_TIMEOUT.saturating_sub(at.elapsed())),
+            None => timeout,
+        };
+
+        let timeout = match state.status_at {
+            Some(at) => timeout.min(STATUS_TIMEOUT.saturating_sub(at.elapsed())),
+            None => timeout,
+        };
+
+        let timeout = match state.notice.as_ref().and_then(|notice| notice.last) {
+            Some(last) => timeout.min(NOTIFY_QUIET.saturating_sub(last.elapsed())),
+            None => timeout,
+        };
+
+        // Worker events are drained first; if any arrived, input is only checked, not awaited.
+        let mut events: Vec<AppEvent> = inbox.try_iter().collect();
+        let timeout = if events.is_empty() {
+            timeout
+        } else {
+            Duration::ZERO
+        };
+        events.push(next_input(timeout)?);
+
+        if RESUMED.swap(false, Ordering::SeqCst) {
+            terminal.resume(stdout)?;
+            needs_redraw = true;
+        }
+
+        if let Some(timer) = state.timer.as_mut() {
+            let shown = timer.minutes_left();
+            if timer.advance(timeout) {
+                state.timer_done = Some(timer.summary());
+                state.timer = None;
+                needs_redraw = true;
+            } else if timer.minutes_left() != shown {
+                needs_redraw = true;
+            }
+        }
+
+        let status_before = state.status.clone();
+        let mut changed = false;
+        for event in events {
+            if is_suspend_key(&event) {
+                terminal.suspend(stdout)?;
+                needs_redraw = true;
+                continue;
+            }
+            let outcome = update(event, data, state)?;
+            if outcome.quit {
+                return Ok(());
+            }
+            needs_redraw |= outcome.redraw;
+            changed |= outcome.changed;
+        }
+        if let Some(hand_off) = state.hand_off.take() {
+            run_hand_off(stdout, terminal, hand_off, state)?;
+            needs_redraw = true;
+        }
+        if expire_pending_key(data, state) {
+            needs_redraw = true;
+            changed = true;
+        }
+        if state.status != status_before {
+            state.status_at = state.status.as_ref().map(|_| Instant::now());
+            log_status(state);
+        } else if state
+            .status_at
+            .is_some_and(|at| at.elapsed() >= STATUS_TIMEOUT)
+        {
+            state.status = None;
+            state.status_at = None;
+            needs_redraw = true;
+        }
+
+        if state.notice.as_ref().is_some_and(Notice::due) {
+            let notice = state.notice.take().unwrap_or_default();
+            let notify = NOTIFY.get().copied().unwrap_or_default();
+            if notify.any() {
+                let tmux = env::var_os("TMUX").is_some();
+                stdout.write_all(notify.sequence(&notice.message(), tmux).as_bytes())?;
+                stdout.flush()?;
+            }
+        }
+
+        if let Some(autosave) = autosave.as_mut() {
+            autosave.update(Session::capture(data, state), changed);
+        }
+    }
+}
+
+/// Programs that take over the terminal until they exit.
+enum HandOff {
+    /// `v`: a post's text for `$PAGER`.
+    Pager(String),
+    /// `e`: a post's markdown file for `$EDITOR`, and the base its URL was built on.
+    Editor { path: PathBuf, base_url: String },
+}
+
+fn run_hand_off(
+    stdout: &mut Stdout,
+    terminal: &TerminalGuard,
+    hand_off: HandOff,
+    state: &mut AppState,
+) -> io::Result<()> {
+    match hand_off {
+        HandOff::Pager(text) => {
+            if let Err(err) = terminal.run_outside(stdout, || page_text(&text))? {
+                state.status = Some(format!("Failed to run the pager ({})", err));
+            }
+        }
+        HandOff::Editor { path, base_url } => {
+            match terminal.run_outside(stdout, || edit_file(&path))? {
+                Ok(()) => {
+                    state.own_edits.insert(path.clone(), Instant::now());
+                    reload_post(path, base_url);
+                }
+                Err(err) => state.status = Some(format!("Failed to run the editor ({})", err)),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs `$EDITOR` (`vi` by default) on `path` and waits for it.
+fn edit_file(path: &Path) -> io::Result<()> {
+    let editor = env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = Command::new(program).args(words).arg(path).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} exited with {}",
+            program, status
+        )))
+    }
+}
+
+fn open_editor(data: &AppData, state: &mut AppState) {
+    let Some(post) = selected_post(data, state).filter(|post| post.file.is_some()) else {
+        state.status = Some("This entry is not editable.".to_string());
+        return;
+    };
+    let path = post.file.clone().unwrap_or_default();
+    let base_url = post
+        .url
+        .rsplit_once('/')
+        .map_or_else(String::new, |(base, _)| base.to_string());
+    if let Err(message) = check_capability(Capability::OpenFile) {
+        state.status = Some(message);
+        return;
+    }
+    state.hand_off = Some(HandOff::Editor { path, base_url });
+}
+
+/// How long a second save may overwrite the file the first one found in the way.
+const SAVE_CONFIRM_WINDOW: Duration = Duration::from_secs(3);
+
+/// Copies the selected post's file, frontmatter and all, into the current directory under its
+/// own name (`2024-03-01-why-simple-wins.md`), or a gallery photo's image. An existing file is
+/// only replaced by a second save within `SAVE_CONFIRM_WINDOW`.
+fn save_selected(data: &AppData, state: &mut AppState) {
+    let gallery = match data.tabs.get(state.tab_index) {
+        Some(TabData::Content(tab)) if is_gallery_tab(tab) => {
+            visible_posts(tab, state).get(state.list_index).copied()
+        }
+        _ => None,
+    };
+    let Some(source) = gallery
+        .or_else(|| selected_post(data, state))
+        .and_then(|post| post.file.clone())
+    else {
+        state.status = Some("Nothing to save here.".to_string());
+        return;
+    };
+    if let Err(message) = check_capability(Capability::WriteState) {
+        state.status = Some(message);
+        return;
+    }
+    let Some(name) = source.file_name().map(PathBuf::from) else {
+        return;
+    };
+    let overwrite = state
+        .save_pending
+        .take()
+        .is_some_and(|(pending, at)| pending == name && at.elapsed() < SAVE_CONFIRM_WINDOW);
+    state.save_pending = Some((name.clone(), Instant::now()));
+    queue_io(move || {
+        Some(AppEvent::Status(
+            match save_copy(&source, &name, overwrite) {
+                Ok(path) => format!("Saved {}", path.display()),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => format!(
+                    "{} already exists; save again to overwrite it.",
+                    name.display()
+                ),
+                Err(err) => format!("Failed to save {} ({})", name.display(), err),
+            },
+        ))
+    });
+}
+
+/// Copies `source` to `name` in the current directory and returns where it went. Without
+/// `overwrite`, an existing file fails with `AlreadyExists` and is left alone.
+fn save_copy(source: &Path, name: &Path, overwrite: bool) -> io::Result<PathBuf> {
+    assert_off_ui_thread("saving a copy");
+    let mut source = fs::File::open(source)?;
+    let path = env::current_dir()?.join(name);
+    let mut target = create_file(&path, !overwrite)?;
+    io::copy(&mut source, &mut target)?;
+    Ok(path)
+}
+
+/// Reads a post again after `e`, on the IO worker. Whether it is a draft is decided when the
+/// result arrives, since only the post's tab knows if it has drafts.
+fn reload_post(path: PathBuf, base_url: String) {
+    queue_io(move || {
+        let post = match load_post(&path, &base_url, false) {
+            Ok(post) => Some(Box::new(post)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Some(AppEvent::Status(format!(
+                    "Failed to reload {} ({})",
+                    path.display(),
+                    err
+                )))
+            }
+        };
+        Some(AppEvent::Reloaded { path, post })
+    });
+}
+
+/// Swaps in a post re-read after `e`, or drops it if the file is gone. An essay unpublished in
+/// the editor stays as a draft, hidden unless drafts are shown. The Tags tab is rebuilt to
+/// match.
+fn apply_reloaded(data: &mut AppData, state: &mut AppState, path: &Path, post: Option<Box<Post>>) {
+    match put_post(data, path, post) {
+        Some((tab, PostChange::Removed, title)) => {
+            let TabData::Content(tab) = &data.tabs[tab] else {
+                return;
+            };
+            state.status = Some(format!("{} is gone from {}", title, tab.name));
+        }
+        Some((tab, _, title)) => {
+            state.status = Some(if hidden_draft(data, tab, path, state) {
+                format!("{} is now a draft and hidden", title)
+            } else {
+                format!("Reloaded {}", title)
+            });
+        }
+        None => {}
+    }
+    refresh_after_changes(data, state);
+}
+
+/// How often the watcher looks for posts added, changed, or deleted outside the app.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Changes to a file edited with `e` within this long after the editor exits are the user's
+/// own, so they don't notify.
+const OWN_EDIT_GRACE: Duration = Duration::from_secs(5);
+
+/// How long the content has to stay still before the changes seen so far go out as one
+/// notification.
+const NOTIFY_QUIET: Duration = Duration::from_millis(1500);
+
+/// Characters of a notification's text; terminals show little more in a desktop banner.
+const NOTIFY_MAX_CHARS: usize = 80;
+
+/// A content directory the watcher scans, and the base URL its posts are loaded with.
+struct WatchedDir {
+    dir: PathBuf,
+    base_url: String,
+}
+
+/// Every markdown post in `dir` with its modification time.
+fn post_mtimes(dir: &Path) -> HashMap<PathBuf, std::time::SystemTime> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().and_then(|ext| ext.to_str()) == Some("md") && !is_section_index(path)
+        })
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+/// Polls the content directories every `WATCH_INTERVAL` and sends whatever changed since the
+/// last look as one `AppEvent::ContentChanged`: new and modified posts read again, deleted
+/// ones as `None`.
+fn start_content_watcher(dirs: Vec<WatchedDir>, events: Sender<AppEvent>) {
+    if dirs.is_empty() {
+        return;
+    }
+    thread::spawn(move || {
+        let mut seen = dirs
+            .iter()
+            .map(|watched| post_mtimes(&watched.dir))
+            .collect::<Vec<_>>();
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let mut changes = Vec::new();
+            for (watched, seen) in dirs.iter().zip(seen.iter_mut()) {
+                let now = post_mtimes(&watched.dir);
+                for (path, modified) in &now {
+                    if seen.get(path) == Some(modified) {
+                        continue;
+                    }
+                    if let Ok(post) = load_post(path, &watched.base_url, false) {
+                        changes.push((path.clone(), Some(Box::new(post))));
+                    }
+                }
+                changes.extend(
+                    seen.keys()
+                        .filter(|path| !now.contains_key(*path))
+                        .map(|path| (path.clone(), None)),
+                );
+                *seen = now;
+            }
+            if !changes.is_empty() && events.send(AppEvent::ContentChanged(changes)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// What a change did to a tab's list.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PostChange {
+    Added,
+    Updated,
+    Removed,
+}
+
+/// Puts a post read from `path` into the tab it belongs to: in place of the old copy, or for a
+/// file the tab hasn't seen, next to the posts it sorts with. `None` drops the old copy. Returns
+/// the tab's index, what happened, and the post's title; `None` when no tab holds `path`.
+fn put_post(
+    data: &mut AppData,
+    path: &Path,
+    post: Option<Box<Post>>,
+) -> Option<(usize, PostChange, String)> {
+    let (tab_index, tab) = data
+        .tabs
+        .iter_mut()
+        .enumerate()
+        .find_map(|(index, tab)| match tab {
+            TabData::Content(tab)
+                if tab
+                    .posts
+                    .iter()
+                    .any(|old| old.file.as_deref() == Some(path))
+                    || (tab.local_dir.as_deref() == path.parent() && !is_gallery_tab(tab)) =>
+            {
+                Some((index, tab))
+            }
+            _ => None,
+        })?;
+    let existing = tab
+        .posts
+        .iter()
+        .position(|old| old.file.as_deref() == Some(path));
+    match (existing, post) {
+        (Some(index), Some(mut post)) => {
+            // As `build_app_data` loads them: only essays have drafts.
+            post.draft = tab.dir == "essays" && is_unpublished(&post.fields);
+            let title = post.title.clone();
+            tab.posts[index] = *post;
+            Some((tab_index, PostChange::Updated, title))
+        }
+        (None, Some(mut post)) => {
+            post.draft = tab.dir == "essays" && is_unpublished(&post.fields);
+            let title = post.title.clone();
+            let index = tab
+                .posts
+                .partition_point(|old| (&post.sort_key, &old.url) < (&old.sort_key, &post.url));
+            tab.posts.insert(index, *post);
+            Some((tab_index, PostChange::Added, title))
+        }
+        (Some(index), None) => {
+            let removed = tab.posts.remove(index);
+            Some((tab_index, PostChange::Removed, removed.title))
+        }
+        (None, None) => None,
+    }
+}
+
+/// Whether the post from `path` in tab `tab` is a draft the list doesn't show right now.
+fn hidden_draft(data: &AppData, tab: usize, path: &Path, state: &AppState) -> bool {
+    !state.show_drafts
+        && matches!(&data.tabs[tab], TabData::Content(tab) if tab
+            .posts
+            .iter()
+            .any(|post| post.draft && post.file.as_deref() == Some(path)))
+}
+
+/// Rebuilds the Tags tab after posts change and keeps the selection on the list.
+fn refresh_after_changes(data: &mut AppData, state: &mut AppState) {
+    let tags = tags_tab(&data.tabs);
+    if let Some(TabData::Tags(old)) = data
+        .tabs
+        .iter_mut()
+        .find(|tab| matches!(tab, TabData::Tags(_)))
+    {
+        *old = tags;
+    }
+    state.list_index = state
+        .list_index
+        .min(list_length(data, state).saturating_sub(1));
+}
+
+/// Applies what the watcher found. Changes to a file just edited with `e` go in quietly; the
+/// rest are counted toward the next notification and summed up on the status line.
+fn apply_content_changes(
+    data: &mut AppData,
+    state: &mut AppState,
+    changes: Vec<(PathBuf, Option<Box<Post>>)>,
+) {
+    state
+        .own_edits
+        .retain(|_, at| at.elapsed() < OWN_EDIT_GRACE);
+    let mut counted = Vec::new();
+    for (path, post) in changes {
+        let Some((tab_index, change, _)) = put_post(data, &path, post) else {
+            continue;
+        };
+        let own = state.own_edits.contains_key(&path);
+        if !own && !hidden_draft(data, tab_index, &path, state) {
+            let TabData::Content(tab) = &data.tabs[tab_index] else {
+                continue;
+            };
+            counted.push((tab.name.to_string(), change));
+        }
+    }
+    refresh_after_changes(data, state);
+    if counted.is_empty() {
+        return;
+    }
+    let notice = state.notice.get_or_insert_with(Notice::default);
+    for (tab, change) in counted {
+        notice.count(&tab, change);
+    }
+    notice.last = Some(Instant::now());
+    state.status = Some(notice.message());
+}
+
+/// Content changes seen since the last notification, by tab, in the order tabs first changed.
+#[derive(Default)]
+struct Notice {
+    /// `(tab, added, updated, removed)`.
+    tabs: Vec<(String, usize, usize, usize)>,
+    /// When the latest change arrived; the notification waits for `NOTIFY_QUIET` after it.
+    last: Option<Instant>,
+}
+
+impl Notice {
+    fn count(&mut self, tab: &str, change: PostChange) {
+        let index = match self.tabs.iter().position(|(name, ..)| name == tab) {
+            Some(index) => index,
+            None => {
+                self.tabs.push((tab.to_string(), 0, 0, 0));
+                self.tabs.len() - 1
+            }
+        };
+        let (_, added, updated, removed) = &mut self.tabs[index];
+        match change {
+            PostChange::Added => *added += 1,
+            PostChange::Updated => *updated += 1,
+            PostChange::Removed => *removed += 1,
+        }
+    }
+
+    /// `2 new Daily Logs, 1 updated in Essays`.
+    fn message(&self) -> String {
+        let mut parts = Vec::new();
+        for (tab, added, updated, removed) in &self.tabs {
+            if *added > 0 {
+                parts.push(format!("{} new {}", added, tab));
+            }
+            if *updated > 0 {
+                parts.push(format!("{} updated in {}", updated, tab));
+            }
+            if *removed > 0 {
+                parts.push(format!("{} removed from {}", removed, tab));
+            }
+        }
+        parts.join(", ")
+    }
+
+    /// Whether the content has been still long enough to send it.
+    fn due(&self) -> bool {
+        self.last.is_some_and(|last| last.elapsed() >= NOTIFY_QUIET)
+    }
+}
+
+/// How a notification gets attention: any of the terminal bell, an OSC 9 desktop notification
+/// (iTerm2, WezTerm, Windows Terminal, ConEmu), and an OSC 777 one (rxvt-unicode, foot,
+/// Ghostty). From `notify` in the config, e.g. `"bell, osc9"`; all off by default.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+struct Notify {
+    bell: bool,
+    osc9: bool,
+    osc777: bool,
+}
+
+impl Notify {
+    /// A comma- or space-separated list of `bell`, `osc9`, and `osc777`; `true` is the bell alone
+    /// and `false` or `off` is nothing. `None` for anything else.
+    fn parse(value: &str) -> Option<Notify> {
+        let mut notify = Notify::default();
+        for word in value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|word| !word.is_empty())
+        {
+            match word.to_lowercase().as_str() {
+                "bell" | "true" | "on" => notify.bell = true,
+                "osc9" => notify.osc9 = true,
+                "osc777" => notify.osc777 = true,
+                "false" | "off" | "none" => {}
+                _ => return None,
+            }
+        }
+        Some(notify)
+    }
+
+    fn any(&self) -> bool {
+        self.bell || self.osc9 || self.osc777
+    }
+
+    /// The bytes that deliver `message`. Inside tmux, OSC sequences are wrapped so tmux passes
+    /// them through to the outer terminal; the bell is left to tmux, which flags the window.
+    fn sequence(&self, message: &str, tmux: bool) -> String {
+        let text = notification_text(message);
+        let mut oscs = Vec::new();
+        if self.osc9 {
+            oscs.push(format!("\x1b]9;{}\x07", text));
+        }
+        if self.osc777 {
+            oscs.push(format!(
+                "\x1b]777;notify;johnjeong;{}\x07",
+                text.replace(';', ",")
+            ));
+        }
+        let mut out = String::new();
+        for osc in oscs {
+            if tmux {
+                out.push_str(&format!(
+                    "\x1bPtmux;{}\x1b\\",
+                    osc.replace('\x1b', "\x1b\x1b")
+                ));
+            } else {
+                out.push_str(&osc);
+            }
+        }
+        if self.bell {
+            out.push('\x07');
+        }
+        out
+    }
+}
+
+/// `message` made safe inside an OSC string: control characters (which could end the sequence
+/// or start another) become spaces, whitespace runs collapse, and it is cut to
+/// `NOTIFY_MAX_CHARS`.
+fn notification_text(message: &str) -> String {
+    let clean = message
+        .chars()
+        .map(|ch| if ch.is_control() { ' ' } else { ch })
+        .collect::<String>();
+    let clean = clean.split_whitespace().collect::<Vec<_>>().join(" ");
+    if clean.chars().count() <= NOTIFY_MAX_CHARS {
+        return clean;
+    }
+    let cut = clean.chars().take(NOTIFY_MAX_CHARS - 1).collect::<String>();
+    format!("{}…", cut.trim_end())
+}
+
+/// `notify` from the config, set in `main`.
+static NOTIFY: OnceLock<Notify> = OnceLock::new();
+
+/// Pipes `text` into `$PAGER` (`less -R` by default) and waits for it. Quitting the pager before
+/// the end closes the pipe, which isn't an error.
+fn page_text(text: &str) -> io::Result<()> {
+    let pager = env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| "less -R".to_string());
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or("less");
+    let mut child = Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        match stdin.write_all(text.as_bytes()) {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(err),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+fn open_pager(data: &AppData, state: &mut AppState) {
+    let Some(post) = selected_post(data, state) else {
+        state.status = Some("Only posts open in the pager.".to_string());
+        return;
+    };
+    let mut text = post.title.clone();
+    if !post.date.is_empty() {
+        text.push('\n');
+        text.push_str(&post.date);
+    }
+    text.push_str("\n\n");
+    text.push_str(&post.body);
+    text.push('\n');
+    if let Err(message) = check_capability(Capability::RunProgram) {
+        state.status = Some(message);
+        return;
+    }
+    state.hand_off = Some(HandOff::Pager(text));
+}
+
+/// How long a status message set by an action stays on screen.
+const STATUS_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// How many status messages the `m` overlay keeps.
+const STATUS_LOG_LIMIT: usize = 50;
+
+/// Records the current status message, if any, dropping the oldest past `STATUS_LOG_LIMIT`.
+fn log_status(state: &mut AppState) {
+    let Some(status) = &state.status else {
+        return;
+    };
+    if state.status_log.len() == STATUS_LOG_LIMIT {
+        state.status_log.pop_front();
+    }
+    state.status_log.push_back((Instant::now(), status.clone()));
+}
+
+/// How long the first key of a sequence like `gg` waits for the second.
+const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Acts on a pending key whose sequence timed out: a single `g` jumps to the gallery.
+fn expire_pending_key(data: &AppData, state: &mut AppState) -> bool {
+    match state.pending_key {
+        Some((key, at)) if at.elapsed() >= KEY_SEQUENCE_TIMEOUT => {
+            state.pending_key = None;
+            if key == 'g' {
+                go_to_gallery(data, state);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+fn go_to_gallery(data: &AppData, state: &mut AppState) {
+    match gallery_tab_index(data) {
+        Some(index) => switch_tab(state, index, data.tabs.len()),
+        None => state.status = Some("There is no gallery tab.".to_string()),
+    }
+}
+
+/// Lines PageUp/PageDown move: a screen of content, keeping two lines of overlap.
+fn page_lines(state: &AppState) -> i32 {
+    state.content_height.saturating_sub(2).max(1) as i32
+}
+
+/// Lines ctrl-d/ctrl-u move: half a screen of content.
+fn half_page_lines(state: &AppState) -> i32 {
+    (state.content_height / 2).max(1) as i32
+}
+
+/// The post shown in the content pane, if it is a text post.
+fn selected_post<'a>(data: &'a AppData, state: &AppState) -> Option<&'a Post> {
+    let tab = match data.tabs.get(state.tab_index)? {
+        TabData::Content(tab) if !is_gallery_tab(tab) => tab,
+        TabData::Tags(tags) if state.tag_filter.is_some() => &tags.posts,
+        _ => return None,
+    };
+    visible_posts(tab, state).get(state.list_index).copied()
+}
+
+/// Bodies of posts as they were when last read, kept under `state_dir()/snapshots` so `d` can
+/// show what changed since. A post's old snapshot is loaded the first time it is shown in a
+/// session and then replaced with the current body, so the diff stays available until exit.
+/// Both happen on the IO worker; `previous` fills in when its `AppEvent::Snapshot` arrives.
+#[derive(Default)]
+struct ReadLog {
+    previous: HashMap<String, Option<String>>,
+    requested: HashSet<String>,
+}
+
+impl ReadLog {
+    fn visit(&mut self, post: &Post) {
+        if !self.requested.insert(post.url.clone()) {
+            return;
+        }
+        let url = post.url.clone();
+        let body = post.body.clone();
+        queue_io(move || {
+            let path = snapshot_path(&url);
+            let previous = path.as_ref().and_then(|path| fs::read_to_string(path).ok());
+            if let Some(path) = path.filter(|_| check_capability(Capability::WriteState).is_ok()) {
+                if body.len() <= SNAPSHOT_MAX_BYTES {
+                    let _ = save_snapshot(&path, &body);
+                }
+            }
+            Some(AppEvent::Snapshot { url, previous })
+        });
+    }
+
+    fn loaded(&mut self, url: String, previous: Option<String>) {
+        self.previous.insert(url, previous);
+    }
+
+    fn pending(&self, url: &str) -> bool {
+        self.requested.contains(url) && !self.previous.contains_key(url)
+    }
+
+    fn previous(&self, url: &str) -> Option<&str> {
+        self.previous.get(url)?.as_deref()
+    }
+}
+
+fn snapshot_path(url: &str) -> Option<PathBuf> {
+    state_dir().map(|dir| {
+        dir.join("snapshots")
+            .join(format!("{:016x}", fnv1a(url.as_bytes())))
+    })
+}
+
+/// FNV-1a, for names and checksums that must stay the same across builds (std's hasher makes
+/// no such promise).
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325_u64, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Writes a snapshot, then drops the least recently read ones beyond `SNAPSHOT_LIMIT`.
+fn save_snapshot(path: &Path, body: &str) -> io::Result<()> {
+    let Some(dir) = path.parent() else {
+        return Ok(());
+    };
+    create_dirs(dir)?;
+    write_atomic(path, body.as_bytes())?;
+
+    let mut snapshots = fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|meta| meta.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    if snapshots.len() > SNAPSHOT_LIMIT {
+        snapshots.sort();
+        for (_, stale) in &snapshots[..snapshots.len() - SNAPSHOT_LIMIT] {
+            let _ = remove_file(stale);
+        }
+    }
+    Ok(())
+}
+
+/// `d`: swap the body for the changes since the last read, or say why there are none.
+fn toggle_diff(data: &AppData, state: &mut AppState) {
+    let Some(post) = selected_post(data, state) else {
+        return;
+    };
+    if state.diff_view.as_ref() == Some(&post.url) {
+        state.diff_view = None;
+        state.content_scroll = 0;
+        return;
+    }
+    if state.reads.pending(&post.url) {
+        state.status = Some("Still loading the last copy of this post…".to_string());
+        return;
+    }
+    match state.reads.previous(&post.url) {
+        None => {
+            state.status =
+                Some("No earlier copy of this post; one is kept each time you read it.".to_string())
+        }
+        Some(previous) if previous == post.body => {
+            state.status = Some("No changes since you last read this post.".to_string())
+        }
+        Some(_) => {
+            state.diff_view = Some(post.url.clone());
+            state.content_scroll = 0;
+        }
+    }
+}
+
+/// `D`: show or hide drafts, keeping the selected post if it is still listed.
+fn toggle_drafts(data: &AppData, state: &mut AppState) {
+    let selected = selected_post(data, state).map(|post| post.url.clone());
+    state.show_drafts = !state.show_drafts;
+    let drafts = data
+        .tabs
+        .iter()
+        .filter_map(|tab| match tab {
+            TabData::Content(tab) => Some(tab),
+            _ => None,
+        })
+        .flat_map(|tab| &tab.posts)
+        .filter(|post| post.draft)
+        .count();
+    state.status = Some(if state.show_drafts {
+        format!(
+            "Showing {} draft{}.",
+            drafts,
+            if drafts == 1 { "" } else { "s" }
+        )
+    } else {
+        "Drafts hidden.".to_string()
+    });
+    let listed = match data.tabs.get(state.tab_index) {
+        Some(TabData::Content(tab)) => visible_posts(tab, state),
+        Some(TabData::Tags(tags)) if state.tag_filter.is_some() => {
+            visible_posts(&tags.posts, state)
+        }
+        _ => return,
+    };
+    match listed
+        .iter()
+        .position(|post| selected.as_ref() == Some(&post.url))
+    {
+        Some(index) => state.list_index = index,
+        None => {
+            state.list_index = state.list_index.min(listed.len().saturating_sub(1));
+            state.content_scroll = 0;
+        }
+    }
+}
+
+enum DiffLine<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+/// Line-level diff via longest common subsequence. Bodies too large for the quadratic table
+/// fall back to "everything removed, everything added".
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut diff = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::Same(line))
+        .collect::<Vec<_>>();
+    if old_mid.len() * new_mid.len() > DIFF_MAX_CELLS {
+        diff.extend(old_mid.iter().map(|line| DiffLine::Removed(line)));
+        diff.extend(new_mid.iter().map(|line| DiffLine::Added(line)));
+    } else {
+        // lengths[i][j]: LCS length of old_mid[i..] and new_mid[j..].
+        let mut lengths = vec![vec![0usize; new_mid.len() + 1]; old_mid.len() + 1];
+        for i in (0..old_mid.len()).rev() {
+            for j in (0..new_mid.len()).rev() {
+                lengths[i][j] = if old_mid[i] == new_mid[j] {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_mid.len() || j < new_mid.len() {
+            if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+                diff.push(DiffLine::Same(old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if i < old_mid.len()
+                && (j == new_mid.len() || lengths[i + 1][j] >= lengths[i][j + 1])
+            {
+                diff.push(DiffLine::Removed(old_mid[i]));
+                i += 1;
+            } else {
+                diff.push(DiffLine::Added(new_mid[j]));
+                j += 1;
+            }
+        }
+    }
+    diff.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Same(line)),
+    );
+    diff
+}
+
+/// Unified-diff style lines: changes with `DIFF_CONTEXT` unchanged lines around them, and a
+/// `⋯` between hunks.
+fn layout_diff(old: &str, new: &str, width: usize) -> Vec<StyledLine> {
+    let diff = diff_lines(old, new);
+    let changed = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let near_change = |index: usize| {
+        changed
+            .iter()
+            .any(|&change| index.abs_diff(change) <= DIFF_CONTEXT)
+    };
+
+    let mut lines = Vec::new();
+    let mut skipped = false;
+    for (index, line) in diff.iter().enumerate() {
+        if !near_change(index) {
+            skipped = true;
+            continue;
+        }
+        if skipped && !lines.is_empty() {
+            lines.push(StyledLine {
+                spans: vec![Span::new("⋯", SpanStyle::Dim)],
+                kind: LineKind::Body,
+            });
+        }
+        skipped = false;
+        let (prefix, text, style) = match line {
+            DiffLine::Same(text) => ("  ", *text, SpanStyle::Plain),
+            DiffLine::Added(text) => ("+ ", *text, SpanStyle::Added),
+            DiffLine::Removed(text) => ("- ", *text, SpanStyle::Removed),
+        };
+        for mut wrapped in wrap_line(&[Span::new(text, style)], width, prefix) {
+            for span in &mut wrapped.spans {
+                span.style = style;
+            }
+            lines.push(wrapped);
+        }
+    }
+    lines
+}
+
+/// The part of `AppState` that survives restarts: which post was open and how far down.
+#[derive(Clone, PartialEq, Default)]
+struct Session {
+    tab_index: usize,
+    post_url: String,
+    content_scroll: usize,
+}
+
+impl Session {
+    fn capture(data: &AppData, state: &AppState) -> Session {
+        let post_url = match data.tabs.get(state.tab_index) {
+            Some(TabData::Content(tab)) => visible_posts(tab, state)
+                .get(state.list_index)
+                .map(|post| post.url.clone())
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+        Session {
+            tab_index: state.tab_index,
+            post_url,
+            content_scroll: state.content_scroll,
+        }
+    }
+
+    fn restore(&self, data: &AppData, state: &mut AppState) {
+        let Some(tab) = data.tabs.get(self.tab_index) else {
+            return;
+        };
+        state.tab_index = self.tab_index;
+        if let TabData::Content(tab) = tab {
+            if let Some(index) = visible_posts(tab, state)
+                .iter()
+                .position(|post| post.url == self.post_url)
+            {
+                state.list_index = index;
+                state.content_scroll = self.content_scroll;
+            }
+        }
+    }
+
+    fn serialize(&self) -> String {
+        format!(
+            "tab={}\npost={}\nscroll={}\n",
+            self.tab_index, self.post_url, self.content_scroll
+        )
+    }
+
+    fn parse(contents: &str) -> Session {
+        let mut session = Session::default();
+        for line in contents.lines() {
+            match line.split_once('=') {
+                Some(("tab", value)) => session.tab_index = value.trim().parse().unwrap_or(0),
+                Some(("post", value)) => session.post_url = value.trim().to_string(),
+                Some(("scroll", value)) => {
+                    session.content_scroll = value.trim().parse().unwrap_or(0)
+                }
+                _ => {}
+            }
+        }
+        session
+    }
+}
+
+/// Where the session file lives. A lock file holding the pid sits next to it for the lifetime
+/// of the process, and the autosave worker touches it every `SESSION_HEARTBEAT`. Finding one at
+/// startup whose process is gone means the last run died without cleaning up.
+struct SessionStore {
+    path: PathBuf,
+    lock: PathBuf,
+}
+
+impl SessionStore {
+    fn open() -> Option<SessionStore> {
+        let dir = state_dir()?;
+        create_dirs(&dir).ok()?;
+        Some(SessionStore {
+            path: dir.join("session"),
+            lock: dir.join("session.lock"),
+        })
+    }
+
+    /// A lock whose pid is dead is a crash. A live pid is another instance unless its lock has
+    /// gone without a heartbeat for `SESSION_LOCK_STALE`, which means the pid was reused. When
+    /// the pid can't be read or checked, the lock's age decides alone.
+    fn previous_run_crashed(&self) -> bool {
+        let Ok(contents) = fs::read_to_string(&self.lock) else {
+            return false;
+        };
+        let stale = fs::metadata(&self.lock)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > SESSION_LOCK_STALE);
+        match contents.trim().parse().ok().and_then(process_alive) {
+            Some(false) => true,
+            Some(true) | None => stale,
+        }
+    }
+
+    fn load(&self) -> Option<Session> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .map(|contents| Session::parse(&contents))
+    }
+
+    fn save(&self, session: &Session) -> io::Result<()> {
+        write_atomic(&self.path, session.serialize().as_bytes())?;
+        self.heartbeat()
+    }
+
+    fn heartbeat(&self) -> io::Result<()> {
+        write_file(&self.lock, std::process::id().to_string().as_bytes())
+    }
+}
+
+/// Whether a process with this pid exists, or `None` where that can't be asked.
+#[cfg(unix)]
+fn process_alive(pid: i32) -> Option<bool> {
+    if pid <= 0 {
+        return Some(false);
+    }
+    // SAFETY: signal 0 sends nothing; it only checks that the pid exists.
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(true);
+    }
+    // Someone else's process still exists; we just may not signal it.
+    Some(io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: i32) -> Option<bool> {
+    None
+}
+
+/// Saves the session from a worker thread so disk writes never stall rendering. A snapshot
+/// is sent after `AUTOSAVE_EVERY` changes or `AUTOSAVE_INTERVAL`, whichever comes first.
+struct Autosave {
+    sender: Sender<Session>,
+    worker: JoinHandle<()>,
+    lock: PathBuf,
+    last_sent: Session,
+    pending: usize,
+    last_save: Instant,
+}
+
+impl Autosave {
+    fn start(store: SessionStore, events: Sender<AppEvent>) -> Autosave {
+        let lock = store.lock.clone();
+        let _ = store.heartbeat();
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || autosave_worker(store, receiver, events));
+        Autosave {
+            sender,
+            worker,
+            lock,
+            last_sent: Session::default(),
+            pending: 0,
+            last_save: Instant::now(),
+        }
+    }
+
+    fn update(&mut self, session: Session, changed: bool) {
+        if changed {
+            self.pending += 1;
+        }
+        let due = self.pending >= AUTOSAVE_EVERY || self.last_save.elapsed() >= AUTOSAVE_INTERVAL;
+        if due && session != self.last_sent {
+            let _ = self.sender.send(session.clone());
+            self.last_sent = session;
+            self.pending = 0;
+            self.last_save = Instant::now();
+        }
+    }
+
+    fn due_in(&self) -> Option<Duration> {
+        (self.pending > 0).then(|| AUTOSAVE_INTERVAL.saturating_sub(self.last_save.elapsed()))
+    }
+
+    fn finish(self, session: Session) {
+        let _ = self.sender.send(session);
+        drop(self.sender);
+        let _ = self.worker.join();
+        let _ = remove_file(&self.lock);
+    }
+}
+
+/// Reports the first failure of a run of failed saves, not every retry. Between saves it keeps
+/// the lock fresh, so an instance left idle isn't taken for a crashed one.
+fn autosave_worker(store: SessionStore, receiver: Receiver<Session>, events: Sender<AppEvent>) {
+    let mut failing = false;
+    loop {
+        let mut session = match receiver.recv_timeout(SESSION_HEARTBEAT) {
+            Ok(session) => session,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let _ = store.heartbeat();
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        // Only the newest snapshot matters if several queued up behind a slow disk.
+        while let Ok(newer) = receiver.try_recv() {
+            session = newer;
+        }
+        match store.save(&session) {
+            Ok(()) => failing = false,
+            Err(err) if !failing => {
+                failing = true;
+                let _ = events.send(AppEvent::Status(format!("Couldn't save session ({})", err)));
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// The write guard for `--read-only`. Every helper here that creates, writes, replaces, or
+/// removes something on disk, and every git command that changes a checkout, calls it first,
+/// so a new feature that writes through them is covered without its own check.
+fn ensure_writable(path: &Path) -> io::Result<()> {
+    assert_off_ui_thread("writing a file");
+    writes_allowed().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("read-only mode, not writing {}", path.display()),
+        )
+    })
+}
+
+/// What `ensure_writable` checks, without a path, for the UI to ask before queueing a write;
+/// `Capability::WriteState` answers from here too.
+fn writes_allowed() -> Result<(), String> {
+    if read_only() {
+        return Err("Writing files is disabled in read-only mode.".to_string());
+    }
+    Ok(())
+}
+
+fn create_dirs(path: &Path) -> io::Result<()> {
+    ensure_writable(path)?;
+    fs::create_dir_all(path)
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    ensure_writable(path)?;
+    fs::write(path, contents)
+}
+
+/// Opens `path` for writing, truncating it; with `create_new` an existing file is left alone
+/// and the open fails with `AlreadyExists`.
+fn create_file(path: &Path, create_new: bool) -> io::Result<fs::File> {
+    ensure_writable(path)?;
+    fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .create_new(create_new)
+        .open(path)
+}
+
+fn remove_file(path: &Path) -> io::Result<()> {
+    ensure_writable(path)?;
+    fs::remove_file(path)
+}
+
+fn remove_dir_all(path: &Path) -> io::Result<()> {
+    ensure_writable(path)?;
+    fs::remove_dir_all(path)
+}
+
+fn rename(from: &Path, to: &Path) -> io::Result<()> {
+    ensure_writable(to)?;
+    fs::rename(from, to)
+}
+
+/// Writes through a temporary sibling and renames it over `path`, so readers see either the
+/// old file or the new one, never a torn write.
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    ensure_writable(path)?;
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("file");
+    let staging = path.with_file_name(format!(".{}.tmp-{}", file_name, std::process::id()));
+    let result = create_file(&staging, false).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    match result.and_then(|()| rename(&staging, path)) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            let _ = remove_file(&staging);
+            Err(err)
+        }
+    }
+}
+
+fn state_dir() -> Option<PathBuf> {
+    if let Ok(path) = env::var("XDG_STATE_HOME") {
+        return Some(PathBuf::from(path).join("johnjeong"));
+    }
+    let home = PathBuf::from(env::var("HOME").ok()?);
+    let support = home.join("Library").join("Application Support");
+    if support.is_dir() {
+        return Some(support.join("johnjeong"));
+    }
+    Some(home.join(".local").join("state").join("johnjeong"))
+}
+
+/// `--config <path>`, which replaces the usual location.
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = CONFIG_PATH.get() {
+        return Some(path.clone());
+    }
+    if let Ok(path) = env::var("XDG_CONFIG_HOME") {
+        return Some(PathBuf::from(path).join("johnjeong").join("config.toml"));
+    }
+    let home = PathBuf::from(env::var("HOME").ok()?);
+    Some(home.join(".config").join("johnjeong").join("config.toml"))
+}
+
+/// The config file as `section -> key -> value`, with top-level keys under `""`. Only the
+/// slice of TOML this tool uses is understood: `[section]` headers, `key = value` pairs with
+/// quoted or bare values, and `#` comments. A missing file is empty; an unreadable or
+/// malformed one is an error naming the file and line. `Config::load` reports it at startup,
+/// so everywhere else can treat it as empty.
+fn read_config() -> Result<HashMap<String, HashMap<String, String>>, String> {
+    assert_off_ui_thread("reading the config");
+    let Some(path) = config_path() else {
+        return Ok(HashMap::new());
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound && CONFIG_PATH.get().is_none() => {
+            return Ok(HashMap::new())
+        }
+        Err(err) => return Err(format!("Cannot read {} ({}).", path.display(), err)),
+    };
+    parse_config(&contents).map_err(|message| format!("{}: {}", path.display(), message))
+}
+
+fn load_config() -> HashMap<String, HashMap<String, String>> {
+    read_config().unwrap_or_default()
+}
+
+/// The config as first read, for anything the running app looks up after startup. Reading it
+/// during loading means the UI thread never has to.
+fn config() -> &'static HashMap<String, HashMap<String, String>> {
+    static CONFIG: OnceLock<HashMap<String, HashMap<String, String>>> = OnceLock::new();
+    CONFIG.get_or_init(load_config)
+}
+
+/// The settings the app started with, flags applied, so `:content` can load again with only
+/// the directory changed. Set in `main`.
+static SETTINGS: OnceLock<Config> = OnceLock::new();
+
+/// Sets `key = "value"` in the config file under `[section]`, or at the top level for `""`,
+/// leaving every other line alone.
+fn set_config_value(section: &str, key: &str, value: &str) -> io::Result<()> {
+    let path = config_path().ok_or_else(|| io::Error::other("no config directory"))?;
+    let contents = fs::read_to_string(&path).unwrap_or_default();
+    if let Some(parent) = path.parent() {
+        create_dirs(parent)?;
+    }
+    write_atomic(
+        &path,
+        config_with_value(&contents, section, key, value).as_bytes(),
+    )
+}
+
+/// `contents` with `key = "value"` in `section`: an existing assignment is replaced, else one
+/// goes after the section's last line, and a missing section is added at the end.
+fn config_with_value(contents: &str, section: &str, key: &str, value: &str) -> String {
+    let assignment = format!("{} = {}", key, toml_string(value));
+    let mut lines = contents.lines().map(str::to_string).collect::<Vec<_>>();
+    fn header(line: &str) -> Option<&str> {
+        line.trim_start()
+            .strip_prefix('[')
+            .and_then(|rest| rest.split(']').next())
+            .map(str::trim)
+    }
+    let start = if section.is_empty() {
+        Some(0)
+    } else {
+        lines
+            .iter()
+            .position(|line| header(line) == Some(section))
+            .map(|index| index + 1)
+    };
+    let Some(start) = start else {
+        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push(format!("[{}]", section));
+        lines.push(assignment);
+        return format!("{}\n", lines.join("\n"));
+    };
+    let end = lines[start..]
+        .iter()
+        .position(|line| header(line).is_some())
+        .map_or(lines.len(), |index| start + index);
+    let existing = lines[start..end].iter().position(|line| {
+        line.split_once('=')
+            .is_some_and(|(name, _)| name.trim() == key)
+    });
+    match existing {
+        Some(index) => lines[start + index] = assignment,
+        None => {
+            let last = lines[start..end]
+                .iter()
+                .rposition(|line| !line.trim().is_empty())
+                .map_or(start, |index| start + index + 1);
+            lines.insert(last, assignment);
+        }
+    }
+    format!("{}\n", lines.join("\n"))
+}
+
+fn parse_config(contents: &str) -> Result<HashMap<String, HashMap<String, String>>, String> {
+    let mut config: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut section = String::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('[') {
+            let Some(name) = rest
+                .split('#')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .strip_suffix(']')
+            else {
+                return Err(format!("line {}: `[` without a closing `]`", number + 1));
+            };
+            section = name.trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!(
+                "line {}: expected `key = value`, found `{}`",
+                number + 1,
+                line
+            ));
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("line {}: missing a key before `=`", number + 1));
+        }
+        let value = value.trim();
+        let value = if let Some(quoted) = value.strip_prefix('"') {
+            parse_basic_string(quoted)
+                .map_err(|problem| format!("line {}: {} {}", number + 1, key, problem))?
+        } else if let Some(quoted) = value.strip_prefix('\'') {
+            // A literal string: no escapes, up to the next `'`.
+            let Some((value, rest)) = quoted.split_once('\'') else {
+                return Err(format!("line {}: {} has no closing quote", number + 1, key));
+            };
+            check_after_string(rest)
+                .map_err(|problem| format!("line {}: {} {}", number + 1, key, problem))?;
+            value.to_string()
+        } else {
+            value
+                .split('#')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+        config
+            .entry(section.clone())
+            .or_default()
+            .insert(key.to_string(), value);
+    }
+    Ok(config)
+}
+
+/// A TOML basic string after its opening `"`, with `\"`, `\\`, `\n`, `\t`, `\r`, `\b`, `\f`,
+/// `\uXXXX`, and `\UXXXXXXXX` escapes. `Err` says what's wrong, to follow the key's name.
+fn parse_basic_string(quoted: &str) -> Result<String, String> {
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => {
+                check_after_string(&quoted[index + 1..])?;
+                return Ok(value);
+            }
+            '\\' => {
+                let escaped = match chars.next().map(|(_, c)| c) {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some(kind @ ('u' | 'U')) => {
+                        let digits = if kind == 'u' { 4 } else { 8 };
+                        let hex = (0..digits)
+                            .filter_map(|_| chars.next().map(|(_, c)| c))
+                            .collect::<String>();
+                        u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .filter(|_| hex.len() == digits)
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("has a bad escape \\{}{}", kind, hex))?
+                    }
+                    Some(other) => return Err(format!("has an unknown escape \\{}", other)),
+                    None => return Err("has no closing quote".to_string()),
+                };
+                value.push(escaped);
+            }
+            c => value.push(c),
+        }
+    }
+    Err("has no closing quote".to_string())
+}
+
+/// What may follow a quoted value: nothing but a comment.
+fn check_after_string(rest: &str) -> Result<(), String> {
+    let rest = rest.trim();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err(format!("has `{}` after its closing quote", rest))
+    }
+}
+
+/// `value` as a TOML basic string, quoted and escaped so `parse_config` reads it back as is.
+fn toml_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Settings that come from the environment or the config file, resolved once in `main` and
+/// passed to what needs them. An environment variable wins over the file, and the file over
+/// the built-in default:
+///
+/// ```toml
+/// [header]
+/// title = "John Jeong"                 # JOHNJEONG_TITLE
+/// subtitle = "Co-founder & Co-CEO"     # JOHNJEONG_SUBTITLE
+///
+/// [content]
+/// dir = "~/part-of-my-brain"           # JOHNJEONG_CONTENT_DIR
+/// site_url = "https://johnjeong.com"   # JOHNJEONG_SITE_URL
+///
+/// [behavior]
+/// read_only = false                    # JOHNJEONG_READ_ONLY
+/// high_contrast = false                # JOHNJEONG_HIGH_CONTRAST
+/// reduced_motion = false               # JOHNJEONG_REDUCED_MOTION
+/// gallery_dates = "auto"
+/// link_max_age_days = 30               # JOHNJEONG_LINK_MAX_AGE_DAYS
+/// hyperlinks = true                    # JOHNJEONG_HYPERLINKS (unset: detect the terminal)
+/// osc52 = true                         # JOHNJEONG_OSC52 (unset: detect the terminal)
+/// browser = "firefox --new-tab"        # else $BROWSER, else the platform opener
+/// start_tab = "daily-logs"              # JOHNJEONG_START_TAB (name or number; --tab wins)
+/// watch = true                         # JOHNJEONG_WATCH
+/// notify = "bell, osc9"                # JOHNJEONG_NOTIFY
+/// ```
+///
+/// Each key may also sit at the top level instead, as older configs have `content_dir`,
+/// `read_only`, `high_contrast`, and `gallery_dates`.
+#[derive(Clone)]
+struct Config {
+    /// `None` leaves the header to the site's `Header.astro`, then the built-in one.
+    title: Option<String>,
+    subtitle: Option<String>,
+    content_dir: Option<PathBuf>,
+    /// Without a trailing `/`.
+    site_url: String,
+    read_only: bool,
+    high_contrast: bool,
+    reduced_motion: bool,
+    gallery_dates: GalleryDates,
+    link_max_age_days: u64,
+    /// `None` leaves it to `terminal_supports_hyperlinks`.
+    hyperlinks: Option<bool>,
+    /// `None` leaves it to `terminal_supports_osc52`.
+    osc52: Option<bool>,
+    /// Command links open with, ahead of `$BROWSER`; see `browser_command`.
+    browser: Option<String>,
+    /// The tab the app opens on, by name or 1-based number; `--tab` overrides it.
+    start_tab: Option<String>,
+    /// Pick up posts changed outside the app while it runs.
+    watch: bool,
+    /// How to announce what the watcher picked up.
+    notify: Notify,
+    /// Keep unpublished essays. Only `--drafts` sets it.
+    drafts: bool,
+}
+
+/// `(section, key, environment variable)` for every `Config` setting.
+const CONFIG_KEYS: [(&str, &str, Option<&str>); 15] = [
+    ("header", "title", Some("JOHNJEONG_TITLE")),
+    ("header", "subtitle", Some("JOHNJEONG_SUBTITLE")),
+    ("content", "dir", Some("JOHNJEONG_CONTENT_DIR")),
+    ("content", "site_url", Some("JOHNJEONG_SITE_URL")),
+    ("behavior", "read_only", Some("JOHNJEONG_READ_ONLY")),
+    ("behavior", "high_contrast", Some("JOHNJEONG_HIGH_CONTRAST")),
+    (
+        "behavior",
+        "reduced_motion",
+        Some("JOHNJEONG_REDUCED_MOTION"),
+    ),
+    ("behavior", "gallery_dates", None),
+    (
+        "behavior",
+        "link_max_age_days",
+        Some("JOHNJEONG_LINK_MAX_AGE_DAYS"),
+    ),
+    ("behavior", "hyperlinks", Some("JOHNJEONG_HYPERLINKS")),
+    ("behavior", "osc52", Some("JOHNJEONG_OSC52")),
+    ("behavior", "start_tab", Some("JOHNJEONG_START_TAB")),
+    ("behavior", "watch", Some("JOHNJEONG_WATCH")),
+    ("behavior", "notify", Some("JOHNJEONG_NOTIFY")),
+    // `$BROWSER` comes after the config rather than before, so it isn't listed here.
+    ("behavior", "browser", None),
+];
+
+impl Config {
+    /// Reads the config file and the environment. Errors say which file, line, or key is wrong.
+    fn load() -> Result<Config, String> {
+        let file = read_config()?;
+        Config::resolve(&file, |name| env::var(name).ok()).map_err(|message| {
+            let path = config_path().map_or_else(String::new, |path| path.display().to_string());
+            format!("{}: {}", path, message)
+        })
+    }
+
+    /// Builds the settings from a parsed config file and an environment lookup.
+    fn resolve(
+        file: &HashMap<String, HashMap<String, String>>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Config, String> {
+        for (section, _, _) in CONFIG_KEYS {
+            let known = |key: &String| {
+                CONFIG_KEYS
+                    .iter()
+                    .any(|(other, name, _)| *other == section && name == key)
+            };
+            if let Some(key) = file
+                .get(section)
+                .and_then(|values| values.keys().find(|key| !known(key)))
+            {
+                return Err(format!("unknown setting `{}.{}`", section, key));
+            }
+        }
+
+        // The environment first, then `[section] key`, then a top-level `key`.
+        let value = |section: &str, key: &str| -> Option<(String, String)> {
+            let (_, _, variable) = CONFIG_KEYS
+                .iter()
+                .find(|(other, name, _)| *other == section && *name == key)?;
+            if let Some(value) = variable
+                .and_then(&env)
+                .filter(|value| !value.trim().is_empty())
+            {
+                return Some((variable.unwrap_or_default().to_string(), value));
+            }
+            let legacy = if key == "dir" { "content_dir" } else { key };
+            file.get(section)
+                .and_then(|values| values.get(key))
+                .map(|value| (format!("{}.{}", section, key), value.clone()))
+                .or_else(|| {
+                    file.get("")
+                        .and_then(|top| top.get(legacy))
+                        .map(|value| (legacy.to_string(), value.clone()))
+                })
+        };
+        let optional_flag = |section: &str, key: &str| -> Result<Option<bool>, String> {
+            match value(section, key) {
+                None => Ok(None),
+                Some((name, value)) => match value.trim().to_lowercase().as_str() {
+                    "1" | "true" | "yes" | "on" => Ok(Some(true)),
+                    "0" | "false" | "no" | "off" => Ok(Some(false)),
+                    _ => Err(format!(
+                        "{} should be true or false, not \"{}\"",
+                        name, value
+                    )),
+                },
+            }
+        };
+        let flag = |section: &str, key: &str| -> Result<bool, String> {
+            Ok(optional_flag(section, key)?.unwrap_or(false))
+        };
+
+        let gallery_dates = match value("behavior", "gallery_dates") {
+            None => GalleryDates::Auto,
+            Some((name, value)) => GalleryDates::parse(&value).ok_or_else(|| {
+                format!(
+                    "{} should be \"auto\", \"eager\", or \"lazy\", not \"{}\"",
+                    name, value
+                )
+            })?,
+        };
+        let link_max_age_days = match value("behavior", "link_max_age_days") {
+            None => 30,
+            Some((name, value)) => value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("{} should be a number of days, not \"{}\"", name, value))?,
+        };
+        let notify = match value("behavior", "notify") {
+            None => Notify::default(),
+            Some((name, value)) => Notify::parse(&value).ok_or_else(|| {
+                format!(
+                    "{} should list \"bell\", \"osc9\", or \"osc777\", not \"{}\"",
+                    name, value
+                )
+            })?,
+        };
+        Ok(Config {
+            title: value("header", "title").map(|(_, value)| value),
+            subtitle: value("header", "subtitle").map(|(_, value)| value),
+            content_dir: value("content", "dir").map(|(_, value)| expand_path(&value)),
+            site_url: value("content", "site_url")
+                .map_or_else(|| "https://johnjeong.com".to_string(), |(_, value)| value)
+                .trim()
+                .trim_end_matches('/')
+                .to_string(),
+            read_only: flag("behavior", "read_only")?,
+            high_contrast: flag("behavior", "high_contrast")?,
+            reduced_motion: flag("behavior", "reduced_motion")?,
+            gallery_dates,
+            link_max_age_days,
+            hyperlinks: optional_flag("behavior", "hyperlinks")?,
+            osc52: optional_flag("behavior", "osc52")?,
+            browser: value("behavior", "browser").map(|(_, value)| value),
+            start_tab: value("behavior", "start_tab").map(|(_, value)| value),
+            watch: optional_flag("behavior", "watch")?.unwrap_or(true),
+            notify,
+            drafts: false,
+        })
+    }
+}
+
+/// Per-tab defaults from a `[tabs.<name>]` config section, e.g.
+/// `sort = "updated_at desc, title asc"` and `filter = "tag != meta && published"`.
+/// The `/` query and tag filter narrow the result further at runtime.
+struct TabView {
+    sort: Vec<(String, bool)>,
+    filter: Option<PostFilter>,
+}
+
+impl TabView {
+    fn parse(section: &HashMap<String, String>) -> Result<TabView, String> {
+        let sort = match section.get("sort") {
+            Some(value) => parse_sort(value).map_err(|err| format!("sort: {}", err))?,
+            None => Vec::new(),
+        };
+        let filter = match section
+            .get("filter")
+            .filter(|value| !value.trim().is_empty())
+        {
+            Some(value) => {
+                Some(PostFilter::parse(value).map_err(|err| format!("filter: {}", err))?)
+            }
+            None => None,
+        };
+        Ok(TabView { sort, filter })
+    }
+
+    /// Sorts the tab's posts and hands it the filter; `listed_posts` applies it.
+    fn apply(self, tab: &mut ContentTab) {
+        // Stable, so posts equal on every configured key keep the default newest-first order.
+        tab.posts.sort_by(|a, b| {
+            self.sort
+                .iter()
+                .map(|(field, descending)| {
+                    let order = post_field(a, field).cmp(post_field(b, field));
+                    if *descending {
+                        order.reverse()
+                    } else {
+                        order
+                    }
+                })
+                .find(|order| order.is_ne())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        tab.filter = self.filter;
+    }
+}
+
+/// Applies every `[tabs.<name>]` section to the matching content tab. Problems are reported
+/// on the status line, naming the section, and leave that tab in its default order.
+fn apply_tab_views(tabs: &mut [TabData], status: &mut Option<String>) {
+    let mut sections = config()
+        .iter()
+        .filter_map(|(section, values)| {
+            let name = section.strip_prefix("tabs.")?.trim_matches('"').to_string();
+            Some((section, name, values))
+        })
+        .collect::<Vec<_>>();
+    sections.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (section, name, values) in sections {
+        let tab = tabs.iter_mut().find_map(|tab| match tab {
+            TabData::Content(tab) if tab_matches(tab, &name) => Some(tab),
+            _ => None,
+        });
+        let result = match tab {
+            Some(tab) => TabView::parse(values).map(|view| view.apply(tab)),
+            None => Err(format!("no tab named `{}`", name)),
+        };
+        if let Err(message) = result {
+            status.get_or_insert_with(|| format!("Config [{}] {}", section, message));
+        }
+    }
+}
+
+/// `field [asc|desc], ...`; ascending when no direction is given.
+fn parse_sort(value: &str) -> Result<Vec<(String, bool)>, String> {
+    value
+        .split(',')
+        .map(|key| {
+            let mut words = key.split_whitespace();
+            let field = words.next().ok_or("empty sort key")?;
+            let descending = match words.next() {
+                None => false,
+                Some(word) if word.eq_ignore_ascii_case("asc") => false,
+                Some(word) if word.eq_ignore_ascii_case("desc") => true,
+                Some(word) => return Err(format!("unexpected `{}` after `{}`", word, field)),
+            };
+            if let Some(word) = words.next() {
+                return Err(format!("unexpected `{}`", word));
+            }
+            Ok((field.to_string(), descending))
+        })
+        .collect()
+}
+
+/// A post's value for a sort or filter field: `title`, `date`, and `url` are the loaded
+/// values, anything else is read from the frontmatter. Missing fields are empty.
+fn post_field<'a>(post: &'a Post, field: &str) -> &'a str {
+    match field {
+        "title" => &post.title,
+        "date" => &post.date,
+        "url" => &post.url,
+        _ => post.fields.get(field).map_or("", String::as_str),
+    }
+}
+
+/// Boolean filter over posts: `field`, `field == value`, `field != value`, combined with
+/// `!`, `&&`, `||`, and parentheses. `tag == x` holds when any of the post's tags is `x`.
+enum PostFilter {
+    Truthy(String),
+    Equals(String, String),
+    Not(Box<PostFilter>),
+    And(Box<PostFilter>, Box<PostFilter>),
+    Or(Box<PostFilter>, Box<PostFilter>),
+}
+
+#[derive(Clone, PartialEq)]
+enum FilterToken {
+    Word(String),
+    And,
+    Or,
+    Not,
+    Equals,
+    NotEquals,
+    Open,
+    Close,
+}
+
+impl FilterToken {
+    fn text(&self) -> String {
+        match self {
+            FilterToken::Word(word) => word.clone(),
+            FilterToken::And => "&&".to_string(),
+            FilterToken::Or => "||".to_string(),
+            FilterToken::Not => "!".to_string(),
+            FilterToken::Equals => "==".to_string(),
+            FilterToken::NotEquals => "!=".to_string(),
+            FilterToken::Open => "(".to_string(),
+            FilterToken::Close => ")".to_string(),
+        }
+    }
+}
+
+impl PostFilter {
+    fn parse(expression: &str) -> Result<PostFilter, String> {
+        let tokens = tokenize_filter(expression)?;
+        let mut position = 0;
+        let filter = parse_filter_or(&tokens, &mut position)?;
+        match tokens.get(position) {
+            Some(token) => Err(format!("unexpected `{}`", token.text())),
+            None => Ok(filter),
+        }
+    }
+
+    fn matches(&self, post: &Post) -> bool {
+        match self {
+            PostFilter::Truthy(field) if field == "tag" || field == "tags" => !post.tags.is_empty(),
+            PostFilter::Truthy(field) => {
+                let value = post_field(post, field).trim();
+                !value.is_empty()
+                    && !["false", "no", "0"]
+                        .iter()
+                        .any(|falsy| value.eq_ignore_ascii_case(falsy))
+            }
+            PostFilter::Equals(field, value) if field == "tag" || field == "tags" => {
+                post.tags.iter().any(|tag| tag.eq_ignore_ascii_case(value))
+            }
+            PostFilter::Equals(field, value) => post_field(post, field).eq_ignore_ascii_case(value),
+            PostFilter::Not(inner) => !inner.matches(post),
+            PostFilter::And(left, right) => left.matches(post) && right.matches(post),
+            PostFilter::Or(left, right) => left.matches(post) || right.matches(post),
+        }
+    }
+}
+
+fn tokenize_filter(expression: &str) -> Result<Vec<FilterToken>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+    while let Some(ch) = rest.chars().next() {
+        let (token, len) = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted
+                .find('"')
+                .ok_or_else(|| format!("unterminated string `{}`", rest))?;
+            (FilterToken::Word(quoted[..end].to_string()), end + 2)
+        } else if rest.starts_with("&&") {
+            (FilterToken::And, 2)
+        } else if rest.starts_with("||") {
+            (FilterToken::Or, 2)
+        } else if rest.starts_with("==") {
+            (FilterToken::Equals, 2)
+        } else if rest.starts_with("!=") {
+            (FilterToken::NotEquals, 2)
+        } else if ch == '!' {
+            (FilterToken::Not, 1)
+        } else if ch == '(' {
+            (FilterToken::Open, 1)
+        } else if ch == ')' {
+            (FilterToken::Close, 1)
+        } else {
+            let len = rest
+                .find(|c: char| c.is_whitespace() || "()!&|=\"".contains(c))
+                .unwrap_or(rest.len());
+            if len == 0 {
+                return Err(format!("unexpected `{}`", ch));
+            }
+            (FilterToken::Word(rest[..len].to_string()), len)
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+fn parse_filter_or(tokens: &[FilterToken], position: &mut usize) -> Result<PostFilter, String> {
+    let mut left = parse_filter_and(tokens, position)?;
+    while tokens.get(*position) == Some(&FilterToken::Or) {
+        *position += 1;
+        let right = parse_filter_and(tokens, position)?;
+        left = PostFilter::Or(Box::new(left), Box::new(right));
+    }
+    Ok(left)
+}
+
+fn parse_filter_and(tokens: &[FilterToken], position: &mut usize) -> Result<PostFilter, String> {
+    let mut left = parse_filter_unary(tokens, position)?;
+    while tokens.get(*position) == Some(&FilterToken::And) {
+        *position += 1;
+        let right = parse_filter_unary(tokens, position)?;
+        left = PostFilter::And(Box::new(left), Box::new(right));
+    }
+    Ok(left)
+}
+
+fn parse_filter_unary(tokens: &[FilterToken], position: &mut usize) -> Result<PostFilter, String> {
+    let token = tokens
+        .get(*position)
+        .ok_or("unexpected end of expression")?;
+    *position += 1;
+    match token {
+        FilterToken::Not => Ok(PostFilter::Not(Box::new(parse_filter_unary(
+            tokens, position,
+        )?))),
+        FilterToken::Open => {
+            let inner = parse_filter_or(tokens, position)?;
+            match tokens.get(*position) {
+                Some(FilterToken::Close) => {
+                    *position += 1;
+                    Ok(inner)
+                }
+                Some(token) => Err(format!("expected `)` but found `{}`", token.text())),
+                None => Err("missing `)`".to_string()),
+            }
+        }
+        FilterToken::Word(field) => {
+            let negate = match tokens.get(*position) {
+                Some(FilterToken::Equals) => false,
+                Some(FilterToken::NotEquals) => true,
+                _ => return Ok(PostFilter::Truthy(field.clone())),
+            };
+            let operator = tokens[*position].text();
+            *position += 1;
+            let value = match tokens.get(*position) {
+                Some(FilterToken::Word(value)) => value.clone(),
+                Some(token) => {
+                    return Err(format!(
+                        "expected a value after `{}` but found `{}`",
+                        operator,
+                        token.text()
+                    ))
+                }
+                None => return Err(format!("expected a value after `{}`", operator)),
+            };
+            *position += 1;
+            let equals = PostFilter::Equals(field.clone(), value);
+            Ok(if negate {
+                PostFilter::Not(Box::new(equals))
+            } else {
+                equals
+            })
+        }
+        token => Err(format!("unexpected `{}`", token.text())),
+    }
+}
+
+/// What a key does in the main view. `Keymap` decides which keys trigger which action.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Quit,
+    /// Esc: leave a tag, clear the filter, or else quit.
+    Back,
+    MoveUp,
+    MoveDown,
+    ToggleFocus,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    Top,
+    Bottom,
+    Footnote,
+    NextTab,
+    PreviousTab,
+    /// The gallery tab, or the top of the post when pressed twice (`gg`).
+    Gallery,
+    GalleryPeek,
+    Open,
+    OpenTabIndex,
+    Share,
+    CopyUrl,
+    CopyBody,
+    QrCode,
+    Pager,
+    Edit,
+    Save,
+    Links,
+    Location,
+    OpenDirectory,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Picker,
+    TagPicker,
+    Command,
+    Diff,
+    HighContrast,
+    Drafts,
+    Messages,
+    Help,
+}
+
+/// Every action as `(action, name in [keys], help category, default keys, description)`. The
+/// defaults use the config's syntax, and `?` and `--help` list actions in this order.
+const ACTIONS: [(Action, &str, &str, &str, &str); 39] = [
+    (
+        Action::MoveUp,
+        "move_up",
+        "Navigation",
+        "up, k",
+        "move selection up (or scroll the post once focus is there)",
+    ),
+    (
+        Action::MoveDown,
+        "move_down",
+        "Navigation",
+        "down, j",
+        "move selection down (or scroll the post once focus is there)",
+    ),
+    (
+        Action::ToggleFocus,
+        "toggle_focus",
+        "Navigation",
+        "w",
+        "switch focus between the list and the post",
+    ),
+    (
+        Action::PageUp,
+        "page_up",
+        "Navigation",
+        "pgup",
+        "scroll the post up a page",
+    ),
+    (
+        Action::PageDown,
+        "page_down",
+        "Navigation",
+        "pgdn",
+        "scroll the post down a page",
+    ),
+    (
+        Action::HalfPageUp,
+        "half_page_up",
+        "Navigation",
+        "ctrl-u",
+        "scroll the post up half a page",
+    ),
+    (
+        Action::HalfPageDown,
+        "half_page_down",
+        "Navigation",
+        "ctrl-d",
+        "scroll the post down half a page",
+    ),
+    (
+        Action::Top,
+        "top",
+        "Navigation",
+        "home, t",
+        "jump to the top of the post (so does the gallery key twice)",
+    ),
+    (
+        Action::Bottom,
+        "bottom",
+        "Navigation",
+        "G",
+        "jump to the bottom of the post",
+    ),
+    (
+        Action::Footnote,
+        "footnote",
+        "Navigation",
+        "f",
+        "jump to footnote / back",
+    ),
+    (
+        Action::NextTab,
+        "next_tab",
+        "Tabs",
+        "tab, right",
+        "next tab",
+    ),
+    (
+        Action::PreviousTab,
+        "previous_tab",
+        "Tabs",
+        "backtab, left",
+        "previous tab",
+    ),
+    (
+        Action::Gallery,
+        "gallery",
+        "Tabs",
+        "g",
+        "gallery tab (after a moment, since pressing it twice is also a key)",
+    ),
+    (
+        Action::GalleryPeek,
+        "gallery_peek",
+        "Tabs",
+        "P",
+        "peek at the gallery without leaving the current post",
+    ),
+    (
+        Action::Back,
+        "back",
+        "Tabs",
+        "esc",
+        "back out of a tag or clear the filter, or else quit",
+    ),
+    (Action::Open, "open", "Opening", "o, enter", "open link"),
+    (
+        Action::OpenTabIndex,
+        "open_tab_index",
+        "Opening",
+        "U",
+        "open the tab's web index",
+    ),
+    (
+        Action::Share,
+        "share",
+        "Opening",
+        "S",
+        "share the selected post (tweet, LinkedIn, newsletter snippet)",
+    ),
+    (
+        Action::CopyUrl,
+        "copy_url",
+        "Opening",
+        "y",
+        "copy the selected post's or link's URL",
+    ),
+    (
+        Action::CopyBody,
+        "copy_body",
+        "Opening",
+        "Y",
+        "copy the selected post's markdown (or a photo's full path)",
+    ),
+    (
+        Action::QrCode,
+        "qr_code",
+        "Opening",
+        "Q",
+        "show a QR code for the selected post's URL (any key closes it)",
+    ),
+    (
+        Action::Pager,
+        "pager",
+        "Opening",
+        "v",
+        "read the selected post in $PAGER",
+    ),
+    (
+        Action::Edit,
+        "edit",
+        "Opening",
+        "e",
+        "edit the selected post's file in $EDITOR, then reload it",
+    ),
+    (
+        Action::Save,
+        "save",
+        "Opening",
+        "ctrl-s",
+        "save the selected post's markdown (or photo) to the current directory",
+    ),
+    (
+        Action::Links,
+        "links",
+        "Opening",
+        "L",
+        "list the links inside the selected post and open one",
+    ),
+    (
+        Action::Location,
+        "location",
+        "Opening",
+        "M",
+        "open the post's or photo's location in maps",
+    ),
+    (
+        Action::OpenDirectory,
+        "open_directory",
+        "Opening",
+        "O",
+        "open the tab's content directory",
+    ),
+    (
+        Action::Search,
+        "search",
+        "Search",
+        "/",
+        "filter the list (enter keep, esc cancel)",
+    ),
+    (
+        Action::NextMatch,
+        "next_match",
+        "Search",
+        "n",
+        "next match in the post",
+    ),
+    (
+        Action::PreviousMatch,
+        "previous_match",
+        "Search",
+        "N",
+        "previous match in the post",
+    ),
+    (
+        Action::Picker,
+        "picker",
+        "Search",
+        "p",
+        "fuzzy-find a post in any tab",
+    ),
+    (
+        Action::TagPicker,
+        "tag_picker",
+        "Search",
+        "T",
+        "filter the list by tag",
+    ),
+    (
+        Action::Command,
+        "command",
+        "Other",
+        ":",
+        "run a command (:timer 15m starts a reading session, :timer off ends it, :export <path>, :content <dir>, :clone <git-url>)",
+    ),
+    (
+        Action::Diff,
+        "diff",
+        "Other",
+        "d",
+        "show what changed in a post since you last read it",
+    ),
+    (
+        Action::HighContrast,
+        "high_contrast",
+        "Other",
+        "H",
+        "toggle high-contrast focus indicators",
+    ),
+    (
+        Action::Drafts,
+        "drafts",
+        "Other",
+        "D",
+        "show or hide unpublished essays",
+    ),
+    (
+        Action::Messages,
+        "messages",
+        "Other",
+        "m",
+        "show recent status messages (esc or m closes)",
+    ),
+    (Action::Help, "help", "Other", "?", "show this list of keys"),
+    (Action::Quit, "quit", "Other", "q", "quit"),
+];
+
+/// A key as bindings see it: the key itself and whether ctrl was held. Shift is part of the
+/// character (`G`, shift-tab) and alt is ignored.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct KeyBinding {
+    code: KeyCode,
+    ctrl: bool,
+}
+
+impl KeyBinding {
+    fn from_event(key: KeyEvent) -> KeyBinding {
+        KeyBinding {
+            code: key.code,
+            ctrl: key.modifiers.contains(KeyModifiers::CONTROL),
+        }
+    }
+
+    /// Parses a single character (`j`, `G`, `?`) or a named key (`enter`, `esc`, `tab`,
+    /// `backtab`, `space`, `up`, `down`, `left`, `right`, `pgup`, `pgdn`, `home`, `end`,
+    /// `backspace`, `delete`), either one optionally after `ctrl-`. Names ignore case.
+    fn parse(text: &str) -> Result<KeyBinding, String> {
+        let (ctrl, name) = match text.get(..5) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("ctrl-") && text.len() > 5 => {
+                (true, &text[5..])
+            }
+            _ => (false, text),
+        };
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(ch), None) => KeyCode::Char(ch),
+            _ => match name.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" | "shift-tab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pgup" | "pageup" => KeyCode::PageUp,
+                "pgdn" | "pgdown" | "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                _ => return Err(format!("unknown key \"{}\"", text)),
+            },
+        };
+        Ok(KeyBinding { code, ctrl })
+    }
+
+    /// How `?` and `--help` show the key.
+    fn label(self) -> String {
+        let name = match self.code {
+            KeyCode::Char(' ') => "space".to_string(),
+            KeyCode::Char(ch) => ch.to_string(),
+            KeyCode::Enter => "enter".to_string(),
+            KeyCode::Esc => "esc".to_string(),
+            KeyCode::Tab => "tab".to_string(),
+            KeyCode::BackTab => "shift-tab".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::PageUp => "pgup".to_string(),
+            KeyCode::PageDown => "pgdn".to_string(),
+            KeyCode::Home => "home".to_string(),
+            KeyCode::End => "end".to_string(),
+            KeyCode::Backspace => "backspace".to_string(),
+            KeyCode::Delete => "delete".to_string(),
+            _ => "?".to_string(),
+        };
+        if self.ctrl {
+            format!("ctrl-{}", name)
+        } else {
+            name
+        }
+    }
+}
+
+/// Which action each key triggers in the main view: the defaults in `ACTIONS`, except that an
+/// action named in the config's `[keys]` section gets the comma-separated keys given there
+/// instead, e.g. `move_down = "n, down"`. An empty value leaves the action unbound. Overlays
+/// and prompts keep their own fixed keys.
+struct Keymap {
+    /// Every binding with its action, in `ACTIONS` order.
+    bindings: Vec<(KeyBinding, Action)>,
+}
+
+impl Keymap {
+    fn from_config(section: Option<&HashMap<String, String>>) -> Result<Keymap, String> {
+        if let Some(name) = section.and_then(|section| {
+            section
+                .keys()
+                .find(|name| !ACTIONS.iter().any(|(_, action, ..)| action == name))
+        }) {
+            return Err(format!("[keys] has no action called \"{}\".", name));
+        }
+        let mut bindings: Vec<(KeyBinding, Action)> = Vec::new();
+        let mut names: HashMap<KeyBinding, &str> = HashMap::new();
+        for (action, name, _, defaults, _) in ACTIONS {
+            let keys = section
+                .and_then(|section| section.get(name))
+                .map_or(defaults, String::as_str);
+            for text in keys
+                .split(',')
+                .map(str::trim)
+                .filter(|text| !text.is_empty())
+            {
+                let key = KeyBinding::parse(text)
+                    .map_err(|message| format!("[keys] {}: {}.", name, message))?;
+                if let Some(other) = names.insert(key, name) {
+                    return Err(format!(
+                        "[keys] {} and {} are both bound to {}; rebind one of them.",
+                        other,
+                        name,
+                        key.label()
+                    ));
+                }
+                bindings.push((key, action));
+            }
+        }
+        Ok(Keymap { bindings })
+    }
+
+    fn action(&self, key: KeyEvent) -> Option<Action> {
+        let key = KeyBinding::from_event(key);
+        self.bindings
+            .iter()
+            .find(|(binding, _)| *binding == key)
+            .map(|(_, action)| *action)
+    }
+
+    /// The keys bound to `action` as help shows them, e.g. `↑/k`; empty when there are none.
+    fn label(&self, action: Action) -> String {
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(key, _)| key.label())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+fn handle_key(key: KeyEvent, data: &AppData, state: &mut AppState) -> io::Result<bool> {
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Ok(true);
+    }
+    if state.timer_done.take().is_some() {
+        return Ok(false);
+    }
+    if let Some(step) = state.tour {
+        if key.code == KeyCode::Esc || step + 1 >= TOUR.len() {
+            finish_tour(state);
+        } else {
+            state.tour = Some(step + 1);
+        }
+        return Ok(false);
+    }
+    if state.command.is_some() {
+        handle_command_key(key, data, state);
+        return Ok(false);
+    }
+    if state.picker.is_some() {
+        handle_picker_key(key, data, state);
+        return Ok(false);
+    }
+    if state.tag_picker.is_some() {
+        handle_tag_picker_key(key, state);
+        return Ok(false);
+    }
+    if state.confirm_url.is_some() {
+        handle_confirm_key(key, state);
+        return Ok(false);
+    }
+    if state.gallery_peek.is_some() {
+        handle_gallery_peek_key(key, data, state);
+        return Ok(false);
+    }
+    if state.share_menu.is_some() {
+        handle_share_key(key, data, state);
+        return Ok(false);
+    }
+    if state.link_list.is_some() {
+        handle_link_list_key(key, state);
+        return Ok(false);
+    }
+    if state.help.is_some() {
+        handle_help_key(key, state);
+        return Ok(false);
+    }
+    if state.qr.is_some() {
+        state.qr = None;
+        return Ok(false);
+    }
+    if state.status_history.is_some() {
+        handle_status_history_key(key, state);
+        return Ok(false);
+    }
+    if state.search.is_some() {
+        handle_search_key(key, data, state);
+        return Ok(false);
+    }
+
+    // A second gallery key completes `gg`; anything else cancels the pending one.
+    let after_g =
+        matches!(state.pending_key.take(), Some(('g', at)) if at.elapsed() < KEY_SEQUENCE_TIMEOUT);
+
+    // Number keys pick tabs unless a binding took them.
+    let Some(action) = state.keys.action(key) else {
+        if let Some(index) = match key.code {
+            KeyCode::Char(c) => tab_for_hotkey(c),
+            _ => None,
+        } {
+            switch_tab(state, index, data.tabs.len());
+        }
+        return Ok(false);
+    };
+
+    match action {
+        Action::Quit => return Ok(true),
+        Action::Back
+            if state.tag_filter.is_some()
+                && matches!(data.tabs.get(state.tab_index), Some(TabData::Tags(_))) =>
+        {
+            back_to_tag_list(data, state)
+        }
+        Action::Back if !state.query.is_empty() || state.tag_filter.is_some() => {
+            state.query.clear();
+            state.tag_filter = None;
+            reset_selection(state);
+        }
+        Action::Back => return Ok(true),
+        Action::Picker => state.picker = Some(Picker::default()),
+        Action::GalleryPeek => state.gallery_peek = Some(Picker::default()),
+        Action::TagPicker => open_tag_picker(data, state),
+        Action::Messages => state.status_history = Some(0),
+        Action::Help => state.help = Some(0),
+        Action::Command => {
+            state.command = Some(LineEditor::default());
+            state.status = None;
+        }
+        Action::Search => {
+            state.search = Some(SearchBackup {
+                query: state.query.clone(),
+                list_index: state.list_index,
+                list_scroll: state.list_scroll,
+                content_scroll: state.content_scroll,
+                editor: LineEditor::new(&state.query),
+            });
+            state.status = None;
+        }
+        Action::Gallery if after_g => state.content_scroll = 0,
+        Action::Gallery => state.pending_key = Some(('g', Instant::now())),
+        Action::NextTab => cycle_tab(state, 1, data.tabs.len()),
+        Action::PreviousTab => cycle_tab(state, -1, data.tabs.len()),
+        Action::MoveUp if content_focused(data, state) => scroll_content(state, -1),
+        Action::MoveDown if content_focused(data, state) => scroll_content(state, 1),
+        Action::MoveUp => move_selection(data, state, -1),
+        Action::MoveDown => move_selection(data, state, 1),
+        Action::ToggleFocus => {
+            state.focus = match state.focus {
+                Focus::List => Focus::Content,
+                Focus::Content => Focus::List,
+            };
+        }
+        Action::PageUp => scroll_content(state, -page_lines(state)),
+        Action::PageDown => scroll_content(state, page_lines(state)),
+        Action::HalfPageUp => scroll_content(state, -half_page_lines(state)),
+        Action::HalfPageDown => scroll_content(state, half_page_lines(state)),
+        Action::Top => state.content_scroll = 0,
+        Action::Bottom => state.content_scroll = state.content_scroll_max,
+        Action::Open => open_selected(data, state),
+        Action::OpenTabIndex => open_tab_index(data, state),
+        Action::Share => open_share_menu(data, state),
+        Action::CopyUrl => copy_selected_url(data, state),
+        Action::CopyBody => copy_selected_body(data, state),
+        Action::QrCode => open_qr(data, state),
+        Action::Pager => open_pager(data, state),
+        Action::Edit => open_editor(data, state),
+        Action::Save => save_selected(data, state),
+        Action::Links => open_link_list(data, state),
+        Action::Location => open_location(data, state),
+        Action::OpenDirectory => open_tab_dir(data, state),
+        Action::Footnote => jump_footnote(state),
+        Action::Diff => toggle_diff(data, state),
+        Action::HighContrast => {
+            state.high_contrast = !state.high_contrast;
+            state.status = Some(format!(
+                "High contrast {}.",
+                if state.high_contrast { "on" } else { "off" }
+            ));
+        }
+        Action::Drafts => toggle_drafts(data, state),
+        Action::NextMatch => jump_match(state, true),
+        Action::PreviousMatch => jump_match(state, false),
+    }
+
+    Ok(false)
+}
+
+/// Keys while the `m` overlay is open. The scroll offset is clamped when it is drawn, since
+/// only then is the wrapped height known.
+fn handle_status_history_key(key: KeyEvent, state: &mut AppState) {
+    let Some(scroll) = state.status_history.as_mut() else {
+        return;
+    };
+    let page = state.content_height.max(1);
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('m') => state.status_history = None,
+        KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => *scroll += 1,
+        KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+        KeyCode::PageDown => *scroll += page,
+        KeyCode::Home => *scroll = 0,
+        _ => {}
+    }
+}
+
+/// Keys while the `?` overlay is open: the scrolling keys move it and anything else closes it.
+fn handle_help_key(key: KeyEvent, state: &mut AppState) {
+    let Some(scroll) = state.help.as_mut() else {
+        return;
+    };
+    let page = state.content_height.max(1);
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => *scroll += 1,
+        KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+        KeyCode::PageDown => *scroll += page,
+        _ => state.help = None,
+    }
+}
+
+/// Keys while the `/` prompt is open: typing narrows the list live, Enter keeps the filter,
+/// Esc restores the list as it was before the prompt opened.
+fn handle_search_key(key: KeyEvent, data: &AppData, state: &mut AppState) {
+    match key.code {
+        KeyCode::Esc => {
+            if let Some(backup) = state.search.take() {
+                state.query = backup.query;
+                state.list_index = backup.list_index;
+                state.list_scroll = backup.list_scroll;
+                state.content_scroll = backup.content_scroll;
+            }
+        }
+        KeyCode::Enter => state.search = None,
+        KeyCode::Up => move_selection(data, state, -1),
+        KeyCode::Down => move_selection(data, state, 1),
+        _ => {
+            if let Some(search) = state.search.as_mut() {
+                if search.editor.handle_key(key) && search.editor.text != state.query {
+                    state.query = search.editor.text.clone();
+                    reset_selection(state);
+                }
+            }
+        }
+    }
+}
+
+/// Keys while the `:` prompt is open: Enter runs the command unless `command_problem` objects,
+/// Esc drops it, ↑/↓ step through earlier commands, and Tab completes the path of a path
+/// command. Checks and completions come back from the IO worker as events.
+fn handle_command_key(key: KeyEvent, data: &AppData, state: &mut AppState) {
+    let Some(editor) = state.command.as_mut() else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => {
+            state.command = None;
+            state.command_recall = None;
+            state.command_checks.clear();
+        }
+        KeyCode::Enter if matches!(state.command_checks.get(&editor.text), Some(Some(_))) => {}
+        KeyCode::Enter => {
+            let line = editor.text.trim().to_string();
+            state.command = None;
+            state.command_recall = None;
+            state.command_checks.clear();
+            if !line.is_empty() && state.command_history.last() != Some(&line) {
+                state.command_history.push(line.clone());
+            }
+            run_command(&line, data, state);
+        }
+        KeyCode::Up | KeyCode::Down => {
+            let last = state.command_history.len().checked_sub(1);
+            let recall = match (key.code, state.command_recall) {
+                (KeyCode::Up, None) => last,
+                (KeyCode::Up, Some(index)) => Some(index.saturating_sub(1)),
+                (_, Some(index)) if Some(index) < last => Some(index + 1),
+                _ => None,
+            };
+            state.command_recall = recall;
+            *editor = LineEditor::new(recall.map_or("", |index| &state.command_history[index]));
+        }
+        KeyCode::Tab => {
+            let before = editor.text[..editor.cursor].to_string();
+            queue_io(move || complete_path_command(before));
+        }
+        _ => {
+            editor.handle_key(key);
+        }
+    }
+    check_command_line(state);
+}
+
+/// The `:` commands that take a path: `export <file>` and `content <dir>`.
+const PATH_COMMANDS: [&str; 2] = ["export", "content"];
+
+/// `(command, path)` when `line` is one of `PATH_COMMANDS` followed by a space.
+fn path_command(line: &str) -> Option<(&'static str, &str)> {
+    PATH_COMMANDS.iter().find_map(|command| {
+        let path = line.strip_prefix(command)?.strip_prefix(' ')?;
+        Some((*command, path))
+    })
+}
+
+/// Queues `command_problem` for the `:` line unless it was checked already. Lines that aren't
+/// path commands have nothing to check.
+fn check_command_line(state: &AppState) {
+    let Some(editor) = &state.command else {
+        return;
+    };
+    if path_command(editor.text.trim_start()).is_none()
+        || state.command_checks.contains_key(&editor.text)
+    {
+        return;
+    }
+    let line = editor.text.clone();
+    queue_io(move || {
+        let problem = command_problem(&line);
+        Some(AppEvent::CommandChecked { line, problem })
+    });
+}
+
+/// The IO worker's half of Tab: directories completing the path in `before`.
+fn complete_path_command(before: String) -> Option<AppEvent> {
+    let (command, path) = path_command(&before)?;
+    let completions = path_completions(path.trim_start())
+        .into_iter()
+        .map(|path| format!("{} {}", command, path))
+        .collect();
+    Some(AppEvent::Completions {
+        before,
+        completions,
+    })
+}
+
+/// What's wrong with a `:` command line before it runs, shown next to the prompt. Only the
+/// path commands are checked: `export` needs its file's directory to exist, `content` the
+/// directory itself.
+fn command_problem(line: &str) -> Option<String> {
+    assert_off_ui_thread("checking a path");
+    let (command, path) = path_command(line.trim())?;
+    let path = expand_path(path.trim());
+    let dir = match command {
+        "export" => path.parent()?.to_path_buf(),
+        _ => path,
+    };
+    if dir.as_os_str().is_empty() || dir.is_dir() {
+        None
+    } else {
+        Some(format!("no directory {}", dir.display()))
+    }
+}
+
+/// Directories that complete `typed`, a path as typed (`~` and `$VARS` are expanded to look it
+/// up), each written as `typed` with the rest of its name and a `/`. Hidden directories only
+/// show up once `typed` has their leading `.`.
+fn path_completions(typed: &str) -> Vec<String> {
+    assert_off_ui_thread("completing a path");
+    let (dir, partial) = match typed.rfind('/') {
+        Some(slash) => typed.split_at(slash + 1),
+        None => ("", typed),
+    };
+    let lookup = if dir.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_path(dir)
+    };
+    let Ok(entries) = fs::read_dir(lookup) else {
+        return Vec::new();
+    };
+    let mut completions = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(partial))
+        .filter(|name| !name.starts_with('.') || partial.starts_with('.'))
+        .map(|name| format!("{}{}/", dir, name))
+        .collect::<Vec<_>>();
+    completions.sort();
+    completions
+}
+
+/// Runs a `:` command. `timer <duration>` starts a reading session (`15m`, `1h`, `90s`, or bare
+/// minutes) and `timer off` cancels it; `export <path>` writes what `johnjeong export` prints to
+/// a file; `content <dir>` reads content from `dir` from now on, and `clone <git-url>` from a
+/// fresh clone.
+fn run_command(line: &str, data: &AppData, state: &mut AppState) {
+    if let Some(path) = line.strip_prefix("export ") {
+        export_to_file(data, state, expand_path(path.trim()));
+        return;
+    }
+    if let Some(dir) = line.strip_prefix("content ") {
+        use_content_dir(state, expand_path(dir.trim()));
+        return;
+    }
+    if let Some(url) = line.strip_prefix("clone ") {
+        clone_in_app(state, url.trim().to_string());
+        return;
+    }
+    let mut words = line.split_whitespace();
+    match (words.next(), words.next(), words.next()) {
+        (None, _, _) => {}
+        (Some("timer"), Some("off" | "stop" | "cancel"), None) => {
+            state.status = Some(match state.timer.take() {
+                Some(_) => "Reading timer cancelled.".to_string(),
+                None => "No reading timer running.".to_string(),
+            });
+        }
+        (Some("timer"), Some(value), None) => match parse_duration(value) {
+            Some(duration) if !duration.is_zero() => {
+                let timer = ReadingTimer::start(duration);
+                state.status = Some(format!("Reading timer set for {}m.", timer.minutes_left()));
+                state.timer = Some(timer);
+            }
+            _ => state.status = Some(format!("Can't read \"{}\" as a duration.", value)),
+        },
+        (Some("timer"), _, _) => {
+            state.status = Some("Usage: :timer 15m, or :timer off".to_string())
+        }
+        (Some("tour"), None, _) => state.tour = Some(0),
+        (Some("export"), _, _) => state.status = Some("Usage: :export <path>".to_string()),
+        (Some("content"), _, _) => state.status = Some("Usage: :content <dir>".to_string()),
+        (Some("clone"), _, _) => state.status = Some("Usage: :clone <git-url>".to_string()),
+        (Some(command), _, _) => state.status = Some(format!("Unknown command :{}", command)),
+    }
+}
+
+/// `:content <dir>`, which the first run also opens with: saves `dir` as `[content] dir` and
+/// loads everything from it on the IO worker.
+fn use_content_dir(state: &mut AppState, dir: PathBuf) {
+    if let Err(message) = check_capability(Capability::WriteState) {
+        state.status = Some(message);
+        return;
+    }
+    let settings = SETTINGS.get().cloned();
+    state.status = Some(format!("Loading {}…", dir.display()));
+    queue_io(move || {
+        if let Err(err) = set_config_value("content", "dir", &dir.to_string_lossy()) {
+            return Some(AppEvent::Status(format!(
+                "Couldn't save the content directory ({})",
+                err
+            )));
+        }
+        Some(load_content_dir(settings, dir))
+    });
+}
+
+/// `:clone <git-url>`: `--clone` from inside the app. git's progress shows on the status line
+/// while it runs, then the clone loads as with `:content`.
+fn clone_in_app(state: &mut AppState, url: String) {
+    if let Err(message) = check_capability(Capability::Network)
+        .and_then(|()| check_capability(Capability::WriteState))
+    {
+        state.status = Some(message);
+        return;
+    }
+    let settings = SETTINGS.get().cloned();
+    state.status = Some(format!("Cloning {}…", url));
+    queue_io(move || {
+        let cloned = clone_content(
+            &url,
+            Some(&mut |line: &str| report_progress(Some(line.to_string()))),
+        );
+        report_progress(None);
+        Some(match cloned {
+            Ok(dir) => load_content_dir(settings, dir),
+            Err(message) => AppEvent::Status(message),
+        })
+    });
+}
+
+/// Everything loaded from `dir` with the settings the app started with. Gallery dates are read
+/// up front, since nothing is waiting on the load.
+fn load_content_dir(settings: Option<Config>, dir: PathBuf) -> AppEvent {
+    let mut settings = match settings.map_or_else(Config::load, Ok) {
+        Ok(settings) => settings,
+        Err(message) => return AppEvent::Status(message),
+    };
+    settings.content_dir = Some(dir.clone());
+    settings.gallery_dates = GalleryDates::Eager;
+    let (data, status) = build_app_data(&settings);
+    AppEvent::ContentLoaded {
+        dir,
+        data: Box::new(data),
+        status,
+    }
+}
+
+/// `:export <path>`: the JSON is built here and written on the IO worker.
+fn export_to_file(data: &AppData, state: &mut AppState, path: PathBuf) {
+    if let Err(message) = check_capability(Capability::WriteState) {
+        state.status = Some(message);
+        return;
+    }
+    let mut json = Vec::new();
+    if let Err(err) = run_export(&mut json, data, false) {
+        state.status = Some(format!("Failed to export ({})", err));
+        return;
+    }
+    queue_io(move || {
+        let written = create_file(&path, false).and_then(|mut file| file.write_all(&json));
+        Some(AppEvent::Status(match written {
+            Ok(()) => format!("Exported to {}", path.display()),
+            Err(err) => format!("Failed to export to {} ({})", path.display(), err),
+        }))
+    });
+}
+
+/// `90s`, `15m`, `1h`, or a bare number of minutes.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (number, unit) = match value.find(|ch: char| !ch.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "m"),
+    };
+    let number = number.parse::<u64>().ok()?;
+    let seconds = match unit {
+        "s" => number,
+        "m" | "min" => number.checked_mul(60)?,
+        "h" => number.checked_mul(3600)?,
+        _ => return None,
+    };
+    Some(Duration::from_secs(seconds))
+}
+
+/// A `:timer` reading session. Time only counts while the app is running: a gap between loop
+/// turns longer than the loop could have slept (the process was stopped, or the machine slept)
+/// is skipped, so the session pauses instead of expiring in the background.
+struct ReadingTimer {
+    remaining: Duration,
+    last: Instant,
+    /// Word counts of the posts opened during the session, by URL.
+    reads: HashMap<String, usize>,
+}
+
+/// Allowance on top of the poll timeout before a gap counts as a pause.
+const TIMER_SLACK: Duration = Duration::from_secs(2);
+
+impl ReadingTimer {
+    fn start(duration: Duration) -> ReadingTimer {
+        ReadingTimer {
+            remaining: duration,
+            last: Instant::now(),
+            reads: HashMap::new(),
+        }
+    }
+
+    fn minutes_left(&self) -> u64 {
+        self.remaining.as_secs().div_ceil(60)
+    }
+
+    /// How long until the minute shown in the footer changes.
+    fn next_change(&self) -> Duration {
+        let shown = Duration::from_secs(self.minutes_left().saturating_sub(1) * 60);
+        self.remaining.saturating_sub(shown)
+    }
+
+    /// Counts the time since the last call, up to `slept + TIMER_SLACK`. Returns true once the
+    /// session is over.
+    fn advance(&mut self, slept: Duration) -> bool {
+        let now = Instant::now();
+        let step = now.duration_since(self.last).min(slept + TIMER_SLACK);
+        self.last = now;
+        self.remaining = self.remaining.saturating_sub(step);
+        self.remaining.is_zero()
+    }
+
+    fn read(&mut self, post: &Post) {
+        self.reads.entry(post.url.clone()).or_insert(post.words);
+    }
+
+    fn summary(&self) -> String {
+        let posts = self.reads.len();
+        let words = self.reads.values().sum::<usize>();
+        format!(
+            "Reading session complete — {} post{} read, {} words",
+            posts,
+            if posts == 1 { "" } else { "s" },
+            group_thousands(words)
+        )
+    }
+}
+
+/// `2400` as `2,400`.
+fn group_thousands(value: usize) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Bracketed paste goes into whichever prompt is open, without being read as keys.
+fn handle_paste(text: &str, state: &mut AppState) {
+    if let Some(editor) = state.command.as_mut() {
+        editor.insert(text);
+        check_command_line(state);
+    } else if let Some(picker) = state.picker.as_mut() {
+        picker.query.insert(text);
+        picker.selected = 0;
+    } else if let Some(peek) = state.gallery_peek.as_mut() {
+        peek.query.insert(text);
+        peek.selected = 0;
+    } else if let Some(search) = state.search.as_mut() {
+        search.editor.insert(text);
+        state.query = search.editor.text.clone();
+        reset_selection(state);
+    }
+}
+
+fn handle_picker_key(key: KeyEvent, data: &AppData, state: &mut AppState) {
+    let Some(picker) = state.picker.as_mut() else {
+        return;
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => state.picker = None,
+        KeyCode::Enter => {
+            let results = picker_results(data, &picker.query.text, state.show_drafts);
+            let choice = results.get(picker.selected).copied();
+            state.picker = None;
+            if let Some((_, tab_index, post_index)) = choice {
+                switch_tab(state, tab_index, data.tabs.len());
+                if let Some(TabData::Content(tab)) = data.tabs.get(tab_index) {
+                    state.list_index = visible_posts(tab, state)
+                        .iter()
+                        .position(|post| std::ptr::eq(*post, &tab.posts[post_index]))
+                        .unwrap_or(0);
+                }
+            }
+        }
+        KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+        KeyCode::Char('p') if ctrl => picker.selected = picker.selected.saturating_sub(1),
+        KeyCode::Down => picker.selected += 1,
+        KeyCode::Char('n') if ctrl => picker.selected += 1,
+        _ => {
+            if picker.query.handle_key(key) {
+                picker.selected = 0;
+            }
+        }
+    }
+
+    if let Some(picker) = state.picker.as_mut() {
+        let total = picker_results(data, &picker.query.text, state.show_drafts).len();
+        picker.selected = picker.selected.min(total.saturating_sub(1));
+    }
+}
+
+/// Keys for the `P` overlay. It never touches the tab, list, or scroll state underneath, so
+/// closing it lands exactly where reading left off.
+fn handle_gallery_peek_key(key: KeyEvent, data: &AppData, state: &mut AppState) {
+    let Some(peek) = state.gallery_peek.as_mut() else {
+        return;
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => state.gallery_peek = None,
+        KeyCode::Enter => {
+            if let Some(post) = gallery_results(data, &peek.query.text).get(peek.selected) {
+                open_gallery_post(state, post);
+            }
+        }
+        KeyCode::Up => peek.selected = peek.selected.saturating_sub(1),
+        KeyCode::Char('p') if ctrl => peek.selected = peek.selected.saturating_sub(1),
+        KeyCode::Down => peek.selected += 1,
+        KeyCode::Char('n') if ctrl => peek.selected += 1,
+        _ => {
+            if peek.query.handle_key(key) {
+                peek.selected = 0;
+            }
+        }
+    }
+
+    if let Some(peek) = state.gallery_peek.as_mut() {
+        let total = gallery_results(data, &peek.query.text).len();
+        peek.selected = peek.selected.min(total.saturating_sub(1));
+    }
+}
+
+/// Gallery items matching `query`, best fuzzy match first; all of them in gallery order when
+/// the query is empty.
+fn gallery_results<'a>(data: &'a AppData, query: &str) -> Vec<&'a Post> {
+    let Some(gallery) = data.tabs.iter().find_map(|tab| match tab {
+        TabData::Content(tab) if is_gallery_tab(tab) => Some(tab),
+        _ => None,
+    }) else {
+        return Vec::new();
+    };
+    let mut results = gallery
+        .posts
+        .iter()
+        .filter_map(|post| fuzzy_score(&post.title, query).map(|score| (score, post)))
+        .collect::<Vec<_>>();
+    results.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    results.into_iter().map(|(_, post)| post).collect()
+}
+
+/// Every post across the content tabs as `(score, tab_index, post_index)`, best match first.
+/// With an empty query everything is listed in tab order. Drafts are left out unless `drafts`.
+fn picker_results(data: &AppData, query: &str, drafts: bool) -> Vec<(i32, usize, usize)> {
+    let mut results = Vec::new();
+    for (tab_index, tab) in data.tabs.iter().enumerate() {
+        let TabData::Content(tab) = tab else {
+            continue;
+        };
+        for (post_index, post) in tab.posts.iter().enumerate() {
+            let listed = if post.draft { drafts } else { tab.lists(post) };
+            if !listed {
+                continue;
+            }
+            let candidate = format!("{} {}", post.title, post.date);
+            if let Some(score) = fuzzy_score(&candidate, query) {
+                results.push((score, tab_index, post_index));
+            }
+        }
+    }
+    results.sort_by_key(|&(score, tab_index, post_index)| {
+        (std::cmp::Reverse(score), tab_index, post_index)
+    });
+    results
+}
+
+/// Scores `candidate` if every non-space character of `query` appears in it in order
+/// (case-insensitive). Hits at the start of a word and runs of consecutive hits score higher,
+/// and shorter candidates win ties.
+fn fuzzy_score(candidate: &str, query: &str) -> Option<i32> {
+    let mut needles = query.chars().filter(|ch| !ch.is_whitespace()).peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut last_hit: Option<usize> = None;
+    let mut length = 0;
+
+    for (index, ch) in candidate.chars().enumerate() {
+        length = index + 1;
+        if let Some(&needle) = needles.peek() {
+            if chars_eq_ignore_case(ch, needle) {
+                score += 1;
+                if previous.is_none_or(|prev| !prev.is_alphanumeric()) {
+                    score += 8;
+                }
+                if last_hit.is_some_and(|hit| hit + 1 == index) {
+                    score += 4;
+                }
+                last_hit = Some(index);
+                needles.next();
+            }
+        }
+        previous = Some(ch);
+    }
+
+    if needles.peek().is_some() {
+        return None;
+    }
+    Some(score * 10 - (length / 8) as i32)
+}
+
+fn open_tag_picker(data: &AppData, state: &mut AppState) {
+    let Some(TabData::Content(tab)) = data.tabs.get(state.tab_index) else {
+        state.status = Some("Tags are only available on content tabs.".to_string());
+        return;
+    };
+    let tags = tag_counts(std::iter::once(tab));
+    if tags.is_empty() {
+        state.status = Some(format!("No tagged posts in {}.", tab.name));
+        return;
+    }
+    let selected = state
+        .tag_filter
+        .as_ref()
+        .and_then(|current| tags.iter().position(|(tag, _)| tag == current))
+        .map_or(0, |index| index + 1);
+    state.tag_picker = Some(TagPicker {
+        tags,
+        selected,
+        scroll: 0,
+    });
+}
+
+/// Tags across `tabs` with how many posts carry each, most used first.
+fn tag_counts<'a>(tabs: impl Iterator<Item = &'a ContentTab>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for tab in tabs {
+        for tag in tab.listed_posts().flat_map(|post| &post.tags) {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    let mut tags = counts
+        .into_iter()
+        .map(|(tag, count)| (tag.to_string(), count))
+        .collect::<Vec<_>>();
+    tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    tags
+}
+
+/// Row 0 of the tag picker is "All posts", which clears the filter; row `n` is tag `n - 1`.
+fn handle_tag_picker_key(key: KeyEvent, state: &mut AppState) {
+    let Some(picker) = state.tag_picker.as_mut() else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => state.tag_picker = None,
+        KeyCode::Up | KeyCode::Char('k') => picker.selected = picker.selected.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => {
+            picker.selected = (picker.selected + 1).min(picker.tags.len())
+        }
+        KeyCode::Enter => {
+            let choice = picker
+                .selected
+                .checked_sub(1)
+                .and_then(|index| picker.tags.get(index))
+                .map(|(tag, _)| tag.clone());
+            state.tag_picker = None;
+            state.tag_filter = choice;
+            reset_selection(state);
+        }
+        _ => {}
+    }
+}
+
+/// Collects the links on the IO worker, since finding images means looking for their files;
+/// `show_link_list` opens the overlay when they arrive.
+fn open_link_list(data: &AppData, state: &mut AppState) {
+    let Some(post) = selected_post(data, state) else {
+        state.status = Some("Select a post to list its links.".to_string());
+        return;
+    };
+    state.status = Some("Collecting links…".to_string());
+    let (url, body, dir) = (post.url.clone(), post.body.clone(), post.dir.clone());
+    queue_io(move || {
+        let links = body_links(&body, dir.as_deref());
+        Some(AppEvent::Links { url, links })
+    });
+}
+
+/// Opens the `L` overlay with links collected for `url`, unless the selection moved on.
+fn show_link_list(
+    data: &AppData,
+    state: &mut AppState,
+    url: &str,
+    links: Vec<(String, LinkTarget)>,
+) {
+    if selected_post(data, state).is_none_or(|post| post.url != url) {
+        return;
+    }
+    if links.is_empty() {
+        state.status = Some("No links in this post.".to_string());
+        return;
+    }
+    state.status = None;
+    state.link_list = Some(LinkList {
+        links,
+        selected: 0,
+        scroll: 0,
+    });
+}
+
+/// Keys while the `L` overlay is open. It never touches the content pane, so Esc lands back
+/// at the same scroll position.
+fn handle_link_list_key(key: KeyEvent, state: &mut AppState) {
+    let Some(list) = state.link_list.as_mut() else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => state.link_list = None,
+        KeyCode::Up | KeyCode::Char('k') => list.selected = list.selected.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => {
+            list.selected = (list.selected + 1).min(list.links.len() - 1)
+        }
+        KeyCode::Enter | KeyCode::Char('o') => {
+            let (label, target) = list.links[list.selected].clone();
+            state.link_list = None;
+            match target {
+                LinkTarget::Web(url) => open_link(state, &url, &label),
+                LinkTarget::File(path) => open_file(state, &path, &label),
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Web and mail links in a post body, from markdown links and bare `http(s)://` URLs alike, in
+/// document order with repeats dropped. Images are included when they are on the web or
+/// resolve to a file from `dir`; other relative links have nowhere to open and are skipped.
+fn body_links(body: &str, dir: Option<&Path>) -> Vec<(String, LinkTarget)> {
+    let mut links: Vec<(String, LinkTarget)> = Vec::new();
+    let mut add = |label: String, target: LinkTarget| {
+        if !links.iter().any(|(_, seen)| *seen == target) {
+            links.push((label, target));
+        }
+    };
+    let web = |url: &str| {
+        ["https://", "http://", "mailto:"]
+            .iter()
+            .any(|scheme| url.starts_with(scheme))
+            .then(|| LinkTarget::Web(url.to_string()))
+    };
+    for line in body.lines() {
+        let mut rest = line;
+        loop {
+            let link = find_link(rest);
+            let prose = link.as_ref().map_or(rest, |(start, _)| &rest[..*start]);
+            for url in bare_urls(prose) {
+                add(url.to_string(), LinkTarget::Web(url.to_string()));
+            }
+            let Some((_, link)) = link else {
+                break;
+            };
+            if link.image {
+                let target =
+                    web(link.url).or_else(|| resolve_image(dir?, link.url).map(LinkTarget::File));
+                if let Some(target) = target {
+                    add(format!("image: {}", image_caption(&link)), target);
+                }
+            } else if let Some(target) = web(link.url) {
+                let label = link.text.trim();
+                add(
+                    if label.is_empty() { link.url } else { label }.to_string(),
+                    target,
+                );
+            }
+            rest = &rest[link.end..];
+        }
+    }
+    links
+}
+
+/// A relative image path as a file, trying the post's own directory and then the content root
+/// above it (for `/images/...`).
+fn resolve_image(dir: &Path, url: &str) -> Option<PathBuf> {
+    assert_off_ui_thread("looking for an image");
+    let relative = url.split(['?', '#']).next().unwrap_or(url);
+    let relative = relative.strip_prefix("./").unwrap_or(relative);
+    if relative.is_empty() || relative.contains("://") {
+        return None;
+    }
+    let from_root = dir
+        .parent()
+        .map(|root| root.join(relative.trim_start_matches('/')));
+    [Some(dir.join(relative)), from_root]
+        .into_iter()
+        .flatten()
+        .find(|path| path.is_file())
+}
+
+/// `http(s)://` URLs in plain text. A URL ends at whitespace or `<>"`, and trailing sentence
+/// punctuation is not part of it, nor is a `)` that closes a parenthesis opened outside it.
+fn bare_urls(text: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let mut search = 0;
+    while let Some(offset) = text[search..].find("http") {
+        let start = search + offset;
+        let candidate = &text[start..];
+        if !(candidate.starts_with("https://") || candidate.starts_with("http://")) {
+            search = start + 4;
+            continue;
+        }
+        let len = candidate
+            .find(|ch: char| ch.is_whitespace() || matches!(ch, '<' | '>' | '"'))
+            .unwrap_or(candidate.len());
+        let mut url = &candidate[..len];
+        loop {
+            let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '*', '_']);
+            let unbalanced = trimmed.ends_with(')')
+                && trimmed.matches('(').count() < trimmed.matches(')').count();
+            url = if unbalanced {
+                &trimmed[..trimmed.len() - 1]
+            } else {
+                trimmed
+            };
+            if !unbalanced {
+                break;
+            }
+        }
+        if !url.ends_with("://") {
+            urls.push(url);
+        }
+        search = start + len;
+    }
+    urls
+}
+
+/// Leaves a tag's post list on the Tags tab, reselecting that tag in the full tag list.
+fn back_to_tag_list(data: &AppData, state: &mut AppState) {
+    let tag = state.tag_filter.take();
+    state.query.clear();
+    reset_selection(state);
+    if let (Some(TabData::Tags(tags)), Some(tag)) = (data.tabs.get(state.tab_index), tag) {
+        state.list_index = visible_tags(tags, state)
+            .iter()
+            .position(|(candidate, _)| *candidate == tag)
+            .unwrap_or(0);
+    }
+}
+
+fn reset_selection(state: &mut AppState) {
+    state.list_index = 0;
+    state.list_scroll = 0;
+    state.content_scroll = 0;
+    state.content_scroll_max = 0;
+    state.footnote_return = None;
+}
+
+fn switch_tab(state: &mut AppState, index: usize, total: usize) {
+    if index >= total {
+        return;
+    }
+    state.tab_index = index;
+    state.query.clear();
+    state.search = None;
+    state.tag_filter = None;
+    state.list_index = 0;
+    state.list_scroll = 0;
+    state.content_scroll = 0;
+    state.content_scroll_max = 0;
+    state.footnote_return = None;
+    state.status = None;
+}
+
+/// `--tab` and `--post` (or `start_tab`): opens on that tab with that post selected. A tab that
+/// doesn't exist starts on About and a post that doesn't leaves the tab's first one selected,
+/// each with a status message instead of an error.
+fn select_start(data: &AppData, state: &mut AppState, tab: Option<&str>, post: Option<&str>) {
+    let total = data.tabs.len();
+    let mut content_tab = None;
+    if let Some(name) = tab {
+        match start_tab_index(data, name) {
+            Some(index) => {
+                switch_tab(state, index, total);
+                if let Some(TabData::Content(tab)) = data.tabs.get(index) {
+                    content_tab = Some(tab);
+                }
+            }
+            None => {
+                switch_tab(state, 0, total);
+                state.status = Some(format!("No tab \"{}\"; starting on About.", name));
+                return;
+            }
+        }
+    }
+    let Some(slug) = post else {
+        return;
+    };
+    if tab.is_some() && content_tab.is_none() {
+        state.status = Some(format!("No posts to select \"{}\" from here.", slug));
+        return;
+    }
+    match resolve_slug(data, slug, content_tab) {
+        Ok((tab, post)) => {
+            let Some(index) = data.tabs.iter().position(|candidate| {
+                matches!(candidate, TabData::Content(candidate) if std::ptr::eq(candidate, tab))
+            }) else {
+                return;
+            };
+            switch_tab(state, index, total);
+            // Asking for a draft by name shows drafts, and for a post the tab's filter hides
+            // lists it anyway.
+            if post.draft {
+                state.show_drafts = true;
+            } else if !tab.lists(post) {
+                state.revealed = Some(post.url.clone());
+            }
+            state.list_index = visible_posts(tab, state)
+                .iter()
+                .position(|candidate| std::ptr::eq(*candidate, post))
+                .unwrap_or(0);
+        }
+        Err(candidates) if candidates.is_empty() => {
+            state.status = Some(format!("No post matches \"{}\".", slug));
+        }
+        Err(candidates) => {
+            state.status = Some(format!(
+                "\"{}\" matches {} posts; pick one with --tab or a longer slug.",
+                slug,
+                candidates.len()
+            ));
+        }
+    }
+}
+
+/// A tab by 1-based number or by name (as `tab_name` shows it, or the content directory), in
+/// any case and with hyphens for spaces.
+fn start_tab_index(data: &AppData, value: &str) -> Option<usize> {
+    let value = value.trim();
+    if let Ok(number) = value.parse::<usize>() {
+        return (1..=data.tabs.len()).contains(&number).then(|| number - 1);
+    }
+    data.tabs.iter().position(|tab| {
+        let name = tab_name(tab);
+        name.eq_ignore_ascii_case(value)
+            || name.replace(' ', "-").eq_ignore_ascii_case(value)
+            || matches!(tab, TabData::Content(tab) if tab_matches(tab, value))
+    })
+}
+
+/// Tab/shift-tab and the arrow keys: the next or previous tab, wrapping at either end.
+fn cycle_tab(state: &mut AppState, delta: isize, total: usize) {
+    if total == 0 {
+        return;
+    }
+    let next = (state.tab_index as isize + delta).rem_euclid(total as isize) as usize;
+    switch_tab(state, next, total);
+}
+
+fn move_selection(data: &AppData, state: &mut AppState, delta: i32) {
+    let max = list_length(data, state);
+    if max == 0 {
+        state.list_index = 0;
+        return;
+    }
+    let len = max as i32;
+    let mut next = state.list_index as i32 + delta;
+    if next < 0 {
+        next = len - 1;
+    } else if next >= len {
+        next = 0;
+    }
+    state.list_index = next as usize;
+    state.content_scroll = 0;
+    state.content_scroll_max = 0;
+    state.footnote_return = None;
+}
+
+/// Where the last frame drew what can be clicked, so clicks map back to tabs and list rows.
+#[derive(Default)]
+struct HitMap {
+    tab_row: u16,
+    /// `(first column, column past the end, tab index)` of each nav label drawn on `tab_row`.
+    tabs: Vec<(u16, u16, usize)>,
+    list: Option<ListArea>,
+}
+
+/// The list rows on screen: row `y + n` shows entry `first + n`, for `n < rows`.
+struct ListArea {
+    x: u16,
+    width: usize,
+    y: u16,
+    rows: usize,
+    first: usize,
+}
+
+impl ListArea {
+    fn new(x: u16, width: usize, y: u16, height: usize, first: usize, total: usize) -> ListArea {
+        ListArea {
+            x,
+            width,
+            y,
+            rows: height.min(total.saturating_sub(first)),
+            first,
+        }
+    }
+
+    fn index_at(&self, column: u16, row: u16) -> Option<usize> {
+        let inside = column >= self.x
+            && ((column - self.x) as usize) < self.width
+            && row >= self.y
+            && ((row - self.y) as usize) < self.rows;
+        inside.then(|| self.first + (row - self.y) as usize)
+    }
+}
+
+/// A left click on a tab label switches to it; on a list row it selects the row, or opens it
+/// if it was already selected (so a double click opens). Anything else is ignored.
+fn handle_click(data: &AppData, state: &mut AppState, column: u16, row: u16) -> bool {
+    if overlay_open(state) {
+        return false;
+    }
+    if row == state.hits.tab_row {
+        let tab = state
+            .hits
+            .tabs
+            .iter()
+            .find(|(start, end, _)| (*start..*end).contains(&column))
+            .map(|(_, _, index)| *index);
+        if let Some(index) = tab {
+            switch_tab(state, index, data.tabs.len());
+            return true;
+        }
+        return false;
+    }
+    let Some(index) = state
+        .hits
+        .list
+        .as_ref()
+        .and_then(|list| list.index_at(column, row))
+    else {
+        return false;
+    };
+    if index == state.list_index {
+        open_selected(data, state);
+    } else {
+        state.list_index = index;
+        state.content_scroll = 0;
+        state.content_scroll_max = 0;
+        state.footnote_return = None;
+    }
+    true
+}
+
+/// Whether `j`/`k` should scroll the post. Only tabs that show a post body can take content
+/// focus; elsewhere the keys keep moving the list whatever `focus` says.
+fn content_focused(data: &AppData, state: &AppState) -> bool {
+    state.focus == Focus::Content
+        && match data.tabs.get(state.tab_index) {
+            Some(TabData::Content(tab)) => !is_gallery_tab(tab),
+            Some(TabData::Tags(_)) => state.tag_filter.is_some(),
+            _ => false,
+        }
+}
+
+/// Whether a prompt or overlay has the keyboard, in which case the mouse does nothing.
+fn overlay_open(state: &AppState) -> bool {
+    state.command.is_some()
+        || state.picker.is_some()
+        || state.tag_picker.is_some()
+        || state.confirm_url.is_some()
+        || state.gallery_peek.is_some()
+        || state.share_menu.is_some()
+        || state.link_list.is_some()
+        || state.timer_done.is_some()
+        || state.tour.is_some()
+        || state.status_history.is_some()
+        || state.help.is_some()
+        || state.qr.is_some()
+}
+
+const WHEEL_LINES: i32 = 3;
+
+/// The wheel over the list column (or anywhere on About, which has no content pane) moves the
+/// selection; to the right of the list it scrolls the content pane. Ignored while an overlay
+/// or prompt is open. Returns whether anything moved.
+fn handle_scroll(
+    data: &AppData,
+    state: &mut AppState,
+    column: u16,
+    delta: i32,
+) -> io::Result<bool> {
+    if overlay_open(state) {
+        return Ok(false);
+    }
+    let max_width = terminal::size()?.0.saturating_sub(4) as usize;
+    let list_end = 2 + list_pane_width(max_width) + 1;
+    let about = matches!(data.tabs.get(state.tab_index), Some(TabData::About(_)));
+    if about || (column as usize) <= list_end {
+        move_selection(data, state, delta);
+    } else {
+        scroll_content(state, delta * WHEEL_LINES);
+    }
+    Ok(true)
+}
+
+fn scroll_content(state: &mut AppState, delta: i32) {
+    let max = state.content_scroll_max as i32;
+    let mut next = state.content_scroll as i32 + delta;
+    if next < 0 {
+        next = 0;
+    } else if next > max {
+        next = max;
+    }
+    state.content_scroll = next as usize;
+}
+
+/// Jumps from the first footnote reference on screen to its definition, or back again if the
+/// previous jump hasn't been undone yet.
+fn jump_footnote(state: &mut AppState) {
+    if let Some(previous) = state.footnote_return.take() {
+        state.content_scroll = previous;
+        return;
+    }
+
+    let visible = state.content_scroll..state.content_scroll + state.content_height.max(1);
+    match state
+        .footnote_jumps
+        .iter()
+        .find(|(reference, _)| visible.contains(reference))
+    {
+        Some(&(_, definition)) => {
+            state.footnote_return = Some(state.content_scroll);
+            state.content_scroll = definition.min(state.content_scroll_max);
+        }
+        None => state.status = Some("No footnote references on screen.".to_string()),
+    }
+}
+
+/// Scrolls so the next (or previous) line holding a search match is at the top of the pane.
+fn jump_match(state: &mut AppState, forward: bool) {
+    let target = if forward {
+        state
+            .match_lines
+            .iter()
+            .find(|&&line| line > state.content_scroll)
+    } else {
+        state
+            .match_lines
+            .iter()
+            .rev()
+            .find(|&&line| line < state.content_scroll)
+    };
+    match target {
+        Some(&line) => state.content_scroll = line.min(state.content_scroll_max),
+        None if state.match_lines.is_empty() => {
+            state.status = Some("No matches in this post.".to_string())
+        }
+        None => {}
+    }
+}
+
+fn open_tab_index(data: &AppData, state: &mut AppState) {
+    let Some(TabData::Content(tab)) = data.tabs.get(state.tab_index) else {
+        state.status = Some("This tab has no web page.".to_string());
+        return;
+    };
+    match &tab.web_url {
+        Some(url) => open_link(state, url, url),
+        None => state.status = Some(format!("{} has no web page.", tab.name)),
+    }
+}
+
+/// Opens the selected post's or photo's `location` in the maps service.
+fn open_location(data: &AppData, state: &mut AppState) {
+    let tab = match data.tabs.get(state.tab_index) {
+        Some(TabData::Content(tab)) => tab,
+        Some(TabData::Tags(tags)) if state.tag_filter.is_some() => &tags.posts,
+        _ => return,
+    };
+    let Some(post) = visible_posts(tab, state).get(state.list_index).copied() else {
+        return;
+    };
+    let Some(location) = post
+        .fields
+        .get("location")
+        .filter(|value| !value.is_empty())
+    else {
+        state.status = Some("This post has no location.".to_string());
+        return;
+    };
+    match map_url(location) {
+        Some(url) => open_link(state, &url, location),
+        None => state.status = Some(format!("Can't map \"{}\".", location)),
+    }
+}
+
+/// The maps link for a `location` value: a place name, or `lat, long` in decimal degrees
+/// (optionally with `N`/`S`/`E`/`W` instead of signs). Text that looks like coordinates but
+/// isn't valid gives `None`. Config `maps_url` overrides the service, with `{query}` replaced
+/// by the encoded location.
+fn map_url(location: &str) -> Option<String> {
+    let query = match parse_coordinates(location) {
+        Some(Some((lat, long))) => format!("{},{}", lat, long),
+        Some(None) => return None,
+        None => location.trim().to_string(),
+    };
+    let template = config()
+        .get("")
+        .and_then(|top| top.get("maps_url"))
+        .filter(|template| template.contains("{query}"))
+        .cloned()
+        .unwrap_or_else(|| {
+            if cfg!(target_os = "macos") {
+                "https://maps.apple.com/?q={query}".to_string()
+            } else {
+                "https://www.google.com/maps/search/?api=1&query={query}".to_string()
+            }
+        });
+    Some(template.replace("{query}", &percent_encode(&query)))
+}
+
+/// `None` if `text` isn't coordinate-shaped at all (a place name), `Some(None)` if it is but
+/// doesn't parse or is out of range.
+fn parse_coordinates(text: &str) -> Option<Option<(f64, f64)>> {
+    let coordinate_like = text.chars().any(|ch| ch.is_ascii_digit())
+        && text
+            .chars()
+            .all(|ch| ch.is_ascii_digit() || " .,+-°NSEWnsew".contains(ch));
+    if !coordinate_like {
+        return None;
+    }
+    let parse = |part: &str, positive: char, negative: char| -> Option<f64> {
+        let part = part.trim().trim_end_matches('°');
+        let (number, sign) = match part.chars().last()?.to_ascii_uppercase() {
+            ch if ch == positive => (&part[..part.len() - 1], 1.0),
+            ch if ch == negative => (&part[..part.len() - 1], -1.0),
+            _ => (part, 1.0),
+        };
+        number
+            .trim()
+            .trim_end_matches('°')
+            .parse::<f64>()
+            .ok()
+            .map(|value| value * sign)
+    };
+    let mut parts = text.split(',');
+    let (Some(lat), Some(long), None) = (parts.next(), parts.next(), parts.next()) else {
+        return Some(None);
+    };
+    let lat = parse(lat, 'N', 'S').filter(|lat| (-90.0..=90.0).contains(lat));
+    let long = parse(long, 'E', 'W').filter(|long| (-180.0..=180.0).contains(long));
+    Some(lat.zip(long))
+}
+
+fn open_tab_dir(data: &AppData, state: &mut AppState) {
+    let Some(TabData::Content(tab)) = data.tabs.get(state.tab_index) else {
+        state.status = Some("This tab has no content directory.".to_string());
+        return;
+    };
+    if let Err(message) = check_capability(Capability::RevealDirectory) {
+        state.status = Some(message);
+        return;
+    }
+    match &tab.local_dir {
+        Some(dir) => {
+            let dir = dir.clone();
+            open_in_background(state, &dir.display().to_string(), move || open_path(&dir));
+        }
+        None => state.status = Some(format!("{} has no local content directory.", tab.name)),
+    }
+}
+
+fn open_selected(data: &AppData, state: &mut AppState) {
+    match data.tabs.get(state.tab_index) {
+        Some(TabData::About(about)) => {
+            if let Some(link) = visible_links(about, state).get(state.list_index) {
+                open_link(state, link.url, link.label);
+            }
+        }
+        Some(TabData::Content(tab)) if is_gallery_tab(tab) => {
+            if let Some(post) = visible_posts(tab, state).get(state.list_index) {
+                open_gallery_post(state, post);
+            }
+        }
+        Some(TabData::Tags(tags)) if state.tag_filter.is_none() => {
+            if let Some((tag, _)) = visible_tags(tags, state).get(state.list_index) {
+                state.tag_filter = Some(tag.clone());
+                state.query.clear();
+                reset_selection(state);
+            }
+        }
+        Some(TabData::Tags(tags)) => open_post(&tags.posts, state),
+        Some(TabData::Content(tab)) => open_post(tab, state),
+        None => {}
+    }
+}
+
+/// Opens the selected post's page. A draft's is opened all the same, with a warning that it may
+/// not be up yet.
+fn open_post(tab: &ContentTab, state: &mut AppState) {
+    if let Some(post) = visible_posts(tab, state).get(state.list_index) {
+        if post.draft {
+            let label = format!("{} (a draft, so it may 404)", post.title);
+            open_link(state, &post.url, &label);
+        } else {
+            open_link(state, &post.url, &post.title);
+        }
+    }
+}
+
+/// Opens `url` in the browser, or in `--restricted` mode asks first with the full URL shown.
+fn open_link(state: &mut AppState, url: &str, label: &str) {
+    if check_capability(Capability::OpenUrl).is_err() {
+        state.confirm_url = Some(url.to_string());
+        return;
+    }
+    let url = url.to_string();
+    open_in_background(state, label, move || open_url(&url));
+}
+
+fn open_gallery_post(state: &mut AppState, post: &Post) {
+    let Some(path) = &post.file else {
+        return;
+    };
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| post.title.clone());
+    open_file(state, path, &name);
+}
+
+fn open_file(state: &mut AppState, path: &Path, name: &str) {
+    if let Err(message) = check_capability(Capability::OpenFile) {
+        state.status = Some(message);
+        return;
+    }
+    let path = path.to_path_buf();
+    open_in_background(state, name, move || open_path(&path));
+}
+
+/// Hands an `open` to the IO worker; the status line says "Opening …" until it reports back.
+fn open_in_background(
+    state: &mut AppState,
+    label: &str,
+    open: impl FnOnce() -> io::Result<String> + Send + 'static,
+) {
+    state.status = Some(format!("Opening {}…", label));
+    let label = label.to_string();
+    queue_io(move || {
+        Some(AppEvent::Status(match open() {
+            Ok(opener) => format!("Opened {} with {}", label, opener),
+            Err(err) => format!("Failed to open {} ({})", label, err),
+        }))
+    });
+}
+
+const SHARE_OPTIONS: [&str; 3] = ["Tweet this", "Share to LinkedIn", "Copy for newsletter"];
+
+/// X counts every link as this many characters, however long it really is.
+const TWEET_URL_LENGTH: usize = 23;
+const TWEET_MAX_LENGTH: usize = 280;
+
+/// The selected post, if it has a page on the web to share. Gallery files and posts whose
+/// `url` is a local path have none.
+fn shareable_post<'a>(data: &'a AppData, state: &AppState) -> Option<&'a Post> {
+    selected_post(data, state)
+        .filter(|post| post.url.starts_with("https://") || post.url.starts_with("http://"))
+}
+
+fn open_share_menu(data: &AppData, state: &mut AppState) {
+    if shareable_post(data, state).is_some() {
+        state.share_menu = Some(0);
+    } else {
+        state.status = Some("This post has no public URL to share.".to_string());
+    }
+}
+
+fn open_qr(data: &AppData, state: &mut AppState) {
+    let Some(post) = shareable_post(data, state) else {
+        state.status = Some("This post has no public URL for a QR code.".to_string());
+        return;
+    };
+    let Some(code) = QrCode::encode(&post.url) else {
+        state.status = Some("This URL is too long for a QR code.".to_string());
+        return;
+    };
+    let (width, height) = qr_overlay_size(&code);
+    let (cols, rows) = terminal::size().unwrap_or((0, 0));
+    if !overlay_fits(cols, rows, width, height) {
+        state.status = Some(format!(
+            "The terminal is too small for this QR code (needs {}x{}).",
+            width + 8,
+            height + 6
+        ));
+        return;
+    }
+    state.qr = Some((post.url.clone(), code));
+}
+
+/// Keys while the `S` menu is open: move with ↑/↓ or j/k, choose with Enter or `1`-`3`.
+fn handle_share_key(key: KeyEvent, data: &AppData, state: &mut AppState) {
+    let Some(selected) = state.share_menu else {
+        return;
+    };
+    let choice = match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => None,
+        KeyCode::Up | KeyCode::Char('k') => {
+            state.share_menu = Some(selected.saturating_sub(1));
+            return;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            state.share_menu = Some((selected + 1).min(SHARE_OPTIONS.len() - 1));
+            return;
+        }
+        KeyCode::Enter => Some(selected),
+        KeyCode::Char(ch) => match ch.to_digit(10) {
+            Some(digit @ 1..=3) => Some(digit as usize - 1),
+            _ => return,
+        },
+        _ => return,
+    };
+    state.share_menu = None;
+    let (Some(choice), Some(post)) = (choice, shareable_post(data, state)) else {
+        return;
+    };
+    match choice {
+        0 => open_link(state, &tweet_intent_url(&post.title, &post.url), "tweet"),
+        1 => open_link(
+            state,
+            &format!(
+                "https://www.linkedin.com/sharing/share-offsite/?url={}",
+                percent_encode(&post.url)
+            ),
+            "LinkedIn share",
+        ),
+        _ => copy_text(state, &newsletter_quote(post), "newsletter snippet"),
+    }
+}
+
+/// An x.com compose link. The title is shortened with `…` so title, space and link fit in one
+/// tweet.
+fn tweet_intent_url(title: &str, url: &str) -> String {
+    let budget = TWEET_MAX_LENGTH - TWEET_URL_LENGTH - 1;
+    let text = if title.chars().count() > budget {
+        let mut short = title.chars().take(budget - 1).collect::<String>();
+        short.truncate(short.trim_end().len());
+        short.push('…');
+        short
+    } else {
+        title.to_string()
+    };
+    format!(
+        "https://x.com/intent/tweet?text={}&url={}",
+        percent_encode(&text),
+        percent_encode(url)
+    )
+}
+
+/// A markdown blockquote linking the post, with its frontmatter `description` or else its
+/// first paragraph.
+fn newsletter_quote(post: &Post) -> String {
+    let mut quote = format!("> **[{}]({})**", post.title, post.url);
+    let description = post
+        .fields
+        .get("description")
+        .filter(|description| !description.is_empty())
+        .cloned()
+        .or_else(|| first_paragraph(&post.body));
+    if let Some(description) = description {
+        quote.push_str("\n>\n> ");
+        quote.push_str(&description);
+    }
+    quote.push('\n');
+    quote
+}
+
+/// RFC 3986 percent-encoding of everything but unreserved characters, over UTF-8 bytes.
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// The URL `o` would open: the About link, the post's web URL, or a photo's path.
+fn selected_url(data: &AppData, state: &AppState) -> Option<String> {
+    match data.tabs.get(state.tab_index)? {
+        TabData::About(about) => visible_links(about, state)
+            .get(state.list_index)
+            .map(|link| link.url.to_string()),
+        TabData::Content(tab) => visible_posts(tab, state)
+            .get(state.list_index)
+            .map(|post| post.url.clone()),
+        TabData::Tags(tags) if state.tag_filter.is_some() => visible_posts(&tags.posts, state)
+            .get(state.list_index)
+            .map(|post| post.url.clone()),
+        TabData::Tags(_) => None,
+    }
+}
+
+fn copy_selected_url(data: &AppData, state: &mut AppState) {
+    match selected_url(data, state) {
+        Some(url) => copy_text(state, &url, &url),
+        None => state.status = Some("Nothing to copy here.".to_string()),
+    }
+}
+
+/// Copies the post's markdown as written, not the wrapped lines on screen. A gallery photo has
+/// no body, so its absolute path is copied instead.
+fn copy_selected_body(data: &AppData, state: &mut AppState) {
+    if let Some(TabData::Content(tab)) = data.tabs.get(state.tab_index) {
+        if is_gallery_tab(tab) {
+            let Some(post) = visible_posts(tab, state).get(state.list_index).copied() else {
+                state.status = Some("Nothing to copy here.".to_string());
+                return;
+            };
+            let path = post
+                .file
+                .as_ref()
+                .map_or_else(|| post.url.clone(), |path| path.display().to_string());
+            copy_text(state, &path, &path);
+            return;
+        }
+    }
+    match selected_post(data, state) {
+        Some(post) => {
+            let label = format!("{} ({} bytes)", post.title, post.body.len());
+            copy_text(state, &post.body, &label);
+        }
+        None => state.status = Some("Nothing to copy here.".to_string()),
+    }
+}
+
+/// Copies with OSC 52 when `osc52()` allows it and the text fits, so it works over SSH, and
+/// otherwise (or if writing the escape fails) with the platform tool on the IO worker.
+fn copy_text(state: &mut AppState, text: &str, label: &str) {
+    if let Err(message) = check_capability(Capability::Clipboard) {
+        state.status = Some(message);
+        return;
+    }
+    if osc52() && copy_with_osc52(text).is_ok() {
+        state.status = Some(format!("Copied {}", label));
+        return;
+    }
+    state.status = Some(format!("Copying {}…", label));
+    let (text, label) = (text.to_string(), label.to_string());
+    queue_io(move || {
+        Some(AppEvent::Status(match copy_to_clipboard(&text) {
+            Ok(()) => format!("Copied {}", label),
+            Err(err) => format!("Failed to copy {} ({})", label, err),
+        }))
+    });
+}
+
+/// Keys while a `--restricted` link confirmation is showing: only `y` opens it.
+fn handle_confirm_key(key: KeyEvent, state: &mut AppState) {
+    let Some(url) = state.confirm_url.take() else {
+        return;
+    };
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => {
+            open_in_background(state, &url.clone(), move || open_url(&url))
+        }
+        _ => state.status = Some("Link not opened.".to_string()),
+    }
+}
+
+/// Things content can make this program do beyond displaying it.
+#[derive(Clone, Copy)]
+enum Capability {
+    OpenUrl,
+    OpenFile,
+    RevealDirectory,
+    RunProgram,
+    WriteState,
+    Network,
+    Clipboard,
+}
+
+/// The single gate for `--restricted`: every call-site that opens, spawns, writes, or fetches
+/// on behalf of content asks here first. `Err` carries the message to show.
+fn check_capability(capability: Capability) -> Result<(), String> {
+    if matches!(capability, Capability::WriteState) && !restricted() {
+        return writes_allowed();
+    }
+    if !restricted() {
+        return Ok(());
+    }
+    Err(match capability {
+        Capability::OpenUrl => "Links need confirmation in restricted mode.",
+        Capability::OpenFile => "Opening files is disabled in restricted mode.",
+        Capability::RevealDirectory => "Revealing directories is disabled in restricted mode.",
+        Capability::RunProgram => "Starting other programs is disabled in restricted mode.",
+        Capability::WriteState => "Writing files is disabled in restricted mode.",
+        Capability::Network => "Network access for content is disabled in restricted mode.",
+        Capability::Clipboard => "Copying to the clipboard is disabled in restricted mode.",
+    }
+    .to_string())
+}
+
+/// Set once from `--restricted` before anything runs; there is no way to turn it off later.
+static RESTRICTED: OnceLock<bool> = OnceLock::new();
+
+fn restricted() -> bool {
+    RESTRICTED.get().copied().unwrap_or(false)
+}
+
+/// Set once from `--read-only`, `read_only = true` in the config, or `JOHNJEONG_READ_ONLY=1`.
+/// Unlike `--restricted` it only stops writes; links, files, and the clipboard still work.
+static READ_ONLY: OnceLock<bool> = OnceLock::new();
+
+fn read_only() -> bool {
+    READ_ONLY.get().copied().unwrap_or(false)
+}
+
+/// The colors the interface is drawn in: a built-in preset, with any slot overridden from the
+/// config's `[theme]` section. Diff, warning, and search-match colors keep their meaning and
+/// aren't themed.
+#[derive(Clone, Copy)]
+struct Theme {
+    /// Footnote markers.
+    accent: Color,
+    /// Secondary text: dates, hints, the status line, and the unfocused pane's heading.
+    dim: Color,
+    /// Unselected list rows.
+    text: Color,
+    /// Unselected list rows for drafts, so they can't pass for published posts.
+    draft: Color,
+    /// The focused pane's heading, the post title, and scrollbar thumbs.
+    heading: Color,
+    selection_fg: Color,
+    selection_bg: Color,
+    /// Off under `NO_COLOR` or `TERM=dumb`: no color is sent at all, and selection and search
+    /// matches show in reverse video instead.
+    color: bool,
+}
+
+/// The palette the app has always used.
+const DEFAULT_THEME: Theme = Theme {
+    accent: Color::Cyan,
+    dim: Color::DarkGrey,
+    text: Color::White,
+    draft: Color::Yellow,
+    heading: Color::White,
+    selection_fg: Color::Black,
+    selection_bg: Color::White,
+    color: true,
+};
+
+/// For light backgrounds, where the dark palette's grey and white text wash out. The selection
+/// bar is white on blue so it shows on any background.
+const LIGHT_THEME: Theme = Theme {
+    accent: Color::DarkBlue,
+    dim: Color::AnsiValue(240),
+    text: Color::Black,
+    draft: Color::DarkYellow,
+    heading: Color::Black,
+    selection_fg: Color::White,
+    selection_bg: Color::DarkBlue,
+    color: true,
+};
+
+/// Greys only, for anyone who finds the accent distracting.
+const MONO_THEME: Theme = Theme {
+    accent: Color::White,
+    dim: Color::DarkGrey,
+    text: Color::Grey,
+    draft: Color::AnsiValue(245),
+    heading: Color::White,
+    selection_fg: Color::Black,
+    selection_bg: Color::Grey,
+    color: true,
+};
+
+impl Theme {
+    fn preset(name: &str) -> Option<Theme> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "default" | "dark" => Some(DEFAULT_THEME),
+            "light" => Some(LIGHT_THEME),
+            "mono" => Some(MONO_THEME),
+            _ => None,
+        }
+    }
+
+    /// The theme from a `[theme]` section such as `preset = "mono"` and `accent = "#d08770"`,
+    /// starting from `preset` (`--theme`) if given. With no preset named anywhere, the light one
+    /// is picked for a light terminal background and the default otherwise. Anything it can't
+    /// use keeps the preset's color and is named in the returned warning.
+    fn from_config(
+        section: Option<&HashMap<String, String>>,
+        preset: Option<&str>,
+    ) -> (Theme, Option<String>) {
+        let empty = HashMap::new();
+        let section = section.unwrap_or(&empty);
+        let mut problems = Vec::new();
+        let mut theme = match preset.or(section.get("preset").map(String::as_str)) {
+            Some(name) => Theme::preset(name).unwrap_or_else(|| {
+                problems.push(format!("preset \"{}\"", name));
+                DEFAULT_THEME
+            }),
+            None if light_background() => LIGHT_THEME,
+            None => DEFAULT_THEME,
+        };
+        let mut keys = section.keys().collect::<Vec<_>>();
+        keys.sort();
+        for key in keys {
+            if key == "preset" {
+                continue;
+            }
+            let value = &section[key];
+            match (theme.slot(key), parse_color(value)) {
+                (Some(slot), Some(color)) => *slot = color,
+                (Some(_), None) => problems.push(format!("{} = \"{}\"", key, value)),
+                (None, _) => problems.push(format!("unknown key {}", key)),
+            }
+        }
+        theme.color = color_enabled();
+        let warning = (!problems.is_empty()).then(|| {
+            format!(
+                "[theme] ignored {}; those keep the preset's colors.",
+                problems.join(", ")
+            )
+        });
+        (theme, warning)
+    }
+
+    fn slot(&mut self, name: &str) -> Option<&mut Color> {
+        match name {
+            "accent" => Some(&mut self.accent),
+            "dim" => Some(&mut self.dim),
+            "text" => Some(&mut self.text),
+            "draft" => Some(&mut self.draft),
+            "heading" => Some(&mut self.heading),
+            "selection_fg" => Some(&mut self.selection_fg),
+            "selection_bg" => Some(&mut self.selection_bg),
+            _ => None,
+        }
+    }
+}
+
+/// Whether to use color at all: not when `NO_COLOR` is set to anything, or for a dumb terminal.
+fn color_enabled() -> bool {
+    env::var("NO_COLOR").map_or(true, |value| value.is_empty())
+        && env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+/// A named ANSI color (`cyan`, `dark_grey`, `reset` for the terminal's own), or `#rrggbb` /
+/// `#rgb` for a true color.
+fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim().to_ascii_lowercase();
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits = match hex.len() {
+            3 => hex.chars().flat_map(|digit| [digit, digit]).collect(),
+            6 => hex.to_string(),
+            _ => return None,
+        };
+        let channel = |index: usize| u8::from_str_radix(digits.get(index..index + 2)?, 16).ok();
+        return Some(Color::Rgb {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    Some(match value.replace(['-', ' '], "_").as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        "grey" | "gray" => Color::Grey,
+        "dark_grey" | "dark_gray" | "darkgrey" | "darkgray" => Color::DarkGrey,
+        "dark_red" => Color::DarkRed,
+        "dark_green" => Color::DarkGreen,
+        "dark_yellow" => Color::DarkYellow,
+        "dark_blue" => Color::DarkBlue,
+        "dark_magenta" => Color::DarkMagenta,
+        "dark_cyan" => Color::DarkCyan,
+        _ => return None,
+    })
+}
+
+fn render(stdout: &mut Stdout, data: &AppData, state: &mut AppState) -> io::Result<()> {
+    let (cols, rows) = terminal::size()?;
+    let max_width = cols.saturating_sub(4) as usize;
+    // Cleared from the top down rather than `ClearType::All`, which some terminals push into
+    // scrollback when running without the alternate screen.
+    queue!(stdout, MoveTo(0, 0), Clear(ClearType::FromCursorDown))?;
+    state.hits = HitMap::default();
+    if cols < MIN_COLS || rows < MIN_ROWS {
+        return render_too_small(stdout, &state.theme, cols, rows);
+    }
+
+    let (header_title, header_subtitle) = header_text(data, state);
+    queue!(
+        stdout,
+        MoveTo(2, 1),
+        SetAttribute(Attribute::Bold),
+        Print(header_title),
+        SetAttribute(Attribute::Reset)
+    )?;
+    // Without color the badges are drawn in reverse video.
+    let badge = if state.theme.color {
+        Attribute::NoReverse
+    } else {
+        Attribute::Reverse
+    };
+    if restricted() {
+        queue!(
+            stdout,
+            MoveTo(2 + display_width(header_title) as u16 + 2, 1),
+            SetForegroundColor(Color::Black),
+            SetBackgroundColor(Color::Yellow),
+            SetAttribute(badge),
+            Print(" RESTRICTED "),
+            SetAttribute(Attribute::Reset)
+        )?;
+    } else if read_only() {
+        queue!(
+            stdout,
+            MoveTo(2 + display_width(header_title) as u16 + 2, 1),
+            SetForegroundColor(Color::Black),
+            SetBackgroundColor(Color::Cyan),
+            SetAttribute(badge),
+            Print(" READ-ONLY "),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+
+    queue!(
+        stdout,
+        MoveTo(2, 2),
+        SetForegroundColor(state.theme.dim),
+        Print(header_subtitle),
+        ResetColor
+    )?;
+
+    render_nav(stdout, data, state, 4, max_width)?;
+
+    let content_top = 6;
+    match data.tabs.get(state.tab_index) {
+        Some(TabData::About(about)) => {
+            render_about(stdout, state, about, max_width, rows, content_top)?
+        }
+        Some(TabData::Content(tab)) => render_content_tab(
+            stdout,
+            state,
+            tab,
+            &data.link_status,
+            max_width,
+            rows,
+            content_top,
+        )?,
+        Some(TabData::Tags(tags)) if state.tag_filter.is_some() => render_content_tab(
+            stdout,
+            state,
+            &tags.posts,
+            &data.link_status,
+            max_width,
+            rows,
+            content_top,
+        )?,
+        Some(TabData::Tags(tags)) => {
+            render_tags_tab(stdout, state, tags, max_width, rows, content_top)?
+        }
+        None => {}
+    }
+
+    if let Some(editor) = &state.command {
+        queue!(stdout, MoveTo(2, rows.saturating_sub(3)))?;
+        let problem = state
+            .command_checks
+            .get(&editor.text)
+            .cloned()
+            .flatten()
+            .map(|problem| format!("  {}", problem));
+        let problem_width = problem.as_deref().map_or(0, display_width);
+        print_prompt(stdout, ":", editor, max_width.saturating_sub(problem_width))?;
+        if let Some(problem) = problem {
+            queue!(
+                stdout,
+                SetForegroundColor(Color::Red),
+                Print(clamp_text(&problem, max_width)),
+                ResetColor
+            )?;
+        }
+    } else if let Some(search) = &state.search {
+        queue!(stdout, MoveTo(2, rows.saturating_sub(3)))?;
+        print_prompt(stdout, "/", &search.editor, max_width)?;
+    } else if let Some(status) = state.progress.as_ref().or(state.status.as_ref()) {
+        let text = clamp_text(status, max_width);
+        queue!(
+            stdout,
+            MoveTo(2, rows.saturating_sub(3)),
+            SetForegroundColor(state.theme.dim),
+            Print(text),
+            ResetColor
+        )?;
+    }
+
+    // Reading progress and the timer sit at the right end; the hints give up their tail to them.
+    let mut right = Vec::new();
+    if let Some(percent) = reading_progress(data, state) {
+        right.push(format!("{}%", percent));
+    }
+    if let Some(timer) = &state.timer {
+        right.push(format!("⏱ {}m", timer.minutes_left()));
+    }
+    let right = if right.is_empty() {
+        String::new()
+    } else {
+        format!(" {} ", right.join("  "))
+    };
+    let right_width = display_width(&right);
+    let hint_width = if right.is_empty() {
+        max_width
+    } else {
+        max_width.saturating_sub(right_width + 1)
+    };
+    queue!(
+        stdout,
+        MoveTo(2, rows.saturating_sub(2)),
+        SetForegroundColor(state.theme.dim),
+        Print(clamp_text(&footer_hint(data, &state.keys), hint_width)),
+        ResetColor
+    )?;
+    if !right.is_empty() {
+        queue!(
+            stdout,
+            MoveTo(
+                cols.saturating_sub(right_width as u16 + 1),
+                rows.saturating_sub(2)
+            ),
+            SetForegroundColor(state.theme.dim),
+            Print(right),
+            ResetColor
+        )?;
+    }
+
+    if state.picker.is_some() {
+        render_picker(stdout, data, state, cols, rows)?;
+    }
+    if state.tag_picker.is_some() {
+        render_tag_picker(stdout, state, cols, rows)?;
+    }
+    if state.gallery_peek.is_some() {
+        render_gallery_peek(stdout, data, state, cols, rows)?;
+    }
+    if state.link_list.is_some() {
+        render_link_list(stdout, state, cols, rows)?;
+    }
+    if let Some(selected) = state.share_menu {
+        render_share_menu(stdout, state, selected, cols, rows)?;
+    }
+    if let Some(url) = &state.confirm_url {
+        render_confirm_url(stdout, &state.theme, url, cols, rows)?;
+    }
+    if state.status_history.is_some() {
+        render_status_history(stdout, state, cols, rows)?;
+    }
+    if state.help.is_some() {
+        render_help(stdout, data, state, cols, rows)?;
+    }
+    if let Some((url, code)) = &state.qr {
+        render_qr(stdout, &state.theme, url, code, cols, rows)?;
+    }
+    if let Some(summary) = &state.timer_done {
+        render_timer_done(stdout, &state.theme, summary, cols, rows)?;
+    }
+    if let Some(step) = state.tour {
+        render_tour(stdout, step, max_width, cols, rows)?;
+    }
+
+    stdout.flush()?;
+    Ok(())
+}
+
+/// How far through the open post the view is, 0 at the top and 100 only at the very end. `None`
+/// when no post is open or it fits on screen.
+fn reading_progress(data: &AppData, state: &AppState) -> Option<usize> {
+    selected_post(data, state)?;
+    if state.content_scroll_max == 0 {
+        return None;
+    }
+    Some(state.content_scroll.min(state.content_scroll_max) * 100 / state.content_scroll_max)
+}
+
+/// The footer's key hints, naming whatever keys the keymap binds.
+fn footer_hint(data: &AppData, keys: &Keymap) -> String {
+    let mut hints = vec![
+        format!(
+            "{} {} move",
+            keys.label(Action::MoveUp),
+            keys.label(Action::MoveDown)
+        ),
+        format!("{} open", keys.label(Action::Open)),
+        format!("{} search", keys.label(Action::Search)),
+        format!(
+            "{} or {} {} tabs",
+            tab_hotkey_range(data.tabs.len()),
+            keys.label(Action::NextTab),
+            keys.label(Action::PreviousTab)
+        ),
+    ];
+    if gallery_tab_index(data).is_some() {
+        hints.push(format!("{} gallery", keys.label(Action::Gallery)));
+    }
+    hints.push(format!("{} help", keys.label(Action::Help)));
+    hints.push(format!("{} quit", keys.label(Action::Quit)));
+    hints
+        .into_iter()
+        .filter(|hint| !hint.starts_with(' '))
+        .collect::<Vec<_>>()
+        .join("  •  ")
+}
+
+/// Smallest terminal the layout fits in; below it `render` shows only a notice. Nothing in
+/// `AppState` changes meanwhile, so growing the window brings back the same view.
+const MIN_COLS: u16 = 20;
+const MIN_ROWS: u16 = 10;
+
+fn render_too_small(stdout: &mut Stdout, theme: &Theme, cols: u16, rows: u16) -> io::Result<()> {
+    let lines = [
+        "Terminal too small".to_string(),
+        format!("(need at least {}x{})", MIN_COLS, MIN_ROWS),
+    ];
+    let top = (rows / 2).saturating_sub(1);
+    for (offset, line) in lines.iter().enumerate() {
+        let text = clamp_text(line, cols as usize);
+        let x = (cols as usize).saturating_sub(display_width(&text)) / 2;
+        queue!(
+            stdout,
+            MoveTo(x as u16, top + offset as u16),
+            SetForegroundColor(theme.dim),
+            Print(text),
+            ResetColor
+        )?;
+    }
+    stdout.flush()
+}
+
+fn render_picker(
+    stdout: &mut Stdout,
+    data: &AppData,
+    state: &mut AppState,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let Some(picker) = state.picker.as_mut() else {
+        return Ok(());
+    };
+    let Some((x, y, width, height)) = clear_overlay(stdout, cols, rows, 80, 20)? else {
+        return Ok(());
+    };
+
+    queue!(stdout, MoveTo(x + 1, y), SetAttribute(Attribute::Bold))?;
+    print_prompt(stdout, "> ", &picker.query, width - 2)?;
+    queue!(stdout, SetAttribute(Attribute::Reset))?;
+
+    let results = picker_results(data, &picker.query.text, state.show_drafts);
+    let list_height = height - 2;
+    picker.scroll = clamp_scroll(picker.scroll, picker.selected, list_height, results.len());
+    if results.is_empty() {
+        queue!(
+            stdout,
+            MoveTo(x + 1, y + 2),
+            SetForegroundColor(state.theme.dim),
+            Print("No matches."),
+            ResetColor
+        )?;
+    }
+
+    for (row, &(_, tab_index, post_index)) in results
+        .iter()
+        .enumerate()
+        .skip(picker.scroll)
+        .take(list_height)
+    {
+        let Some(TabData::Content(tab)) = data.tabs.get(tab_index) else {
+            continue;
+        };
+        let post = &tab.posts[post_index];
+        let date = if post.date.is_empty() {
+            String::new()
+        } else {
+            format!("{} ", format_date(&post.date))
+        };
+        let label = clamp_text(&format!("{}{} · {}", date, post.title, tab.name), width - 4);
+        let is_selected = row == picker.selected;
+        queue!(stdout, MoveTo(x + 1, y + 2 + (row - picker.scroll) as u16))?;
+        if is_selected {
+            queue_selection(stdout, &state.theme, state.high_contrast)?;
+        }
+        queue!(
+            stdout,
+            Print(format!("{} {}", if is_selected { "›" } else { " " }, label)),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+
+    Ok(())
+}
+
+fn render_gallery_peek(
+    stdout: &mut Stdout,
+    data: &AppData,
+    state: &mut AppState,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let Some(peek) = state.gallery_peek.as_mut() else {
+        return Ok(());
+    };
+    let Some((x, y, width, height)) = clear_overlay(stdout, cols, rows, 110, 30)? else {
+        return Ok(());
+    };
+
+    let list_width = (width / 3).clamp(10, 36);
+    queue!(stdout, MoveTo(x + 1, y), SetAttribute(Attribute::Bold))?;
+    print_prompt(stdout, "Gallery > ", &peek.query, width - 2)?;
+    queue!(stdout, SetAttribute(Attribute::Reset))?;
+
+    let results = gallery_results(data, &peek.query.text);
+    let list_height = height - 2;
+    peek.scroll = clamp_scroll(peek.scroll, peek.selected, list_height, results.len());
+    if results.is_empty() {
+        queue!(
+            stdout,
+            MoveTo(x + 1, y + 2),
+            SetForegroundColor(state.theme.dim),
+            Print("No matches."),
+            ResetColor
+        )?;
+    }
+
+    for (row, post) in results
+        .iter()
+        .enumerate()
+        .skip(peek.scroll)
+        .take(list_height)
+    {
+        let is_selected = row == peek.selected;
+        queue!(stdout, MoveTo(x + 1, y + 2 + (row - peek.scroll) as u16))?;
+        if is_selected {
+            queue_selection(stdout, &state.theme, state.high_contrast)?;
+        }
+        queue!(
+            stdout,
+            Print(format!(
+                "{} {}",
+                if is_selected { "›" } else { " " },
+                clamp_text(&post.title, list_width - 2)
+            )),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+
+    let preview_x = x + list_width as u16 + 3;
+    let preview_width = width.saturating_sub(list_width + 4);
+    let selected = results.get(peek.selected).copied();
+    if let Some(post) = selected {
+        render_gallery_preview(
+            stdout,
+            state,
+            post,
+            preview_x,
+            y + 2,
+            preview_width,
+            y + height as u16,
+        )?;
+    }
+    Ok(())
+}
+
+/// Shows the whole URL, wrapped rather than truncated, so a misleading tail can't hide.
+fn render_confirm_url(
+    stdout: &mut Stdout,
+    theme: &Theme,
+    url: &str,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let chars = url.chars().collect::<Vec<_>>();
+    let expected_width = cols.saturating_sub(8).min(80) as usize;
+    let url_rows = chars.len() / expected_width.saturating_sub(2).max(1) + 1;
+    let Some((x, y, width, height)) = clear_overlay(stdout, cols, rows, 80, url_rows as u16 + 4)?
+    else {
+        return Ok(());
+    };
+    queue!(
+        stdout,
+        MoveTo(x + 1, y),
+        SetAttribute(Attribute::Bold),
+        Print("Open this link?"),
+        SetAttribute(Attribute::Reset)
+    )?;
+    let line_width = width - 2;
+    let chunks = chars.chunks(line_width).take(height.saturating_sub(4));
+    for (row, chunk) in (y + 2..).zip(chunks) {
+        queue!(
+            stdout,
+            MoveTo(x + 1, row),
+            Print(chunk.iter().collect::<String>())
+        )?;
+    }
+    queue!(
+        stdout,
+        MoveTo(x + 1, y + height as u16 - 1),
+        SetForegroundColor(theme.dim),
+        Print(clamp_text("y open  •  any other key cancels", line_width)),
+        ResetColor
+    )?;
+    Ok(())
+}
+
+/// Prints `prefix` and the editor's text with the cursor cell in reverse video, scrolling the
+/// text left when the cursor would fall past `width`.
+fn print_prompt(
+    stdout: &mut Stdout,
+    prefix: &str,
+    editor: &LineEditor,
+    width: usize,
+) -> io::Result<()> {
+    let budget = width.saturating_sub(prefix.chars().count() + 1);
+    let before = editor.text[..editor.cursor].chars().collect::<Vec<_>>();
+    let before = before[before.len().saturating_sub(budget)..]
+        .iter()
+        .collect::<String>();
+    let mut rest = editor.text[editor.cursor..].chars();
+    let under = rest.next().unwrap_or(' ');
+    let after = rest
+        .take(budget - before.chars().count())
+        .collect::<String>();
+    queue!(
+        stdout,
+        Print(prefix),
+        Print(before),
+        SetAttribute(Attribute::Reverse),
+        Print(under),
+        SetAttribute(Attribute::NoReverse),
+        Print(after)
+    )
+}
+
+enum TourAnchor {
+    Nav,
+    List,
+    Content,
+}
+
+const TOUR: [(TourAnchor, &str); 3] = [
+    (
+        TourAnchor::Nav,
+        "number keys or tab switch tabs, g jumps to the gallery",
+    ),
+    (TourAnchor::List, "j/k or ↑/↓ move through the list"),
+    (
+        TourAnchor::Content,
+        "o or enter opens the post in your browser",
+    ),
+];
+
+fn tour_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("tour"))
+}
+
+/// Closes the tour, finished or skipped, and records it so the first-run check passes.
+fn finish_tour(state: &mut AppState) {
+    state.tour = None;
+    if check_capability(Capability::WriteState).is_err() {
+        return;
+    }
+    queue_io(|| {
+        let path = tour_path()?;
+        if let Some(parent) = path.parent() {
+            let _ = create_dirs(parent);
+        }
+        let _ = write_file(&path, b"done\n");
+        None
+    });
+}
+
+/// Draws step `step` as a callout next to the pane it describes, with an arrow pointing at it.
+/// The callout sits below its anchor, or above when that would run into the footer, and
+/// shifts left to stay on screen.
+fn render_tour(
+    stdout: &mut Stdout,
+    step: usize,
+    max_width: usize,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let Some((anchor, text)) = TOUR.get(step) else {
+        return Ok(());
+    };
+    let content_top = 6;
+    let (anchor_x, anchor_y) = match anchor {
+        TourAnchor::Nav => (4, 4),
+        TourAnchor::List => (6, content_top + 1),
+        TourAnchor::Content => ((2 + list_pane_width(max_width) + 4) as u16, content_top),
+    };
+    let hint = format!("{}/{}  any key: next  esc: skip", step + 1, TOUR.len());
+    let inner = display_width(text).max(display_width(&hint));
+    let width = (inner + 4).min(cols.saturating_sub(2) as usize);
+    let height = 4;
+    if width < 10 {
+        return Ok(());
+    }
+    let x = (anchor_x as usize).min(cols as usize - width - 1) as u16;
+    let below = anchor_y + 2 + height <= rows.saturating_sub(3);
+    let (arrow, arrow_y, y) = if below {
+        ("▲", anchor_y + 1, anchor_y + 2)
+    } else {
+        (
+            "▼",
+            anchor_y.saturating_sub(1),
+            anchor_y.saturating_sub(1 + height),
+        )
+    };
+
+    let blank = " ".repeat(width);
+    queue!(stdout, SetAttribute(Attribute::Reverse))?;
+    for row in 0..height {
+        queue!(stdout, MoveTo(x, y + row), Print(&blank))?;
+    }
+    queue!(
+        stdout,
+        MoveTo(x + 2, y + 1),
+        SetAttribute(Attribute::Bold),
+        Print(clamp_text(text, width - 4)),
+        SetAttribute(Attribute::NormalIntensity),
+        MoveTo(x + 2, y + 2),
+        Print(clamp_text(&hint, width - 4)),
+        SetAttribute(Attribute::Reset),
+        MoveTo(anchor_x.min(cols.saturating_sub(1)), arrow_y),
+        SetAttribute(Attribute::Bold),
+        Print(arrow),
+        SetAttribute(Attribute::Reset)
+    )?;
+    Ok(())
+}
+
+/// Width of the post list in content tabs, shared with anything that points at the panes.
+fn list_pane_width(max_width: usize) -> usize {
+    (((max_width as f32) * 0.33) as usize).clamp(24, 38)
+}
+
+fn render_timer_done(
+    stdout: &mut Stdout,
+    theme: &Theme,
+    summary: &str,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let width = display_width(summary) as u16 + 4;
+    let Some((x, y, width, _)) = clear_overlay(stdout, cols, rows, width, 4)? else {
+        return Ok(());
+    };
+    queue!(
+        stdout,
+        MoveTo(x + 2, y + 1),
+        Print(clamp_text(summary, width - 4)),
+        MoveTo(x + 2, y + 2),
+        SetForegroundColor(theme.dim),
+        Print(clamp_text("any key to continue", width - 4)),
+        ResetColor
+    )?;
+    Ok(())
+}
+
+/// A QR code for the `Q` overlay, in byte mode at error correction level M. Versions stop at
+/// 10 (57 modules across, about 210 bytes of URL), which is already more than most terminals
+/// have room for.
+#[derive(Clone)]
+struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    /// Finder, timing, alignment, format, and version modules, which masks leave alone.
+    function: Vec<bool>,
+}
+
+/// `(error correction codewords per block, blocks)` at level M for versions 1 to 10.
+const QR_BLOCKS: [(usize, usize); 10] = [
+    (10, 1),
+    (16, 1),
+    (26, 1),
+    (18, 2),
+    (24, 2),
+    (16, 4),
+    (18, 4),
+    (22, 4),
+    (22, 5),
+    (26, 5),
+];
+
+impl QrCode {
+    /// The smallest version that holds `text`, with the mask that scores best. `None` if it
+    /// doesn't fit in version 10.
+    fn encode(text: &str) -> Option<QrCode> {
+        let bytes = text.as_bytes();
+        let count_bits = |version: usize| if version < 10 { 8 } else { 16 };
+        let version = (1..=QR_BLOCKS.len()).find(|&version| {
+            4 + count_bits(version) + bytes.len() * 8 <= qr_data_codewords(version) * 8
+        })?;
+
+        let capacity = qr_data_codewords(version) * 8;
+        let mut bits = Vec::with_capacity(capacity);
+        let mut push = |value: u32, count: usize| {
+            bits.extend((0..count).rev().map(|index| value >> index & 1 == 1));
+        };
+        push(0b0100, 4);
+        push(bytes.len() as u32, count_bits(version));
+        for byte in bytes {
+            push(*byte as u32, 8);
+        }
+        let terminator = (capacity - bits.len()).min(4);
+        bits.extend(std::iter::repeat_n(false, terminator));
+        bits.resize(bits.len().div_ceil(8) * 8, false);
+        let mut data: Vec<u8> = bits
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0, |value, &bit| value << 1 | bit as u8))
+            .collect();
+        for pad in [0xEC, 0x11]
+            .into_iter()
+            .cycle()
+            .take(capacity / 8 - data.len())
+        {
+            data.push(pad);
+        }
+
+        let size = version * 4 + 17;
+        let mut code = QrCode {
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+        code.draw_function_patterns(version);
+        code.draw_codewords(&qr_add_error_correction(version, &data));
+        let mask = (0..8).min_by_key(|&mask| {
+            let mut candidate = code.clone();
+            candidate.apply_mask(mask);
+            candidate.draw_format(mask);
+            candidate.penalty()
+        })?;
+        code.apply_mask(mask);
+        code.draw_format(mask);
+        Some(code)
+    }
+
+    fn module(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for index in 0..size {
+            self.set_function(6, index, index % 2 == 0);
+            self.set_function(index, 6, index % 2 == 0);
+        }
+        // Finders, with their light separators where they fit.
+        for (center_x, center_y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (center_x as i32 + dx, center_y as i32 + dy);
+                    if (0..size as i32).contains(&x) && (0..size as i32).contains(&y) {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+        let positions = qr_alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (row, &center_y) in positions.iter().enumerate() {
+            for (column, &center_x) in positions.iter().enumerate() {
+                if (row, column) == (0, 0)
+                    || (row == 0 && column == last)
+                    || (row == last && column == 0)
+                {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let (x, y) = (center_x as i32 + dx, center_y as i32 + dy);
+                        self.set_function(x as usize, y as usize, dx.abs().max(dy.abs()) != 1);
+                    }
+                }
+            }
+        }
+        // Reserves the format modules; they're drawn for real once the mask is picked.
+        self.draw_format(0);
+        if version >= 7 {
+            let mut remainder = version as u32;
+            for _ in 0..12 {
+                remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+            }
+            let bits = (version as u32) << 12 | remainder;
+            for index in 0..18 {
+                let dark = bits >> index & 1 == 1;
+                let (a, b) = (size - 11 + index % 3, index / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    /// Both copies of the level and mask, BCH-protected, plus the always-dark module.
+    fn draw_format(&mut self, mask: u32) {
+        // Level M is 0b00, so the data is just the mask.
+        let mut remainder = mask;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (mask << 10 | remainder) ^ 0x5412;
+        let bit = |index: usize| bits >> index & 1 == 1;
+        let size = self.size;
+        for index in 0..6 {
+            self.set_function(8, index, bit(index));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for index in 9..15 {
+            self.set_function(14 - index, 8, bit(index));
+        }
+        for index in 0..8 {
+            self.set_function(size - 1 - index, 8, bit(index));
+        }
+        for index in 8..15 {
+            self.set_function(8, size - 15 + index, bit(index));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Fills the non-function modules in the zigzag of two-wide columns, right to left,
+    /// skipping the vertical timing column.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut index = 0;
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vertical in 0..size {
+                let y = if upward {
+                    size - 1 - vertical
+                } else {
+                    vertical
+                };
+                for x in [right, right - 1] {
+                    if !self.function[y * size + x] && index < codewords.len() * 8 {
+                        self.modules[y * size + x] =
+                            codewords[index / 8] >> (7 - index % 8) & 1 == 1;
+                        index += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * self.size + x;
+                if invert && !self.function[index] {
+                    self.modules[index] = !self.modules[index];
+                }
+            }
+        }
+    }
+
+    /// The spec's mask penalty without the finder-lookalike rule: long runs, 2×2 blocks, and
+    /// how far the dark share strays from half. Good enough to avoid the ugliest masks.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        for line in 0..size {
+            for horizontal in [true, false] {
+                let mut run = 0;
+                let mut previous = None;
+                for index in 0..size {
+                    let dark = if horizontal {
+                        self.module(index, line)
+                    } else {
+                        self.module(line, index)
+                    };
+                    if previous == Some(dark) {
+                        run += 1;
+                    } else {
+                        run = 1;
+                        previous = Some(dark);
+                    }
+                    penalty += match run {
+                        5 => 3,
+                        6.. => 1,
+                        _ => 0,
+                    };
+                }
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.module(x, y);
+                if dark == self.module(x + 1, y)
+                    && dark == self.module(x, y + 1)
+                    && dark == self.module(x + 1, y + 1)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&dark| dark).count();
+        penalty
+            + (dark * 20)
+                .abs_diff(total * 10)
+                .div_ceil(total)
+                .saturating_sub(1)
+                * 10
+    }
+}
+
+/// Modules left for data and error correction once the function patterns are placed.
+fn qr_raw_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        modules -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules
+}
+
+fn qr_data_codewords(version: usize) -> usize {
+    let (ecc_len, blocks) = QR_BLOCKS[version - 1];
+    qr_raw_modules(version) / 8 - ecc_len * blocks
+}
+
+fn qr_alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = (version * 8 + count * 3 + 5) / (count * 4 - 4) * 2;
+    let size = version * 4 + 17;
+    let mut positions: Vec<usize> = (0..count - 1)
+        .map(|index| size - 7 - index * step)
+        .collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+/// Splits `data` into blocks, appends each block's Reed-Solomon codewords, and interleaves
+/// them. Later blocks are one data codeword longer when the count doesn't divide evenly.
+fn qr_add_error_correction(version: usize, data: &[u8]) -> Vec<u8> {
+    let (ecc_len, blocks) = QR_BLOCKS[version - 1];
+    let raw = qr_raw_modules(version) / 8;
+    let short_blocks = blocks - raw % blocks;
+    let short_len = raw / blocks;
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut all = Vec::with_capacity(blocks);
+    let mut start = 0;
+    for index in 0..blocks {
+        let data_len = short_len - ecc_len + usize::from(index >= short_blocks);
+        let mut block = data[start..start + data_len].to_vec();
+        start += data_len;
+        let ecc = reed_solomon_remainder(&block, &divisor);
+        // A placeholder so every block has the same length; skipped when interleaving.
+        if index < short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc);
+        all.push(block);
+    }
+    let mut result = Vec::with_capacity(raw);
+    for position in 0..=short_len {
+        for (index, block) in all.iter().enumerate() {
+            if position != short_len - ecc_len || index >= short_blocks {
+                result.push(block[position]);
+            }
+        }
+    }
+    result
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for index in 0..degree {
+            result[index] = gf_multiply(result[index], root);
+            if index + 1 < degree {
+                result[index] ^= result[index + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (value, coefficient) in result.iter_mut().zip(divisor) {
+            *value ^= gf_multiply(*coefficient, factor);
+        }
+    }
+    result
+}
+
+/// Multiplication in GF(2^8) modulo the QR polynomial x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut product: u32 = 0;
+    for bit in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x11D);
+        product ^= (y as u32 >> bit & 1) * x as u32;
+    }
+    product as u8
+}
+
+/// Modules left light around the code. The spec asks for four; two scan fine and save room.
+const QR_QUIET_ZONE: usize = 2;
+
+/// The `Q` overlay's box: the code at two modules per cell, a blank line, and the URL.
+fn qr_overlay_size(code: &QrCode) -> (u16, u16) {
+    let modules = code.size + 2 * QR_QUIET_ZONE;
+    (modules as u16 + 2, modules.div_ceil(2) as u16 + 3)
+}
+
+/// Draws the code with half blocks, black on white so it scans whatever the terminal's colors.
+/// If a resize made it too big, says so instead of drawing a clipped code that won't scan.
+fn render_qr(
+    stdout: &mut Stdout,
+    theme: &Theme,
+    url: &str,
+    code: &QrCode,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let (width, height) = qr_overlay_size(code);
+    if !overlay_fits(cols, rows, width, height) {
+        return render_timer_done(
+            stdout,
+            theme,
+            "The terminal is too small for this QR code.",
+            cols,
+            rows,
+        );
+    }
+    let Some((x, y, width, height)) = clear_overlay(stdout, cols, rows, width, height)? else {
+        return Ok(());
+    };
+    let modules = code.size + 2 * QR_QUIET_ZONE;
+    let dark = |column: usize, row: usize| {
+        let (column, row) = (
+            column.wrapping_sub(QR_QUIET_ZONE),
+            row.wrapping_sub(QR_QUIET_ZONE),
+        );
+        column < code.size && row < code.size && code.module(column, row)
+    };
+    for line in 0..modules.div_ceil(2) {
+        let text: String = (0..modules)
+            .map(
+                |column| match (dark(column, line * 2), dark(column, line * 2 + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                },
+            )
+            .collect();
+        queue!(
+            stdout,
+            MoveTo(x + 1, y + line as u16),
+            SetForegroundColor(Color::Black),
+            SetBackgroundColor(Color::White),
+            Print(text),
+            ResetColor
+        )?;
+    }
+    queue!(
+        stdout,
+        MoveTo(x + 1, y + height as u16 - 1),
+        SetForegroundColor(theme.dim),
+        Print(clamp_text(url, width - 2)),
+        ResetColor
+    )?;
+    Ok(())
+}
+
+fn render_link_list(
+    stdout: &mut Stdout,
+    state: &mut AppState,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let (theme, high_contrast) = (state.theme, state.high_contrast);
+    let Some(list) = state.link_list.as_mut() else {
+        return Ok(());
+    };
+    let height = list.links.len() as u16 + 2;
+    let Some((x, y, width, height)) = clear_overlay(stdout, cols, rows, 80, height)? else {
+        return Ok(());
+    };
+    queue!(
+        stdout,
+        MoveTo(x + 1, y),
+        SetAttribute(Attribute::Bold),
+        Print("Links in this post"),
+        SetAttribute(Attribute::Reset)
+    )?;
+
+    let list_height = height - 2;
+    list.scroll = clamp_scroll(list.scroll, list.selected, list_height, list.links.len());
+    for (row, (label, target)) in list
+        .links
+        .iter()
+        .enumerate()
+        .skip(list.scroll)
+        .take(list_height)
+    {
+        let is_selected = row == list.selected;
+        let target = target.display();
+        let text = if *label == target {
+            target
+        } else {
+            format!("{} · {}", label, target)
+        };
+        queue!(stdout, MoveTo(x + 1, y + 2 + (row - list.scroll) as u16))?;
+        if is_selected {
+            queue_selection(stdout, &theme, high_contrast)?;
+        }
+        queue!(
+            stdout,
+            Print(format!(
+                "{} {}",
+                if is_selected { "›" } else { " " },
+                clamp_text(&text, width - 4)
+            )),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+    Ok(())
+}
+
+/// The `m` overlay: logged status messages, newest first, each wrapped under its age.
+fn render_status_history(
+    stdout: &mut Stdout,
+    state: &mut AppState,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let Some((x, y, width, height)) = clear_overlay(stdout, cols, rows, 80, rows)? else {
+        return Ok(());
+    };
+    queue!(
+        stdout,
+        MoveTo(x + 1, y),
+        SetAttribute(Attribute::Bold),
+        Print("Messages"),
+        SetAttribute(Attribute::Reset)
+    )?;
+
+    let text_width = width - 2;
+    let mut lines = Vec::new();
+    for (at, message) in state.status_log.iter().rev() {
+        let age = format!("{:<6}", message_age(at.elapsed()));
+        let mut wrapped = wrap_line(&[Span::new(message, SpanStyle::Plain)], text_width, &age);
+        if let Some(first) = wrapped[0].spans.first_mut() {
+            first.style = SpanStyle::Dim;
+        }
+        lines.extend(wrapped);
+    }
+    if lines.is_empty() {
+        lines.push(StyledLine {
+            spans: vec![Span::new("No messages yet.", SpanStyle::Dim)],
+            kind: LineKind::Body,
+        });
+    }
+
+    let list_height = height - 2;
+    let scroll = state
+        .status_history
+        .unwrap_or(0)
+        .min(lines.len().saturating_sub(list_height));
+    state.status_history = Some(scroll);
+    for (row, line) in lines.iter().skip(scroll).take(list_height).enumerate() {
+        queue!(stdout, MoveTo(x + 1, y + 2 + row as u16))?;
+        print_styled_line(stdout, &state.theme, line, text_width)?;
+    }
+    Ok(())
+}
+
+/// The `?` overlay: `key_bindings` by category, with descriptions wrapped beside their keys.
+fn render_help(
+    stdout: &mut Stdout,
+    data: &AppData,
+    state: &mut AppState,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let Some((x, y, width, height)) = clear_overlay(stdout, cols, rows, 72, rows)? else {
+        return Ok(());
+    };
+    queue!(
+        stdout,
+        MoveTo(x + 1, y),
+        SetAttribute(Attribute::Bold),
+        Print("Keys"),
+        SetAttribute(Attribute::Reset)
+    )?;
+
+    let text_width = width - 2;
+    let bindings = key_bindings(&state.keys, data.tabs.len());
+    let key_width = bindings
+        .iter()
+        .flat_map(|(_, keys)| keys.iter().map(|(key, _)| display_width(key)))
+        .max()
+        .unwrap_or(0);
+    let mut lines = Vec::new();
+    for (category, keys) in &bindings {
+        if !lines.is_empty() {
+            lines.push(StyledLine::default());
+        }
+        lines.push(StyledLine {
+            spans: vec![Span::new(*category, SpanStyle::Plain)],
+            kind: LineKind::Heading(2),
+        });
+        for (key, description) in keys {
+            let prefix = format!("  {:<width$}  ", key, width = key_width);
+            lines.extend(wrap_line(
+                &[Span::new(description, SpanStyle::Plain)],
+                text_width,
+                &prefix,
+            ));
+        }
+    }
+
+    let list_height = height - 2;
+    let scroll = state
+        .help
+        .unwrap_or(0)
+        .min(lines.len().saturating_sub(list_height));
+    state.help = Some(scroll);
+    for (row, line) in lines.iter().skip(scroll).take(list_height).enumerate() {
+        queue!(stdout, MoveTo(x + 1, y + 2 + row as u16))?;
+        print_styled_line(stdout, &state.theme, line, text_width)?;
+    }
+    Ok(())
+}
+
+/// A short age for the `m` overlay: `now`, `42s`, `5m`, `2h`.
+fn message_age(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        0..=4 => "now".to_string(),
+        secs @ 5..=59 => format!("{}s", secs),
+        secs @ 60..=3599 => format!("{}m", secs / 60),
+        secs => format!("{}h", secs / 3600),
+    }
+}
+
+fn render_share_menu(
+    stdout: &mut Stdout,
+    state: &AppState,
+    selected: usize,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let height = SHARE_OPTIONS.len() as u16 + 2;
+    let Some((x, y, width, height)) = clear_overlay(stdout, cols, rows, 36, height)? else {
+        return Ok(());
+    };
+    queue!(
+        stdout,
+        MoveTo(x + 1, y),
+        SetAttribute(Attribute::Bold),
+        Print("Share"),
+        SetAttribute(Attribute::Reset)
+    )?;
+    for (row, option) in SHARE_OPTIONS.iter().enumerate().take(height - 2) {
+        let is_selected = row == selected;
+        queue!(stdout, MoveTo(x + 1, y + 2 + row as u16))?;
+        if is_selected {
+            queue_selection(stdout, &state.theme, state.high_contrast)?;
+        }
+        let label = format!(
+            "{} {} {}",
+            if is_selected { "›" } else { " " },
+            row + 1,
+            option
+        );
+        queue!(
+            stdout,
+            Print(clamp_text(&label, width - 2)),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+    Ok(())
+}
+
+fn render_tag_picker(
+    stdout: &mut Stdout,
+    state: &mut AppState,
+    cols: u16,
+    rows: u16,
+) -> io::Result<()> {
+    let Some(picker) = state.tag_picker.as_mut() else {
+        return Ok(());
+    };
+    let Some((x, y, width, height)) = clear_overlay(stdout, cols, rows, 40, 16)? else {
+        return Ok(());
+    };
+    queue!(
+        stdout,
+        MoveTo(x + 1, y),
+        SetAttribute(Attribute::Bold),
+        Print("Filter by tag"),
+        SetAttribute(Attribute::Reset)
+    )?;
+
+    let rows_total = picker.tags.len() + 1;
+    let list_height = height - 2;
+    picker.scroll = clamp_scroll(picker.scroll, picker.selected, list_height, rows_total);
+    for row in (picker.scroll..rows_total).take(list_height) {
+        let label = match row.checked_sub(1).and_then(|index| picker.tags.get(index)) {
+            Some((tag, count)) => format!("#{} ({})", tag, count),
+            None => "All posts".to_string(),
+        };
+        let is_selected = row == picker.selected;
+        queue!(stdout, MoveTo(x + 1, y + 2 + (row - picker.scroll) as u16))?;
+        if is_selected {
+            queue_selection(stdout, &state.theme, state.high_contrast)?;
+        }
+        queue!(
+            stdout,
+            Print(format!(
+                "{} {}",
+                if is_selected { "›" } else { " " },
+                clamp_text(&label, width - 4)
+            )),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+    Ok(())
+}
+
+/// Whether `clear_overlay` can give a box of `width` by `height` without shrinking it.
+fn overlay_fits(cols: u16, rows: u16, width: u16, height: u16) -> bool {
+    width <= cols.saturating_sub(8) && height <= rows.saturating_sub(6)
+}
+
+/// Blanks a centered box of at most `max_width` x `max_height` cells for an overlay and returns
+/// its `(x, y, width, height)`, or `None` when the terminal is too small to draw one.
+fn clear_overlay(
+    stdout: &mut Stdout,
+    cols: u16,
+    rows: u16,
+    max_width: u16,
+    max_height: u16,
+) -> io::Result<Option<(u16, u16, usize, usize)>> {
+    let width = cols.saturating_sub(8).min(max_width) as usize;
+    let height = rows.saturating_sub(6).min(max_height) as usize;
+    if width < 10 || height < 3 {
+        return Ok(None);
+    }
+    let x = (cols as usize - width) as u16 / 2;
+    let y = (rows as usize - height) as u16 / 2;
+    let blank = " ".repeat(width);
+    for row in 0..height as u16 {
+        queue!(stdout, MoveTo(x, y + row), Print(&blank))?;
+    }
+    Ok(Some((x, y, width, height)))
+}
+
+/// Colors the selected list row. High-contrast mode and color-less terminals use bold reverse
+/// video instead, which stays visible whatever the palette. Rows end with `Attribute::Reset`.
+fn queue_selection(stdout: &mut impl Write, theme: &Theme, high_contrast: bool) -> io::Result<()> {
+    if high_contrast || !theme.color {
+        queue!(
+            stdout,
+            SetAttribute(Attribute::Bold),
+            SetAttribute(Attribute::Reverse)
+        )
+    } else {
+        queue!(
+            stdout,
+            SetForegroundColor(theme.selection_fg),
+            SetBackgroundColor(theme.selection_bg)
+        )
+    }
+}
+
+/// A pane's heading, bracketed in high-contrast mode when the pane has focus.
+fn pane_title(heading: &str, state: &AppState, focused: bool) -> String {
+    if state.high_contrast && focused {
+        format!("[ {} ]", heading)
+    } else {
+        heading.to_string()
+    }
+}
+
+/// The tab bar. When the labels don't all fit in `max_width`, it starts late enough that the
+/// active tab is on screen and marks the hidden ends with `…`.
+fn render_nav(
+    stdout: &mut impl Write,
+    data: &AppData,
+    state: &mut AppState,
+    y: u16,
+    max_width: usize,
+) -> io::Result<()> {
+    state.hits.tab_row = y;
+    const GAP: usize = 3;
+    let labels = data
+        .tabs
+        .iter()
+        .enumerate()
+        .map(|(idx, tab)| tab_label(idx, tab))
+        .collect::<Vec<_>>();
+    let span = |from: usize, to: usize| {
+        labels[from..=to]
+            .iter()
+            .map(|label| display_width(label) + GAP)
+            .sum::<usize>()
+    };
+    let mut first = 0;
+    let active = state.tab_index.min(labels.len().saturating_sub(1));
+    while first < active && span(first, active) + GAP > max_width {
+        first += 1;
+    }
+
+    let mut x = 2;
+    if first > 0 {
+        queue!(stdout, MoveTo(x, y), Print("…"))?;
+        x += GAP as u16;
+    }
+    for (idx, label) in labels.iter().enumerate().skip(first) {
+        let end = x as usize + display_width(label);
+        let more = idx + 1 < labels.len();
+        if end + if more { GAP } else { 0 } > max_width + 2 && idx > active {
+            queue!(stdout, MoveTo(x, y), Print("…"))?;
+            break;
+        }
+        let label = label.clone();
+        state
+            .hits
+            .tabs
+            .push((x, x + display_width(&label) as u16, idx));
+        queue!(stdout, MoveTo(x, y))?;
+        if idx == state.tab_index && state.high_contrast {
+            queue!(
+                stdout,
+                SetAttribute(Attribute::Bold),
+                SetAttribute(Attribute::Reverse),
+                Print(&label),
+                SetAttribute(Attribute::Reset)
+            )?;
+        } else if idx == state.tab_index {
+            queue!(
+                stdout,
+                SetAttribute(Attribute::Underlined),
+                Print(&label),
+                SetAttribute(Attribute::Reset)
+            )?;
+        } else {
+            queue!(stdout, Print(&label))?;
+        }
+        x += (display_width(&label) + GAP) as u16;
+    }
+    Ok(())
+}
+fn render_about(
+    stdout: &mut Stdout,
+    state: &mut AppState,
+    about: &AboutData,
+    max_width: usize,
+    rows: u16,
+    content_top: u16,
+) -> io::Result<()> {
+    let tagline = clamp_text(about.tagline, max_width);
+    queue!(stdout, MoveTo(2, content_top), Print(tagline))?;
+
+    let list_y = content_top + 2;
+    let list_height = rows.saturating_sub(list_y + 3) as usize;
+    let links = visible_links(about, state);
+    state.list_scroll = clamp_scroll(
+        state.list_scroll,
+        state.list_index,
+        list_height,
+        links.len(),
+    );
+
+    if links.is_empty() {
+        queue!(
+            stdout,
+            MoveTo(4, list_y),
+            SetForegroundColor(state.theme.dim),
+            Print("No links match."),
+            ResetColor
+        )?;
+    }
+
+    for (idx, link) in links
+        .iter()
+        .enumerate()
+        .skip(state.list_scroll)
+        .take(list_height)
+    {
+        let is_selected = idx == state.list_index;
+        let y = list_y + (idx - state.list_scroll) as u16;
+        queue!(stdout, MoveTo(4, y))?;
+        if is_selected {
+            queue_selection(stdout, &state.theme, state.high_contrast)?;
+        } else {
+            queue!(stdout, SetForegroundColor(state.theme.text))?;
+        }
+        queue!(
+            stdout,
+            Print(hyperlink(
+                &list_row(link.label, max_width.saturating_sub(4), is_selected),
+                link.url
+            )),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+    state.hits.list = Some(ListArea::new(
+        4,
+        max_width.saturating_sub(4),
+        list_y,
+        list_height,
+        state.list_scroll,
+        links.len(),
+    ));
+
+    Ok(())
+}
+
+fn render_tags_tab(
+    stdout: &mut Stdout,
+    state: &mut AppState,
+    tags: &TagsTab,
+    max_width: usize,
+    rows: u16,
+    content_top: u16,
+) -> io::Result<()> {
+    let list_x = 2;
+    let list_y = content_top + 1;
+    let list_width = list_pane_width(max_width);
+    let list_height = rows.saturating_sub(list_y + 3) as usize;
+    let visible = visible_tags(tags, state);
+    state.content_scroll = 0;
+    state.content_scroll_max = 0;
+    state.footnote_jumps.clear();
+    state.match_lines.clear();
+
+    state.list_scroll = clamp_scroll(
+        state.list_scroll,
+        state.list_index,
+        list_height,
+        visible.len(),
+    );
+
+    let heading = if state.query.is_empty() {
+        "Tags".to_string()
+    } else {
+        format!("Tags matching \"{}\" ({})", state.query, visible.len())
+    };
+    queue!(
+        stdout,
+        MoveTo(list_x, list_y - 1),
+        SetForegroundColor(state.theme.heading),
+        Print(clamp_text(&pane_title(&heading, state, true), list_width)),
+        ResetColor
+    )?;
+
+    if visible.is_empty() {
+        let message = if tags.tags.is_empty() {
+            "No tagged posts."
+        } else {
+            "No tags match."
+        };
+        queue!(
+            stdout,
+            MoveTo(list_x, list_y),
+            SetForegroundColor(state.theme.dim),
+            Print(message),
+            ResetColor
+        )?;
+    }
+
+    for (idx, (tag, count)) in visible
+        .iter()
+        .enumerate()
+        .skip(state.list_scroll)
+        .take(list_height)
+    {
+        let is_selected = idx == state.list_index;
+        let y = list_y + (idx - state.list_scroll) as u16;
+        queue!(stdout, MoveTo(list_x, y))?;
+        if is_selected {
+            queue_selection(stdout, &state.theme, state.high_contrast)?;
+        } else {
+            queue!(stdout, SetForegroundColor(state.theme.text))?;
+        }
+        let label = clamp_text(&format!("#{} ({})", tag, count), list_width - 2);
+        queue!(
+            stdout,
+            Print(format!("{} {}", if is_selected { "›" } else { " " }, label)),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+
+    state.hits.list = Some(ListArea::new(
+        list_x,
+        list_width,
+        list_y,
+        list_height,
+        state.list_scroll,
+        visible.len(),
+    ));
+
+    // Preview the posts behind the highlighted tag; Enter switches the list to them.
+    let content_x = (list_x + list_width as u16 + 2).min(max_width as u16);
+    let content_width = max_width.saturating_sub(content_x as usize + 1).max(10);
+    let Some((tag, _)) = visible.get(state.list_index) else {
+        return Ok(());
+    };
+    queue!(
+        stdout,
+        MoveTo(content_x, list_y - 1),
+        SetAttribute(Attribute::Bold),
+        Print(clamp_text(&format!("#{}", tag), content_width)),
+        SetAttribute(Attribute::Reset)
+    )?;
+    let tagged = tags
+        .posts
+        .posts
+        .iter()
+        .filter(|post| state.show_drafts || !post.draft)
+        .filter(|post| post.tags.iter().any(|candidate| candidate == tag));
+    for (y, post) in (list_y + 1..rows.saturating_sub(2)).zip(tagged) {
+        let date = if post.date.is_empty() {
+            String::new()
+        } else {
+            format!("{} ", format_date(&post.date))
+        };
+        queue!(
+            stdout,
+            MoveTo(content_x, y),
+            SetForegroundColor(state.theme.dim),
+            Print(clamp_text(
+                &format!("{}{}", date, post.title),
+                content_width
+            )),
+            ResetColor
+        )?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_content_tab(
+    stdout: &mut Stdout,
+    state: &mut AppState,
+    tab: &ContentTab,
+    link_status: &HashMap<String, LinkStatus>,
+    max_width: usize,
+    rows: u16,
+    content_top: u16,
+) -> io::Result<()> {
+    let list_x = 2;
+    let list_y = content_top + 1;
+    let list_width = ((max_width as f32) * 0.33) as usize;
+    let list_width = list_width.clamp(24, 38);
+    let list_height = rows.saturating_sub(list_y + 3) as usize;
+    let posts = visible_posts(tab, state);
+
+    state.list_scroll = clamp_scroll(
+        state.list_scroll,
+        state.list_index,
+        list_height,
+        posts.len(),
+    );
+
+    render_post_list(
+        stdout,
+        state,
+        tab,
+        &posts,
+        link_status,
+        list_x,
+        list_y,
+        list_width,
+        list_height,
+    )?;
+    let content_focus = state.focus == Focus::Content && !is_gallery_tab(tab);
+
+    let content_x = (list_x + list_width as u16 + 2).min(max_width as u16);
+    let content_width = max_width.saturating_sub(content_x as usize + 1).max(10);
+
+    if let Some(post) = posts.get(state.list_index).copied() {
+        if is_gallery_tab(tab) {
+            state.content_scroll = 0;
+            state.content_scroll_max = 0;
+            state.footnote_jumps.clear();
+            state.match_lines.clear();
+            render_gallery_preview(
+                stdout,
+                state,
+                post,
+                content_x,
+                list_y - 1,
+                content_width,
+                rows,
+            )?;
+        } else {
+            let content_top = list_y - 1;
+            let mut y = content_top;
+
+            let title = clamp_text(
+                &pane_title(&post.title, state, content_focus),
+                content_width,
+            );
+            queue!(
+                stdout,
+                MoveTo(content_x, y),
+                SetForegroundColor(if content_focus {
+                    state.theme.heading
+                } else {
+                    state.theme.dim
+                }),
+                SetAttribute(Attribute::Bold),
+                Print(hyperlink(&title, &post.url)),
+                SetAttribute(Attribute::Reset),
+                ResetColor
+            )?;
+            y += 1;
+
+            let mut meta = Vec::new();
+            if !post.date.is_empty() {
+                meta.push((state.theme.dim, post.date.clone()));
+            }
+            if post.words > 0 {
+                meta.push((
+                    state.theme.dim,
+                    format!(
+                        "{} word{} · {} min read",
+                        group_thousands(post.words),
+                        if post.words == 1 { "" } else { "s" },
+                        post.reading_minutes()
+                    ),
+                ));
+            }
+            if let Some(location) = post
+                .fields
+                .get("location")
+                .filter(|value| !value.is_empty())
+            {
+                meta.push((state.theme.dim, format!("📍 {}", location)));
+            }
+            if !post.tags.is_empty() {
+                let tags = post
+                    .tags
+                    .iter()
+                    .map(|tag| format!("#{}", tag))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                meta.push((state.theme.dim, tags));
+            }
+            if let Some(link) = link_status.get(&post.url) {
+                if link.is_dead() {
+                    meta.push((
+                        Color::Red,
+                        format!("⚠ This page returned {} on the last link check.", link.code),
+                    ));
+                } else if let Some(location) = link.moved_to() {
+                    meta.push((Color::Yellow, format!("↪ Moved to {}", location)));
+                }
+            }
+
+            let previous = state
+                .reads
+                .previous(&post.url)
+                .filter(|previous| *previous != post.body);
+            let showing_diff = previous.is_some() && state.diff_view.as_ref() == Some(&post.url);
+            if previous.is_some() {
+                let text = if showing_diff {
+                    "✱ Changes since you last read this (d to close)"
+                } else {
+                    "✱ Updated since you last read this (d shows changes)"
+                };
+                meta.push((Color::Yellow, text.to_string()));
+            }
+
+            // On a short terminal the metadata stops above the footer like the body does.
+            for (color, text) in meta {
+                if y >= rows.saturating_sub(2) {
+                    break;
+                }
+                queue!(
+                    stdout,
+                    MoveTo(content_x, y),
+                    SetForegroundColor(color),
+                    Print(clamp_text(&text, content_width)),
+                    ResetColor
+                )?;
+                y += 1;
+            }
+
+            let mut lines = match previous {
+                Some(previous) if showing_diff => layout_diff(previous, &post.body, content_width),
+                _ => layout_post_body(&post.body, content_width, &state.query),
+            };
+            if let Some(Decoding::Transcoded(encoding)) = post.decoding {
+                push_section_heading(&mut lines, &format!("(transcoded from {})", encoding));
+            }
+            let available = rows.saturating_sub(y + 2) as usize;
+            state.content_height = available;
+            state.footnote_jumps = footnote_jumps(&lines);
+            state.match_lines = match_lines(&lines);
+            state.content_scroll_max = lines.len().saturating_sub(available);
+            if state.content_scroll > state.content_scroll_max {
+                state.content_scroll = state.content_scroll_max;
+            }
+
+            let body_top = y;
+            for line in lines.iter().skip(state.content_scroll).take(available) {
+                queue!(stdout, MoveTo(content_x, y))?;
+                print_styled_line(stdout, &state.theme, line, content_width)?;
+                y += 1;
+                if y >= rows.saturating_sub(2) {
+                    break;
+                }
+            }
+            render_scrollbar(
+                stdout,
+                &state.theme,
+                content_x + content_width as u16,
+                body_top,
+                available,
+                state.content_scroll,
+                lines.len(),
+            )?;
+        }
+    } else {
+        state.content_scroll_max = 0;
+        state.footnote_jumps.clear();
+        state.match_lines.clear();
+        queue!(
+            stdout,
+            MoveTo(content_x, list_y),
+            SetForegroundColor(state.theme.dim),
+            Print("Select a post to read."),
+            ResetColor
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The post list to the left of the reader: its heading, the rows, and the scrollbar.
+#[allow(clippy::too_many_arguments)]
+fn render_post_list(
+    stdout: &mut impl Write,
+    state: &mut AppState,
+    tab: &ContentTab,
+    posts: &[&Post],
+    link_status: &HashMap<String, LinkStatus>,
+    list_x: u16,
+    list_y: u16,
+    list_width: usize,
+    list_height: usize,
+) -> io::Result<()> {
+    let mut heading = "Posts".to_string();
+    if let Some(tag) = &state.tag_filter {
+        heading.push_str(&format!(" tagged #{}", tag));
+    }
+    if !state.query.is_empty() {
+        heading.push_str(&format!(" matching \"{}\"", state.query));
+    }
+    if state.tag_filter.is_some() || !state.query.is_empty() {
+        heading.push_str(&format!(" ({})", posts.len()));
+    }
+    // The focused pane's heading is the brighter one.
+    let content_focus = state.focus == Focus::Content && !is_gallery_tab(tab);
+    queue!(
+        stdout,
+        MoveTo(list_x, list_y - 1),
+        SetForegroundColor(if content_focus {
+            state.theme.dim
+        } else {
+            state.theme.heading
+        }),
+        Print(clamp_text(
+            &pane_title(&heading, state, !content_focus),
+            list_width
+        )),
+        ResetColor
+    )?;
+
+    if posts.is_empty() {
+        let message = if tab.listed_posts().next().is_none() {
+            "No posts found."
+        } else {
+            "No posts match."
+        };
+        queue!(
+            stdout,
+            MoveTo(list_x, list_y),
+            SetForegroundColor(state.theme.dim),
+            Print(message),
+            ResetColor
+        )?;
+    } else {
+        for (idx, post) in posts
+            .iter()
+            .enumerate()
+            .skip(state.list_scroll)
+            .take(list_height)
+        {
+            let is_selected = idx == state.list_index;
+            let y = list_y + (idx - state.list_scroll) as u16;
+            queue!(stdout, MoveTo(list_x, y))?;
+            if is_selected {
+                queue_selection(stdout, &state.theme, state.high_contrast)?;
+            } else if post.draft {
+                queue!(stdout, SetForegroundColor(state.theme.draft))?;
+            } else {
+                queue!(stdout, SetForegroundColor(state.theme.text))?;
+            }
+            let date = if post.date.is_empty() {
+                "".to_string()
+            } else {
+                format!("{} ", format_date(&post.date))
+            };
+            let marker = if link_status.get(&post.url).is_some_and(LinkStatus::is_dead) {
+                "⚠ "
+            } else {
+                ""
+            };
+            let label = format!("{}{}{}", marker, date, post.title);
+            let suffix = if post.draft { DRAFT_SUFFIX } else { "" };
+            queue!(
+                stdout,
+                Print(list_row(
+                    &label,
+                    list_width.saturating_sub(suffix.len()),
+                    is_selected
+                ))
+            )?;
+            // The selection bar runs through the suffix; elsewhere it's dimmed.
+            if !is_selected {
+                queue!(stdout, SetForegroundColor(state.theme.dim))?;
+            }
+            queue!(stdout, Print(suffix), SetAttribute(Attribute::Reset))?;
+        }
+    }
+
+    state.hits.list = Some(ListArea::new(
+        list_x,
+        list_width,
+        list_y,
+        list_height,
+        state.list_scroll,
+        posts.len(),
+    ));
+    render_scrollbar(
+        stdout,
+        &state.theme,
+        list_x + list_width as u16,
+        list_y,
+        list_height,
+        state.list_scroll,
+        posts.len(),
+    )?;
+    Ok(())
+}
+
+/// Draws a one-column scrollbar for `total` lines seen `height` at a time from `offset`: a dim
+/// track with a bright thumb sized to the visible fraction. Nothing is drawn when it all fits.
+fn render_scrollbar(
+    stdout: &mut impl Write,
+    theme: &Theme,
+    x: u16,
+    top: u16,
+    height: usize,
+    offset: usize,
+    total: usize,
+) -> io::Result<()> {
+    if height == 0 || total <= height {
+        return Ok(());
+    }
+    let (start, len) = scrollbar_thumb(height, offset, total);
+    for row in 0..height {
+        let (color, glyph) = if (start..start + len).contains(&row) {
+            (theme.heading, "┃")
+        } else {
+            (theme.dim, "│")
+        };
+        queue!(
+            stdout,
+            MoveTo(x, top + row as u16),
+            SetForegroundColor(color),
+            Print(glyph),
+            ResetColor
+        )?;
+    }
+    Ok(())
+}
+
+/// First row and length of the scrollbar thumb. The thumb reaches the bottom of the track only
+/// at the last offset, so it never looks finished while there is more to read.
+fn scrollbar_thumb(height: usize, offset: usize, total: usize) -> (usize, usize) {
+    let len = (height * height).div_ceil(total).clamp(1, height);
+    let max_offset = total.saturating_sub(height).max(1);
+    let travel = height - len;
+    let start = if offset >= max_offset {
+        travel
+    } else {
+        (offset * travel / max_offset).min(travel.saturating_sub(1))
+    };
+    (start, len)
+}
+
+/// Prints spans left to right, truncating with `…` once `max_width` columns are used.
+fn print_styled_line(
+    stdout: &mut impl Write,
+    theme: &Theme,
+    line: &StyledLine,
+    max_width: usize,
+) -> io::Result<()> {
+    let gutter = if line.kind == LineKind::Quote {
+        "│ "
+    } else {
+        ""
+    };
+    let total = display_width(gutter)
+        + line
+            .spans
+            .iter()
+            .map(|span| display_width(&span.text))
+            .sum::<usize>();
+    let overflow = total > max_width;
+    let mut budget = if overflow {
+        max_width.saturating_sub(1)
+    } else {
+        max_width
+    };
+    match line.kind {
+        LineKind::Body => {}
+        LineKind::Quote => {
+            let gutter = take_columns(gutter, budget);
+            budget -= display_width(&gutter);
+            queue!(
+                stdout,
+                SetForegroundColor(theme.dim),
+                Print(gutter),
+                ResetColor
+            )?;
+        }
+        LineKind::Heading(1) => queue!(
+            stdout,
+            SetAttribute(Attribute::Bold),
+            SetAttribute(Attribute::Underlined)
+        )?,
+        LineKind::Heading(_) => queue!(stdout, SetAttribute(Attribute::Bold))?,
+    }
+
+    for span in &line.spans {
+        if budget == 0 {
+            break;
+        }
+        let text = take_columns(&span.text, budget);
+        budget = budget.saturating_sub(display_width(&text));
+        if text.is_empty() && !span.text.is_empty() {
+            break;
+        }
+        let color = match span.style {
+            SpanStyle::Plain if line.kind == LineKind::Quote => Some(theme.dim),
+            SpanStyle::Plain | SpanStyle::Link => None,
+            SpanStyle::Dim => Some(theme.dim),
+            SpanStyle::FootnoteRef(_) | SpanStyle::FootnoteDef(_) => Some(theme.accent),
+            SpanStyle::Code | SpanStyle::Added => Some(Color::Green),
+            SpanStyle::Removed => Some(Color::Red),
+        };
+        if span.highlight && !theme.color {
+            queue!(stdout, SetAttribute(Attribute::Reverse))?;
+        } else if span.highlight {
+            queue!(
+                stdout,
+                SetForegroundColor(Color::Black),
+                SetBackgroundColor(Color::Yellow)
+            )?;
+        } else if let Some(color) = color {
+            queue!(stdout, SetForegroundColor(color))?;
+        }
+        if span.bold {
+            queue!(stdout, SetAttribute(Attribute::Bold))?;
+        }
+        if span.italic {
+            queue!(stdout, SetAttribute(Attribute::Italic))?;
+        }
+        let underline = span.style == SpanStyle::Link && line.kind != LineKind::Heading(1);
+        if underline {
+            queue!(stdout, SetAttribute(Attribute::Underlined))?;
+        }
+        queue!(stdout, Print(text))?;
+        if underline {
+            queue!(stdout, SetAttribute(Attribute::NoUnderline))?;
+        }
+        if span.highlight || color.is_some() {
+            queue!(stdout, ResetColor)?;
+        }
+        // Headings are bold for the whole line, so only drop intensity inside body text.
+        if span.bold && !matches!(line.kind, LineKind::Heading(_)) {
+            queue!(stdout, SetAttribute(Attribute::NormalIntensity))?;
+        }
+        if span.italic {
+            queue!(stdout, SetAttribute(Attribute::NoItalic))?;
+        }
+    }
+    if overflow && max_width > 0 {
+        queue!(stdout, Print('…'))?;
+    }
+    if matches!(line.kind, LineKind::Heading(_)) {
+        queue!(stdout, SetAttribute(Attribute::Reset))?;
+    }
+    Ok(())
+}
+
+/// The scroll offset that keeps `index` inside a window of `height` rows. A window of zero or
+/// one row scrolls to the selection itself, so it is the first row shown once there is room.
+fn clamp_scroll(scroll: usize, index: usize, height: usize, total: usize) -> usize {
+    if total <= height {
+        return 0;
+    }
+    if height <= 1 {
+        return index.min(total - 1);
+    }
+    if index < scroll {
+        return index;
+    }
+    if index >= scroll + height {
+        return index.saturating_sub(height.saturating_sub(1));
+    }
+    scroll.min(total.saturating_sub(height))
+}
+
+fn list_length(data: &AppData, state: &AppState) -> usize {
+    match data.tabs.get(state.tab_index) {
+        Some(TabData::About(about)) => visible_links(about, state).len(),
+        Some(TabData::Content(tab)) => visible_posts(tab, state).len(),
+        Some(TabData::Tags(tags)) if state.tag_filter.is_some() => {
+            visible_posts(&tags.posts, state).len()
+        }
+        Some(TabData::Tags(tags)) => visible_tags(tags, state).len(),
+        None => 0,
+    }
+}
+
+/// Posts `tab` lists whose title or date contains the search query (case-insensitive) and that
+/// carry the active tag filter, in tab order. Drafts are left out unless they're shown.
+fn visible_posts<'a>(tab: &'a ContentTab, state: &AppState) -> Vec<&'a Post> {
+    let query = state.query.trim();
+    tab.posts
+        .iter()
+        .filter(|post| {
+            if post.draft {
+                state.show_drafts
+            } else {
+                tab.lists(post) || state.revealed.as_deref() == Some(post.url.as_str())
+            }
+        })
+        .filter(|post| {
+            query.is_empty()
+                || find_ignore_case(&post.title, query).is_some()
+                || find_ignore_case(&post.date, query).is_some()
+        })
+        .filter(|post| {
+            state
+                .tag_filter
+                .as_ref()
+                .is_none_or(|tag| post.tags.iter().any(|candidate| candidate == tag))
+        })
+        .collect()
+}
+
+fn visible_tags<'a>(tags: &'a TagsTab, state: &AppState) -> Vec<&'a (String, usize)> {
+    let query = state.query.trim();
+    tags.tags
+        .iter()
+        .filter(|(tag, _)| query.is_empty() || find_ignore_case(tag, query).is_some())
+        .collect()
+}
+
+fn visible_links<'a>(about: &'a AboutData, state: &AppState) -> Vec<&'a Link> {
+    let query = state.query.trim();
+    about
+        .links
+        .iter()
+        .filter(|link| query.is_empty() || find_ignore_case(link.label, query).is_some())
+        .collect()
+}
+
+fn tab_name(tab: &TabData) -> &'static str {
+    match tab {
+        TabData::About(_) => "About",
+        TabData::Content(tab) => tab.name,
+        TabData::Tags(tags) => tags.posts.name,
+    }
+}
+fn is_gallery_tab(tab: &ContentTab) -> bool {
+    tab.name == "Gallery"
+}
+fn header_text<'a>(data: &'a AppData, state: &AppState) -> (&'a str, &'a str) {
+    match data.tabs.get(state.tab_index) {
+        Some(TabData::Content(tab)) => (tab.name, &tab.description),
+        Some(TabData::Tags(tags)) => (tags.posts.name, &tags.posts.description),
+        Some(TabData::About(_)) | None => (&data.header.title, &data.header.subtitle),
+    }
+}
+
+/// `1`-`9` for the first nine tabs and `0` for the tenth; later tabs have no digit and are
+/// reached with tab/arrow cycling.
+fn tab_hotkey(index: usize) -> Option<char> {
+    match index {
+        0..=8 => char::from_digit(index as u32 + 1, 10),
+        9 => Some('0'),
+        _ => None,
+    }
+}
+
+fn tab_for_hotkey(key: char) -> Option<usize> {
+    match key.to_digit(10)? {
+        0 => Some(9),
+        digit => Some(digit as usize - 1),
+    }
+}
+
+/// `1-7`, or `1-9, 0` once there are ten or more tabs, for hints and help.
+fn tab_hotkey_range(count: usize) -> String {
+    match count {
+        0 => String::new(),
+        1 => "1".to_string(),
+        2..=9 => format!("1-{}", count),
+        _ => "1-9, 0".to_string(),
+    }
+}
+
+fn gallery_tab_index(data: &AppData) -> Option<usize> {
+    data.tabs
+        .iter()
+        .position(|tab| matches!(tab, TabData::Content(tab) if is_gallery_tab(tab)))
+}
+
+fn tab_label(index: usize, tab: &TabData) -> String {
+    match tab_hotkey(index) {
+        Some(key) => format!("{}. {}", key, tab_name(tab)),
+        None => tab_name(tab).to_string(),
+    }
+}
+
+/// Expands a leading `~` and `$VAR` / `${VAR}` references the way a shell would, so paths
+/// copied from a shell profile work without quoting gymnastics. Unset variables expand to
+/// nothing.
+fn expand_path(input: &str) -> PathBuf {
+    let mut out = String::new();
+    let mut rest = input;
+    if rest == "~" || rest.starts_with("~/") {
+        if let Ok(home) = env::var("HOME") {
+            out.push_str(&home);
+            rest = &rest[1..];
+        }
+    }
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remainder) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => ("", after),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        if name.is_empty() {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        out.push_str(&env::var(name).unwrap_or_default());
+        rest = remainder;
+    }
+    out.push_str(rest);
+    PathBuf::from(out)
+}
+
+/// The directory `--content-dir` names, which beats every other setting, so a typo is an error
+/// rather than a fall back to the next one.
+fn content_dir_flag(value: &str) -> Result<PathBuf, String> {
+    let path = expand_path(value);
+    if path.is_dir() {
+        Ok(path)
+    } else {
+        Err(format!(
+            "--content-dir: {} is not a directory.",
+            path.display()
+        ))
+    }
+}
+
+fn resolve_content_root(config: &Config, status: &mut Option<String>) -> Option<PathBuf> {
+    find_local_content_root(config).or_else(|| ensure_remote_content(status))
+}
+
+fn find_local_content_root(config: &Config) -> Option<PathBuf> {
+    locate_content_root(
+        config.content_dir.as_deref(),
+        env::current_dir().ok().as_deref(),
+    )
+}
+
+/// The content directory on disk, if there is one: `configured` when it exists, else a
+/// `part-of-my-brain` directory in `start` or up to five levels above it. `configured` is
+/// already the winner of `--content-dir` over `JOHNJEONG_CONTENT_DIR` over the config's
+/// `content.dir` (see `Config::resolve` and `main`); a fetched copy comes after all of these.
+fn locate_content_root(configured: Option<&Path>, start: Option<&Path>) -> Option<PathBuf> {
+    if let Some(dir) = configured.filter(|dir| dir.is_dir()) {
+        return Some(dir.to_path_buf());
+    }
+
+    let mut dir = start?.to_path_buf();
+    for _ in 0..6 {
+        let candidate = dir.join("part-of-my-brain");
+        if candidate.is_dir() {
+            return Some(candidate);
+        }
+        if !dir.pop() {
+            break;
+        }
+    }
+    None
+}
+
+/// The header from the config when it sets one, else from the site's `Header.astro` when run
+/// inside a checkout of the site, else the built-in one.
+fn load_header_data(config: &Config) -> HeaderData {
+    let mut title = None;
+    let mut subtitle = None;
+    if config.title.is_none() || config.subtitle.is_none() {
+        if let Some(contents) = resolve_header_path().and_then(|path| fs::read_to_string(path).ok())
+        {
+            title = extract_quoted_value(&contents, "title =");
+            subtitle =
+                extract_quoted_value(&contents, "subtitle =").map(|value| strip_html_tags(&value));
+        }
+    }
+
+    HeaderData {
+        title: config
+            .title
+            .clone()
+            .or(title)
+            .unwrap_or_else(|| "John Jeong".to_string()),
+        subtitle: config
+            .subtitle
+            .clone()
+            .or(subtitle)
+            .unwrap_or_else(|| "Co-founder & Co-CEO at Hyprnote".to_string()),
+    }
+}
+
+fn resolve_header_path() -> Option<PathBuf> {
+    let mut dir = env::current_dir().ok()?;
+    for _ in 0..6 {
+        let candidate = dir.join("src/components/Header.astro");
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if !dir.pop() {
+            break;
+        }
+    }
+    None
+}
+
+fn extract_quoted_value(contents: &str, marker: &str) -> Option<String> {
+    let index = contents.find(marker)?;
+    let after_marker = &contents[index + marker.len()..];
+    let quote_pos = after_marker.find(['"', '\''])?;
+    let quote_char = after_marker.chars().nth(quote_pos)?;
+    let rest = &after_marker[quote_pos + 1..];
+    let end = rest.find(quote_char)?;
+    Some(rest[..end].trim().to_string())
+}
+
+fn strip_html_tags(input: &str) -> String {
+    let mut output = String::new();
+    let mut in_tag = false;
+    for ch in input.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => output.push(ch),
+            _ => {}
+        }
+    }
+    output.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether the frontmatter has a `published` other than true. Posts without one count as
+/// published.
+fn is_unpublished(frontmatter: &HashMap<String, String>) -> bool {
+    frontmatter
+        .get("published")
+        .is_some_and(|published| published.to_lowercase() != "true")
+}
+
+fn load_posts(dir: &Path, base_url: &str, mark_drafts: bool) -> io::Result<Vec<Post>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut posts = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("md") || is_section_index(&path) {
+            continue;
+        }
+        posts.push(load_post(&path, base_url, mark_drafts)?);
+    }
+
+    // Newest first; ties fall back to the URL so the order never depends on `read_dir`.
+    posts.sort_by(|a, b| b.sort_key.cmp(&a.sort_key).then_with(|| a.url.cmp(&b.url)));
+    Ok(posts)
+}
+
+/// Reads one markdown post. With `mark_drafts`, one whose frontmatter says it isn't published
+/// comes back as a draft.
+fn load_post(path: &Path, base_url: &str, mark_drafts: bool) -> io::Result<Post> {
+    let (content, decoding) = read_text(path)?;
+    let (frontmatter, body) = split_frontmatter(&content);
+    let draft = mark_drafts && is_unpublished(&frontmatter);
+
+    let slug = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("post");
+
+    let date = frontmatter
+        .get("created_at")
+        .cloned()
+        .or_else(|| date_from_slug(slug));
+
+    let title = frontmatter
+        .get("title")
+        .cloned()
+        .unwrap_or_else(|| title_from_slug(slug));
+
+    let description = frontmatter.get("description").cloned().unwrap_or_default();
+    let body_text = normalize_body(body);
+    let body = if decoding == Some(Decoding::Undecodable) {
+        format!(
+            "This file isn't UTF-8 and its encoding couldn't be worked out, so it isn't shown \
+             here.\n\nOpen {} in an editor and save it as UTF-8.",
+            path.display()
+        )
+    } else if body_text.trim().is_empty() && !description.is_empty() {
+        description
+    } else {
+        body_text.trim().to_string()
+    };
+
+    let words = if decoding == Some(Decoding::Undecodable) {
+        0
+    } else {
+        body.split_whitespace().count()
+    };
+    let sort_key = date.clone().unwrap_or_else(|| slug.to_string());
+    let url_slug = post_slug(slug, &frontmatter);
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), url_slug);
+
+    let tags = frontmatter
+        .get("tags")
+        .map(|value| parse_tags(value))
+        .unwrap_or_default();
+
+    Ok(Post {
+        title,
+        date: date.unwrap_or_default(),
+        tags,
+        fields: frontmatter,
+        body,
+        url,
+        slug: url_slug,
+        sort_key,
+        dir: path.parent().map(Path::to_path_buf),
+        file: Some(path.to_path_buf()),
+        decoding,
+        words,
+        draft,
+    })
+}
+
+const SNAPSHOT_MAX_BYTES: usize = 256 * 1024;
+const SNAPSHOT_LIMIT: usize = 200;
+const DIFF_MAX_CELLS: usize = 4_000_000;
+const DIFF_CONTEXT: usize = 2;
+
+const SECTION_INDEX_FILES: [&str; 2] = ["_index.md", "README.md"];
+
+/// `_index.md` / `README.md` describe a section rather than being posts in it.
+fn is_section_index(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            SECTION_INDEX_FILES
+                .iter()
+                .any(|index| index.eq_ignore_ascii_case(name))
+        })
+}
+
+/// Reads a tab description from the section's index file: its frontmatter `description`, or
+/// failing that the first paragraph of prose.
+fn load_tab_description(dir: &Path) -> Option<String> {
+    SECTION_INDEX_FILES.iter().find_map(|name| {
+        let contents = fs::read_to_string(dir.join(name)).ok()?;
+        let (frontmatter, body) = split_frontmatter(&contents);
+        frontmatter
+            .get("description")
+            .filter(|description| !description.is_empty())
+            .cloned()
+            .or_else(|| first_paragraph(body))
+    })
+}
+
+fn first_paragraph(body: &str) -> Option<String> {
+    let paragraph = body
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty() || line.starts_with('#'))
+        .take_while(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if paragraph.is_empty() {
+        None
+    } else {
+        Some(strip_html_tags(&paragraph))
+    }
+}
+
+/// Cleans up export artifacts for display: trailing whitespace, non-breaking spaces outside
+/// fenced code, and runs of three or more blank lines (collapsed to one). Files on disk are
+/// left alone.
+fn normalize_body(body: &str) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut in_fence = false;
+    let mut blank_run = 0;
+
+    for raw in body.lines() {
+        let line = raw.trim_end();
+        let is_fence = is_fence_line(line);
+
+        if !in_fence && line.is_empty() {
+            blank_run += 1;
+            continue;
+        }
+        if blank_run > 0 {
+            let keep = if blank_run >= 3 { 1 } else { blank_run };
+            for _ in 0..keep {
+                output.push('\n');
+            }
+            blank_run = 0;
+        }
+
+        if in_fence || is_fence {
+            output.push_str(line);
+        } else {
+            output.push_str(&line.replace('\u{a0}', " "));
+        }
+        output.push('\n');
+
+        if is_fence {
+            in_fence = !in_fence;
+        }
+    }
+
+    output
+}
+
+fn is_fence_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+fn split_frontmatter(contents: &str) -> (HashMap<String, String>, &str) {
+    let mut map = HashMap::new();
+    if !contents.starts_with("---") {
+        return (map, contents);
+    }
+
+    let mut offset = 0usize;
+    let mut lines = contents.lines();
+    let first = lines.next();
+    if first != Some("---") {
+        return (map, contents);
+    }
+    offset += first.unwrap_or("").len() + 1;
+
+    // A key with no inline value may be followed by a YAML block list (`  - item`), which is
+    // folded into a comma-separated value.
+    let mut list_key: Option<String> = None;
+    for line in lines {
+        if line == "---" {
+            offset += line.len() + 1;
+            break;
+        }
+        offset += line.len() + 1;
+        if let (Some(key), Some(item)) = (&list_key, line.trim_start().strip_prefix("- ")) {
+            let value: &mut String = map.entry(key.clone()).or_default();
+            if !value.is_empty() {
+                value.push_str(", ");
+            }
+            value.push_str(&clean_frontmatter_value(item));
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            let key = key.trim().to_string();
+            list_key = value.trim().is_empty().then(|| key.clone());
+            map.insert(key, clean_frontmatter_value(value.trim()));
+        }
+    }
+
+    let body = contents.get(offset..).unwrap_or("");
+    (map, body)
+}
+
+/// Parses `[a, b]`, `a, b`, or a folded block list into trimmed, unquoted tag names.
+fn parse_tags(value: &str) -> Vec<String> {
+    value
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(clean_frontmatter_value)
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+fn clean_frontmatter_value(value: &str) -> String {
+    let trimmed = value.trim();
+    if (trimmed.starts_with('"') && trimmed.ends_with('"'))
+        || (trimmed.starts_with('\'') && trimmed.ends_with('\''))
+    {
+        trimmed[1..trimmed.len().saturating_sub(1)].to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// How the gallery gets its dates (config `gallery_dates`). `Eager` stats and reads EXIF from
+/// every image before the UI starts; `Lazy` lists images by filename and fills those in from a
+/// background thread. `Auto` starts eagerly and goes lazy if the first stats are slow, as on a
+/// network drive.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GalleryDates {
+    Auto,
+    Eager,
+    Lazy,
+}
+
+impl GalleryDates {
+    fn parse(value: &str) -> Option<GalleryDates> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "auto" => Some(GalleryDates::Auto),
+            "eager" => Some(GalleryDates::Eager),
+            "lazy" => Some(GalleryDates::Lazy),
+            _ => None,
+        }
+    }
+}
+
+/// Stats timed before `GalleryDates::Auto` decides, and the average that counts as slow.
+const SLOW_STAT_SAMPLE: usize = 12;
+const SLOW_STAT_THRESHOLD: Duration = Duration::from_millis(25);
+
+/// Loads gallery images, newest first. When dates are deferred the images come back sorted by
+/// filename and the second value is `true`; `backfill_gallery_dates` supplies the rest.
+fn load_gallery(dir: &Path, mode: GalleryDates) -> io::Result<(Vec<Post>, bool)> {
+    if !dir.is_dir() {
+        return Ok((Vec::new(), false));
+    }
+
+    // Absolute so a photo still opens if the opener runs in another directory. Not
+    // `canonicalize`, whose `\\?\` paths on Windows confuse openers.
+    let dir = std::path::absolute(dir)?;
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let ext = path
+            .extension()
+            .and_then(|value| value.to_str())
+            .unwrap_or("");
+        if matches!(ext.to_lowercase().as_str(), "jpg" | "jpeg" | "png" | "webp") {
+            paths.push(path);
+        }
+    }
+
+    let mut posts = Vec::with_capacity(paths.len());
+    let mut lazy = mode == GalleryDates::Lazy;
+    let started = Instant::now();
+    for (index, path) in paths.iter().enumerate() {
+        if lazy {
+            break;
+        }
+        let (sort_key, location) = gallery_details(path);
+        posts.push(gallery_post(path, sort_key, location));
+        if mode == GalleryDates::Auto
+            && index + 1 == SLOW_STAT_SAMPLE
+            && started.elapsed() > SLOW_STAT_THRESHOLD * SLOW_STAT_SAMPLE as u32
+        {
+            lazy = true;
+        }
+    }
+
+    if lazy {
+        posts = paths
+            .iter()
+            .map(|path| gallery_post(path, gallery_title(path).to_string(), None))
+            .collect();
+        posts.sort_by(|a, b| a.sort_key.cmp(&b.sort_key).then_with(|| a.url.cmp(&b.url)));
+    } else {
+        posts.sort_by(|a, b| b.sort_key.cmp(&a.sort_key).then_with(|| a.url.cmp(&b.url)));
+    }
+    Ok((posts, lazy))
+}
+
+fn gallery_title(path: &Path) -> &str {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("image")
+}
+
+/// The sort key (mtime, or the filename if that fails) and EXIF location of one image. This is
+/// the slow part on network filesystems.
+fn gallery_details(path: &Path) -> (String, Option<String>) {
+    let sort_key = fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| format!("{:020}", duration.as_secs()))
+        .unwrap_or_else(|| gallery_title(path).to_string());
+    let location = exif_gps(path).map(|(lat, long)| format!("{:.5}, {:.5}", lat, long));
+    (sort_key, location)
+}
+
+fn gallery_post(path: &Path, sort_key: String, location: Option<String>) -> Post {
+    let mut fields = HashMap::new();
+    if let Some(location) = location {
+        fields.insert("location".to_string(), location);
+    }
+    Post {
+        title: gallery_title(path).to_string(),
+        date: String::new(),
+        tags: Vec::new(),
+        fields,
+        body: format!("Image file: {}", path.display()),
+        url: path.to_string_lossy().to_string(),
+        slug: gallery_title(path).to_string(),
+        sort_key,
+        dir: None,
+        file: Some(path.to_path_buf()),
+        decoding: None,
+        words: 0,
+        draft: false,
+    }
+}
+
+/// Looks up deferred gallery dates off the UI thread and posts them back as one event.
+fn backfill_gallery_dates(paths: Vec<PathBuf>, events: Sender<AppEvent>) {
+    thread::spawn(move || {
+        let details = paths
+            .iter()
+            .map(|path| {
+                let (sort_key, location) = gallery_details(path);
+                (path.to_string_lossy().to_string(), sort_key, location)
+            })
+            .collect();
+        let _ = events.send(AppEvent::GalleryDates(details));
+    });
+}
+
+/// Applies backfilled dates and re-sorts the gallery the way it would have been sorted at
+/// startup, default order first and then any `[tabs.gallery]` sort. The selection stays on the
+/// same image.
+fn apply_gallery_dates(
+    data: &mut AppData,
+    state: &mut AppState,
+    details: Vec<(String, String, Option<String>)>,
+) {
+    data.gallery_dates_pending = false;
+    let Some(index) = gallery_tab_index(data) else {
+        return;
+    };
+    let selected_url = match &data.tabs[index] {
+        TabData::Content(tab) if state.tab_index == index => visible_posts(tab, state)
+            .get(state.list_index)
+            .map(|post| post.url.clone()),
+        _ => None,
+    };
+
+    let details: HashMap<String, (String, Option<String>)> = details
+        .into_iter()
+        .map(|(url, sort_key, location)| (url, (sort_key, location)))
+        .collect();
+    if let TabData::Content(tab) = &mut data.tabs[index] {
+        for post in &mut tab.posts {
+            if let Some((sort_key, location)) = details.get(&post.url) {
+                post.sort_key = sort_key.clone();
+                if let Some(location) = location {
+                    post.fields.insert("location".to_string(), location.clone());
+                }
+            }
+        }
+        tab.posts
+            .sort_by(|a, b| b.sort_key.cmp(&a.sort_key).then_with(|| a.url.cmp(&b.url)));
+    }
+    // Only this tab is passed in, so complaints about other sections were already reported at
+    // startup and are dropped here.
+    apply_tab_views(std::slice::from_mut(&mut data.tabs[index]), &mut None);
+
+    if let (Some(url), TabData::Content(tab)) = (selected_url, &data.tabs[index]) {
+        if let Some(position) = visible_posts(tab, state)
+            .iter()
+            .position(|post| post.url == url)
+        {
+            state.list_index = position;
+        }
+    }
+    if state.status.as_deref() == Some(GALLERY_DATES_LOADING) {
+        state.status = None;
+    }
+}
+
+const GALLERY_DATES_LOADING: &str = "Gallery dates loading…";
+
+/// GPS position from a JPEG's EXIF block, in signed decimal degrees. Only the first 128 KiB
+/// is read, which is where cameras put EXIF; anything unexpected gives `None`.
+fn exif_gps(path: &Path) -> Option<(f64, f64)> {
+    let mut head = Vec::new();
+    fs::File::open(path)
+        .ok()?
+        .take(128 * 1024)
+        .read_to_end(&mut head)
+        .ok()?;
+    if !head.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut offset = 2;
+    let tiff = loop {
+        let marker = head.get(offset..offset + 4)?;
+        if marker[0] != 0xFF || marker[1] == 0xDA {
+            return None;
+        }
+        let length = u16::from_be_bytes([marker[2], marker[3]]) as usize;
+        let segment = head.get(offset + 4..offset + 2 + length)?;
+        if marker[1] == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            break &segment[6..];
+        }
+        offset += 2 + length;
+    };
+
+    let little = match tiff.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| -> Option<u16> {
+        let bytes = [*tiff.get(at)?, *tiff.get(at + 1)?];
+        Some(if little {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let u32_at = |at: usize| -> Option<u32> {
+        let bytes: [u8; 4] = tiff.get(at..at + 4)?.try_into().ok()?;
+        Some(if little {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+    // Entries are 12 bytes: tag, type, count, then the value or an offset to it.
+    let find_entry = |ifd: usize, tag: u16| -> Option<usize> {
+        let count = u16_at(ifd)? as usize;
+        (0..count)
+            .map(|index| ifd + 2 + index * 12)
+            .find(|&entry| u16_at(entry) == Some(tag))
+    };
+    let degrees = |ifd: usize, tag: u16| -> Option<f64> {
+        let values = u32_at(find_entry(ifd, tag)? + 8)? as usize;
+        let rational = |index: usize| -> Option<f64> {
+            let numerator = u32_at(values + index * 8)?;
+            let denominator = u32_at(values + index * 8 + 4)?;
+            (denominator != 0).then(|| numerator as f64 / denominator as f64)
+        };
+        Some(rational(0)? + rational(1)? / 60.0 + rational(2)? / 3600.0)
+    };
+    let reference =
+        |ifd: usize, tag: u16| -> Option<u8> { tiff.get(find_entry(ifd, tag)? + 8).copied() };
+
+    let ifd0 = u32_at(4)? as usize;
+    let gps = u32_at(find_entry(ifd0, 0x8825)? + 8)? as usize;
+    let mut lat = degrees(gps, 2)?;
+    let mut long = degrees(gps, 4)?;
+    if reference(gps, 1) == Some(b'S') {
+        lat = -lat;
+    }
+    if reference(gps, 3) == Some(b'W') {
+        long = -long;
+    }
+    ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&long)).then_some((lat, long))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SlugPreset {
+    /// What the Astro site does (github-slugger plus stripped diacritics): lowercase, drop
+    /// punctuation and symbols, and turn each space into `-`. Runs of `-` are kept as they are.
+    Astro,
+    /// The file name as is.
+    Plain,
+}
+
+/// `slugs = "astro"` (the default) or `"plain"` in the config. A frontmatter `slug` always wins.
+fn slug_preset() -> SlugPreset {
+    static PRESET: OnceLock<SlugPreset> = OnceLock::new();
+    *PRESET.get_or_init(|| {
+        let config = load_config();
+        match config
+            .get("")
+            .and_then(|top| top.get("slugs"))
+            .map(|value| value.trim().to_lowercase())
+            .as_deref()
+        {
+            Some("plain") => SlugPreset::Plain,
+            _ => SlugPreset::Astro,
+        }
+    })
+}
+
+/// The URL segment for a post: its frontmatter `slug`, else the file name run through `slugify`.
+fn post_slug(stem: &str, frontmatter: &HashMap<String, String>) -> String {
+    frontmatter
+        .get("slug")
+        .map(|value| value.trim().trim_matches('/'))
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| slugify(stem, slug_preset()))
+}
+
+/// The URL segment for a post file named `stem`.
+fn slugify(stem: &str, preset: SlugPreset) -> String {
+    if preset == SlugPreset::Plain {
+        return stem.to_string();
+    }
+    let mut slug = String::with_capacity(stem.len());
+    for c in stem.trim().chars().flat_map(char::to_lowercase) {
+        if let Some(plain) = strip_diacritic(c) {
+            slug.push_str(plain);
+        } else if c.is_alphanumeric() || c == '-' || c == '_' {
+            slug.push(c);
+        } else if c == ' ' {
+            slug.push('-');
+        }
+    }
+    slug
+}
+
+/// ASCII for the accented Latin letters that turn up in titles; `None` for anything else.
+fn strip_diacritic(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'ł' | 'ľ' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'œ' => "oe",
+        'ř' => "r",
+        'ß' => "ss",
+        'ś' | 'š' | 'ş' => "s",
+        'ť' | 'ţ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+/// A file name for a post titled `title`, roughly the inverse of `title_from_slug`: lowercase,
+/// accents and apostrophes stripped, letters and digits in any script kept, and every other run
+/// of characters turned into a single `-`. Empty when the title has no letters or digits.
+fn slug_from_title(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if let Some(plain) = strip_diacritic(c) {
+            slug.push_str(plain);
+        } else if c.is_alphanumeric() {
+            slug.push(c);
+        } else if c == '\'' || c == '’' {
+            // "Don't" is `dont`, not `don-t`.
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+fn title_from_slug(slug: &str) -> String {
+    slug.replace(['-', '_'], " ")
+        .split_whitespace()
+        .map(capitalize)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn date_from_slug(slug: &str) -> Option<String> {
+    if slug.len() >= 10
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '_' || c == '-')
+    {
+        let normalized = slug.replace('_', "-");
+        return Some(normalized);
+    }
+    None
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SpanStyle {
+    Plain,
+    Dim,
+    FootnoteRef(usize),
+    FootnoteDef(usize),
+    Code,
+    /// Link text, underlined; its `[n]` marker follows as a separate `Dim` span.
+    Link,
+    Added,
+    Removed,
+}
+
+#[derive(Clone)]
+struct Span {
+    text: String,
+    style: SpanStyle,
+    highlight: bool,
+    bold: bool,
+    italic: bool,
+}
+
+impl Span {
+    fn new(text: impl Into<String>, style: SpanStyle) -> Span {
+        Span {
+            text: text.into(),
+            style,
+            highlight: false,
+            bold: false,
+            italic: false,
+        }
+    }
+
+    /// The same styling over different text, used when a span is split.
+    fn with_text(&self, text: impl Into<String>) -> Span {
+        Span {
+            text: text.into(),
+            ..self.clone()
+        }
+    }
+}
+
+/// One wrapped row of the content pane.
+#[derive(Clone, Default)]
+struct StyledLine {
+    spans: Vec<Span>,
+    kind: LineKind,
+}
+
+/// Line-level styling that applies on top of the spans.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum LineKind {
+    #[default]
+    Body,
+    Heading(u8),
+    /// Printed behind a `│ ` gutter, which the wrap width already accounts for.
+    Quote,
+}
+
+struct Footnote {
+    label: String,
+    text: String,
+}
+
+/// Wraps a post body for the content pane, moving footnote definitions into a trailing
+/// "Notes" section numbered in order of first reference, then listing link URLs under
+/// "Links", numbered on from the notes so every `[n]` in a post is unique. Occurrences of
+/// `highlight` are marked before wrapping, so a match broken across lines stays marked on both.
+fn layout_post_body(body: &str, width: usize, highlight: &str) -> Vec<StyledLine> {
+    let (text, notes) = extract_footnotes(body);
+    let mut links = Vec::new();
+    let mut lines = wrap_markdown(&text, width, &notes, &mut links, highlight);
+
+    if !notes.is_empty() {
+        push_section_heading(&mut lines, "── Notes ──");
+        for (index, note) in notes.iter().enumerate() {
+            let number = index + 1;
+            let prefix = format!("[{}] ", number);
+            let spans = highlight_spans(parse_inline(&note.text, &notes, &mut links), highlight);
+            let mut wrapped = wrap_line(&spans, width, &prefix);
+            if let Some(label) = wrapped.first_mut().and_then(|line| line.spans.first_mut()) {
+                label.style = SpanStyle::FootnoteDef(number);
+            }
+            lines.extend(wrapped);
+        }
+    }
+
+    if !links.is_empty() {
+        push_section_heading(&mut lines, "── Links ──");
+        for (index, url) in links.iter().enumerate() {
+            let prefix = format!("[{}] ", notes.len() + index + 1);
+            let spans = highlight_spans(vec![Span::new(url.as_str(), SpanStyle::Plain)], highlight);
+            let mut wrapped = wrap_line(&spans, width, &prefix);
+            if let Some(label) = wrapped.first_mut().and_then(|line| line.spans.first_mut()) {
+                label.style = SpanStyle::Dim;
+            }
+            lines.extend(wrapped);
+        }
+    }
+    lines
+}
+
+/// Starts a trailing section after exactly one blank line.
+fn push_section_heading(lines: &mut Vec<StyledLine>, heading: &str) {
+    while lines.last().is_some_and(|line| line.spans.is_empty()) {
+        lines.pop();
+    }
+    lines.push(StyledLine::default());
+    lines.push(StyledLine {
+        spans: vec![Span::new(heading, SpanStyle::Dim)],
+        kind: LineKind::Body,
+    });
+}
+
+/// Pulls `[^label]: text` definitions (plus indented continuation lines) out of the body,
+/// wherever they appear. Notes are ordered by first reference, then unreferenced definitions.
+fn extract_footnotes(body: &str) -> (String, Vec<Footnote>) {
+    let mut text = String::with_capacity(body.len());
+    let mut definitions: Vec<Footnote> = Vec::new();
+    let mut in_fence = false;
+    let mut in_definition = false;
+    let mut removed = false;
+
+    for line in body.lines() {
+        if is_fence_line(line) {
+            in_fence = !in_fence;
+        }
+        if !in_fence {
+            if let Some((label, rest)) = parse_footnote_definition(line) {
+                definitions.push(Footnote {
+                    label: label.to_string(),
+                    text: rest.trim().to_string(),
+                });
+                in_definition = true;
+                removed = true;
+                continue;
+            }
+            if in_definition && line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+                if let Some(note) = definitions.last_mut() {
+                    note.text.push(' ');
+                    note.text.push_str(line.trim());
+                }
+                continue;
+            }
+        }
+        // Don't leave a double gap where a definition used to sit between paragraphs.
+        if removed && line.trim().is_empty() && (text.is_empty() || text.ends_with("\n\n")) {
+            continue;
+        }
+        in_definition = false;
+        removed = false;
+        text.push_str(line);
+        text.push('\n');
+    }
+
+    let mut ordered = Vec::with_capacity(definitions.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("[^") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find(']') else {
+            break;
+        };
+        let label = &after[..end];
+        if let Some(pos) = definitions.iter().position(|note| note.label == label) {
+            ordered.push(definitions.remove(pos));
+        }
+        rest = &after[end + 1..];
+    }
+    ordered.extend(definitions);
+
+    (text, ordered)
+}
+
+fn parse_footnote_definition(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("[^")?;
+    let end = rest.find("]:")?;
+    let label = &rest[..end];
+    if label.is_empty() || label.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((label, &rest[end + 2..]))
+}
+
+/// Splits a line of prose into styled spans. Footnote references with a known definition
+/// become `[n]` markers, and `[text](url)` links keep only their text followed by a marker
+/// whose URL is appended to `links` (numbered after the footnotes). Images become an
+/// `[image: caption]` placeholder. Anything unrecognised stays as plain text.
+fn parse_inline(text: &str, notes: &[Footnote], links: &mut Vec<String>) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while let Some((start, link)) = find_link(rest) {
+        push_prose(&rest[..start], notes, &mut spans);
+        if link.image {
+            spans.push(Span::new(
+                format!("[image: {}]", image_caption(&link)),
+                SpanStyle::Dim,
+            ));
+            rest = &rest[link.end..];
+            continue;
+        }
+        // Repeated URLs share a number.
+        let index = links
+            .iter()
+            .position(|url| url == link.url)
+            .unwrap_or_else(|| {
+                links.push(link.url.to_string());
+                links.len() - 1
+            });
+        let number = notes.len() + index + 1;
+        let label = if link.text.trim().is_empty() {
+            link.url
+        } else {
+            link.text
+        };
+        let mut runs = Vec::new();
+        parse_emphasis(label, false, false, &mut runs);
+        spans.extend(runs.into_iter().map(|(run, bold, italic)| Span {
+            bold,
+            italic,
+            ..Span::new(run, SpanStyle::Link)
+        }));
+        spans.push(Span::new(format!("[{}]", number), SpanStyle::Dim));
+        rest = &rest[link.end..];
+    }
+    push_prose(rest, notes, &mut spans);
+    spans
+}
+
+/// An image's alt text, or its file name when the alt text is empty.
+fn image_caption(link: &InlineLink) -> String {
+    if !link.text.trim().is_empty() {
+        return link.text.trim().to_string();
+    }
+    let path = link.url.split(['?', '#']).next().unwrap_or(link.url);
+    path.rsplit('/').next().unwrap_or(path).to_string()
+}
+
+/// Emphasis and footnote references for text outside links.
+fn push_prose(text: &str, notes: &[Footnote], spans: &mut Vec<Span>) {
+    let mut runs = Vec::new();
+    parse_emphasis(text, false, false, &mut runs);
+    for (run, bold, italic) in runs {
+        spans.extend(
+            parse_footnote_refs(&run, notes)
+                .into_iter()
+                .map(|span| Span {
+                    bold,
+                    italic,
+                    ..span
+                }),
+        );
+    }
+}
+
+struct InlineLink<'a> {
+    text: &'a str,
+    url: &'a str,
+    /// `![alt](url)` rather than `[text](url)`.
+    image: bool,
+    /// Byte offset just past the closing `)`.
+    end: usize,
+}
+
+/// The first well-formed `[text](url)` or `![alt](url)` in `text`, with the byte offset where
+/// it starts. Brackets may nest in the text and parentheses may nest in the URL; an optional
+/// `"title"` after the URL is dropped. Footnote references (`[^1]`) are never links.
+fn find_link(text: &str) -> Option<(usize, InlineLink<'_>)> {
+    let mut search = 0;
+    while let Some(offset) = text[search..].find('[') {
+        let open = search + offset;
+        if let Some(mut link) = parse_link_at(text, open) {
+            link.image = text[..open].ends_with('!');
+            let start = if link.image { open - 1 } else { open };
+            return Some((start, link));
+        }
+        search = open + 1;
+    }
+    None
+}
+
+fn parse_link_at(text: &str, open: usize) -> Option<InlineLink<'_>> {
+    let label_start = open + 1;
+    if text[label_start..].starts_with('^') {
+        return None;
+    }
+    let close = matching_close(text, label_start, '[', ']')?;
+    let target_start = close + 1;
+    if !text[target_start..].starts_with('(') {
+        return None;
+    }
+    let target_end = matching_close(text, target_start + 1, '(', ')')?;
+    let target = text[target_start + 1..target_end].trim();
+    let url = match target.strip_prefix('<') {
+        Some(inner) => &inner[..inner.find('>')?],
+        None => target.split_whitespace().next().unwrap_or(""),
+    };
+    if url.is_empty() {
+        return None;
+    }
+    Some(InlineLink {
+        text: &text[label_start..close],
+        url,
+        image: false,
+        end: target_end + 1,
+    })
+}
+
+/// Byte offset of the `close` that balances an `open` just before `start`.
+fn matching_close(text: &str, start: usize, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (index, ch) in text[start..].char_indices() {
+        if ch == open {
+            depth += 1;
+        } else if ch == close {
+            if depth == 0 {
+                return Some(start + index);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Splits `text` on balanced `**strong**` / `__strong__` and `*em*` / `_em_` markers into
+/// `(text, bold, italic)` runs, stripping the markers. A marker without a partner on the same
+/// line stays literal, and `_` inside a word (`snake_case`) is never a marker.
+fn parse_emphasis(text: &str, bold: bool, italic: bool, runs: &mut Vec<(String, bool, bool)>) {
+    let mut plain = String::new();
+    let mut index = 0;
+    while let Some(ch) = text[index..].chars().next() {
+        if ch != '*' && ch != '_' {
+            plain.push(ch);
+            index += ch.len_utf8();
+            continue;
+        }
+        let marker_len = if text[index + 1..].starts_with(ch) {
+            2
+        } else {
+            1
+        };
+        match find_emphasis_close(text, index, marker_len) {
+            Some(close) => {
+                if !plain.is_empty() {
+                    runs.push((std::mem::take(&mut plain), bold, italic));
+                }
+                parse_emphasis(
+                    &text[index + marker_len..close],
+                    bold || marker_len == 2,
+                    italic || marker_len == 1,
+                    runs,
+                );
+                index = close + marker_len;
+            }
+            None => {
+                plain.push_str(&text[index..index + marker_len]);
+                index += marker_len;
+            }
+        }
+    }
+    if !plain.is_empty() {
+        runs.push((plain, bold, italic));
+    }
+}
+
+/// Byte offset of the marker closing the `marker_len`-long run of `*` or `_` at `open`, if the
+/// run can open emphasis at all. Closers follow CommonMark's flanking rules loosely: no space
+/// just inside either marker, and `_` must not touch a letter on the outside.
+fn find_emphasis_close(text: &str, open: usize, marker_len: usize) -> Option<usize> {
+    let marker = &text[open..open + marker_len];
+    let ch = marker.chars().next()?;
+    let inner_start = open + marker_len;
+    let first_inner = text[inner_start..].chars().next()?;
+    let before_open = text[..open].chars().next_back();
+    if first_inner.is_whitespace() || (ch == '_' && before_open.is_some_and(char::is_alphanumeric))
+    {
+        return None;
+    }
+
+    let mut search = inner_start;
+    while let Some(offset) = text[search..].find(marker) {
+        let run_start = search + offset;
+        let run = text[run_start..].chars().take_while(|&c| c == ch).count();
+        // In `***`, the closer is the last `marker_len` characters of the run.
+        let close = run_start + run.saturating_sub(marker_len);
+        let before_close = text[..close].chars().next_back();
+        let after_close = text[close + marker_len..].chars().next();
+        let valid = close > inner_start
+            && (marker_len == 2 || run == 1)
+            && before_close.is_some_and(|c| !c.is_whitespace())
+            && !(ch == '_' && after_close.is_some_and(char::is_alphanumeric));
+        if valid {
+            return Some(close);
+        }
+        search = run_start + run;
+    }
+    None
+}
+
+/// Turns `[^label]` references to known footnotes into numbered reference spans.
+fn parse_footnote_refs(text: &str, notes: &[Footnote]) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("[^") {
+        plain.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let number = after.find(']').and_then(|end| {
+            let label = &after[..end];
+            notes
+                .iter()
+                .position(|note| note.label == label)
+                .map(|index| (index + 1, end))
+        });
+        match number {
+            Some((number, end)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::new(std::mem::take(&mut plain), SpanStyle::Plain));
+                }
+                spans.push(Span::new(
+                    format!("[{}]", number),
+                    SpanStyle::FootnoteRef(number),
+                ));
+                rest = &after[end + 1..];
+            }
+            None => {
+                plain.push_str("[^");
+                rest = after;
+            }
+        }
+    }
+    plain.push_str(rest);
+    if !plain.is_empty() {
+        spans.push(Span::new(plain, SpanStyle::Plain));
+    }
+    spans
+}
+
+/// Pairs each line holding a footnote reference with the line of that note's definition.
+fn footnote_jumps(lines: &[StyledLine]) -> Vec<(usize, usize)> {
+    let definitions: HashMap<usize, usize> = lines
+        .iter()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            line.spans.iter().filter_map(move |span| match span.style {
+                SpanStyle::FootnoteDef(number) => Some((number, index)),
+                _ => None,
+            })
+        })
+        .collect();
+
+    lines
+        .iter()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            line.spans.iter().filter_map(move |span| match span.style {
+                SpanStyle::FootnoteRef(number) => Some((index, number)),
+                _ => None,
+            })
+        })
+        .filter_map(|(index, number)| definitions.get(&number).map(|&target| (index, target)))
+        .collect()
+}
+
+/// Splits spans so every case-insensitive occurrence of `query` is its own highlighted span.
+fn highlight_spans(spans: Vec<Span>, query: &str) -> Vec<Span> {
+    let query = query.trim();
+    if query.is_empty() {
+        return spans;
+    }
+
+    let mut output = Vec::with_capacity(spans.len());
+    for span in spans {
+        let mut rest = span.text.as_str();
+        while let Some((start, end)) = find_ignore_case(rest, query) {
+            if start > 0 {
+                output.push(span.with_text(&rest[..start]));
+            }
+            let mut hit = span.with_text(&rest[start..end]);
+            hit.highlight = true;
+            output.push(hit);
+            rest = &rest[end..];
+        }
+        if !rest.is_empty() {
+            output.push(span.with_text(rest));
+        }
+    }
+    output
+}
+
+/// Indices of wrapped lines that contain a highlighted match, for `n`/`N`.
+fn match_lines(lines: &[StyledLine]) -> Vec<usize> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.spans.iter().any(|span| span.highlight))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn wrap_markdown(
+    text: &str,
+    width: usize,
+    notes: &[Footnote],
+    links: &mut Vec<String>,
+    highlight: &str,
+) -> Vec<StyledLine> {
+    let mut lines = Vec::new();
+    let width = width.max(10);
+    let mut in_fence = false;
+    let mut previous_blank = true;
+    let mut raw_lines = text.lines().peekable();
+    while let Some(raw) = raw_lines.next() {
+        // Fence markers are dropped; everything between them is shown verbatim, clipped rather
+        // than wrapped. A fence left open simply runs to the end of the body.
+        if is_fence_line(raw) {
+            in_fence = !in_fence;
+            previous_blank = false;
+            continue;
+        }
+        let indented_code = !in_fence
+            && (raw.starts_with("    ") || raw.starts_with('\t'))
+            && !raw.trim().is_empty()
+            && (previous_blank || lines.last().is_some_and(is_code_line))
+            && !is_list_item(raw.trim_start());
+        if in_fence || indented_code {
+            let code = raw.trim_end().replace('\t', "    ");
+            let code = if indented_code {
+                code.strip_prefix("    ").unwrap_or(&code).to_string()
+            } else {
+                code
+            };
+            lines.push(StyledLine {
+                spans: highlight_spans(vec![Span::new(code, SpanStyle::Code)], highlight),
+                kind: LineKind::Body,
+            });
+            previous_blank = false;
+            continue;
+        }
+
+        if raw.trim().is_empty() {
+            lines.push(StyledLine::default());
+            previous_blank = true;
+            continue;
+        }
+        previous_blank = false;
+
+        // A pipe table needs its separator row right under the header; it runs until the first
+        // line without a pipe.
+        if let Some(aligns) = raw_lines
+            .peek()
+            .filter(|_| raw.contains('|'))
+            .and_then(|next| table_separator(next))
+            .filter(|aligns| aligns.len() == table_cells(raw).len())
+        {
+            raw_lines.next();
+            let mut rows = vec![raw];
+            while let Some(row) =
+                raw_lines.next_if(|line| line.contains('|') && !line.trim().is_empty())
+            {
+                rows.push(row);
+            }
+            lines.extend(layout_table(&rows, &aligns, width, notes, links, highlight));
+            continue;
+        }
+
+        let trimmed = raw.trim_end();
+        if let Some(content) = strip_quote(trimmed) {
+            // Nested quotes are flattened to one level.
+            let spans = highlight_spans(parse_inline(content, notes, links), highlight);
+            let wrapped = if content.is_empty() {
+                vec![StyledLine::default()]
+            } else {
+                wrap_line(&spans, width.saturating_sub(2), "")
+            };
+            lines.extend(wrapped.into_iter().map(|line| StyledLine {
+                kind: LineKind::Quote,
+                ..line
+            }));
+        } else if let Some((level, content)) = parse_heading(trimmed) {
+            let spans = highlight_spans(parse_inline(content, notes, links), highlight);
+            lines.extend(
+                wrap_line(&spans, width, "")
+                    .into_iter()
+                    .map(|line| StyledLine {
+                        kind: LineKind::Heading(level),
+                        ..line
+                    }),
+            );
+        } else {
+            // Leading indentation (nested bullets, item continuations) is kept, capped so deep
+            // nesting still leaves room for text; the wrap width already counts it.
+            let indent = " ".repeat(indent_columns(raw).min(width / 2));
+            let text = trimmed.trim_start();
+            match list_marker(text) {
+                Some(marker) => {
+                    let prefix = format!("{}{}", indent, &text[..marker]);
+                    let content = text[marker..].trim();
+                    let spans = highlight_spans(parse_inline(content, notes, links), highlight);
+                    lines.extend(wrap_line(&spans, width, &prefix));
+                }
+                None => {
+                    let spans = highlight_spans(parse_inline(text, notes, links), highlight);
+                    lines.extend(wrap_line(&spans, width, &indent));
+                }
+            }
+        }
+    }
+    lines
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TableAlign {
+    Left,
+    Center,
+    Right,
+}
+
+/// Column alignments from a table's `| --- | :---: | ---: |` row, or `None` if `line` isn't one.
+fn table_separator(line: &str) -> Option<Vec<TableAlign>> {
+    if !line.contains('|') {
+        return None;
+    }
+    table_cells(line)
+        .into_iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|ch| ch == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => TableAlign::Center,
+                (false, true) => TableAlign::Right,
+                _ => TableAlign::Left,
+            })
+        })
+        .collect()
+}
+
+/// Trimmed cells of a table row; outer pipes are optional.
+fn table_cells(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(str::trim).collect()
+}
+
+/// Lays out a pipe table as aligned columns with a bold header. Rows are never wrapped: columns
+/// that don't fit in `width` are dropped from the right (marked with `…`), and a single column
+/// wider than the pane is clipped when printed.
+fn layout_table(
+    rows: &[&str],
+    aligns: &[TableAlign],
+    width: usize,
+    notes: &[Footnote],
+    links: &mut Vec<String>,
+    highlight: &str,
+) -> Vec<StyledLine> {
+    const GAP: &str = " │ ";
+    let cells: Vec<Vec<Vec<Span>>> = rows
+        .iter()
+        .map(|row| {
+            let mut row_cells = table_cells(row);
+            row_cells.resize(aligns.len(), "");
+            row_cells
+                .into_iter()
+                .map(|cell| highlight_spans(parse_inline(cell, notes, links), highlight))
+                .collect()
+        })
+        .collect();
+    let cell_width =
+        |cell: &[Span]| -> usize { cell.iter().map(|span| display_width(&span.text)).sum() };
+    let widths: Vec<usize> = (0..aligns.len())
+        .map(|column| {
+            cells
+                .iter()
+                .map(|row| cell_width(&row[column]))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut shown = 0;
+    let mut used = 0;
+    for (column, column_width) in widths.iter().enumerate() {
+        let needed = column_width + if column == 0 { 0 } else { GAP.chars().count() };
+        if column > 0 && used + needed > width {
+            break;
+        }
+        used += needed;
+        shown += 1;
+    }
+    let dropped = shown < aligns.len();
+
+    let mut lines = Vec::with_capacity(cells.len() + 1);
+    for (index, row) in cells.into_iter().enumerate() {
+        let mut spans = Vec::new();
+        for (column, cell) in row.into_iter().take(shown).enumerate() {
+            if column > 0 {
+                spans.push(Span::new(GAP, SpanStyle::Dim));
+            }
+            let padding = widths[column].saturating_sub(cell_width(&cell));
+            let (before, mut after) = match aligns[column] {
+                TableAlign::Left => (0, padding),
+                TableAlign::Center => (padding / 2, padding - padding / 2),
+                TableAlign::Right => (padding, 0),
+            };
+            if column + 1 == shown && !dropped {
+                after = 0;
+            }
+            spans.push(Span::new(" ".repeat(before), SpanStyle::Plain));
+            spans.extend(cell.into_iter().map(|span| Span {
+                bold: span.bold || index == 0,
+                ..span
+            }));
+            spans.push(Span::new(" ".repeat(after), SpanStyle::Plain));
+        }
+        if dropped {
+            spans.push(Span::new(" …", SpanStyle::Dim));
+        }
+        spans.retain(|span| !span.text.is_empty());
+        lines.push(StyledLine {
+            spans,
+            kind: LineKind::Body,
+        });
+        if index == 0 {
+            let rule = widths
+                .iter()
+                .take(shown)
+                .map(|column_width| "─".repeat(*column_width))
+                .collect::<Vec<_>>()
+                .join("─┼─");
+            lines.push(StyledLine {
+                spans: vec![Span::new(rule, SpanStyle::Dim)],
+                kind: LineKind::Body,
+            });
+        }
+    }
+    lines
+}
+
+/// The text of a `> quote` line with every leading `>` removed.
+fn strip_quote(line: &str) -> Option<&str> {
+    let mut rest = line.trim_start().strip_prefix('>')?;
+    while let Some(inner) = rest.trim_start().strip_prefix('>') {
+        rest = inner;
+    }
+    Some(rest.trim())
+}
+
+fn is_code_line(line: &StyledLine) -> bool {
+    line.spans
+        .first()
+        .is_some_and(|span| span.style == SpanStyle::Code)
+}
+
+/// Width of a line's leading whitespace, with tabs advancing to the next multiple of four so
+/// mixed tab and space indentation nests consistently.
+fn indent_columns(line: &str) -> usize {
+    line.chars()
+        .take_while(|ch| *ch == ' ' || *ch == '\t')
+        .fold(0, |columns, ch| match ch {
+            '\t' => (columns / 4 + 1) * 4,
+            _ => columns + 1,
+        })
+}
+
+fn is_list_item(line: &str) -> bool {
+    list_marker(line).is_some()
+}
+
+/// Byte length of a list marker and its space (`- `, `* `, `+ `, `1. `, `12) `), so wrapped
+/// continuation lines can hang under the item text.
+fn list_marker(line: &str) -> Option<usize> {
+    if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+        return Some(2);
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+    ((1..=9).contains(&digits) && (rest.starts_with(". ") || rest.starts_with(") ")))
+        .then_some(digits + 2)
+}
+
+/// Splits an ATX heading (`## Title ##`) into its level and text. `#tag` without a space is
+/// not a heading.
+fn parse_heading(line: &str) -> Option<(u8, &str)> {
+    let line = line.trim_start();
+    let level = line.chars().take_while(|&ch| ch == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    let text = if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        without_closing.trim_end()
+    } else {
+        text
+    };
+    Some((level as u8, text))
+}
+
+/// Rows being filled by `wrap_line`, with `used` counted in terminal columns.
+struct RowBuilder<'a> {
+    lines: Vec<StyledLine>,
+    current: StyledLine,
+    used: usize,
+    has_text: bool,
+    prefix: &'a str,
+    indent: &'a str,
+}
+
+impl RowBuilder<'_> {
+    /// Begins a row with the list marker on the first row and the hanging indent after.
+    fn start(&mut self) {
+        let lead = if self.lines.is_empty() {
+            self.prefix
+        } else {
+            self.indent
+        };
+        if !lead.is_empty() {
+            self.current.spans.push(Span::new(lead, SpanStyle::Plain));
+        }
+        self.used = display_width(lead);
+        self.has_text = false;
+    }
+
+    fn next_row(&mut self) {
+        self.lines.push(std::mem::take(&mut self.current));
+        self.start();
+    }
+
+    fn push_text(&mut self, word: &[Span], separator: &str) {
+        if !separator.is_empty() {
+            self.current
+                .spans
+                .push(Span::new(separator, SpanStyle::Plain));
+        }
+        self.current.spans.extend(word.iter().cloned());
+        self.used += display_width(separator)
+            + word
+                .iter()
+                .map(|span| display_width(&span.text))
+                .sum::<usize>();
+        self.has_text = true;
+    }
+
+    /// Places a word longer than a whole row, as with unspaced Hangul or CJK runs or a long
+    /// URL: fills the rest of this row and carries on below, cutting between characters. Only
+    /// the rows are cut; the post text links are read from is untouched.
+    fn break_word(&mut self, word: &[Span], width: usize) {
+        if self.has_text {
+            if self.used + 2 > width {
+                self.next_row();
+            } else {
+                self.current.spans.push(Span::new(" ", SpanStyle::Plain));
+                self.used += 1;
+            }
+        }
+        for span in word {
+            let mut piece = String::new();
+            for cluster in clusters(&span.text) {
+                let cluster_len = cluster_width(cluster);
+                if self.used + cluster_len > width && (self.has_text || !piece.is_empty()) {
+                    if !piece.is_empty() {
+                        self.current
+                            .spans
+                            .push(span.with_text(std::mem::take(&mut piece)));
+                    }
+                    self.next_row();
+                }
+                piece.push_str(cluster);
+                self.used += cluster_len;
+                self.has_text = true;
+            }
+            if !piece.is_empty() {
+                self.current.spans.push(span.with_text(piece));
+            }
+        }
+    }
+}
+
+/// Greedy word wrap over styled spans, measured in terminal columns. A "word" may mix styles
+/// (e.g. `text[1]`), words wider than a row are cut between characters, and continuation lines
+/// get a hanging indent the width of `prefix`.
+fn wrap_line(spans: &[Span], width: usize, prefix: &str) -> Vec<StyledLine> {
+    let mut words: Vec<Vec<Span>> = Vec::new();
+    let mut word: Vec<Span> = Vec::new();
+    for span in spans {
+        let mut piece = String::new();
+        for ch in span.text.chars() {
+            if ch.is_whitespace() {
+                if !piece.is_empty() {
+                    word.push(span.with_text(std::mem::take(&mut piece)));
+                }
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            } else {
+                piece.push(ch);
+            }
+        }
+        if !piece.is_empty() {
+            word.push(span.with_text(piece));
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    let indent = " ".repeat(display_width(prefix));
+    let mut rows = RowBuilder {
+        lines: Vec::new(),
+        current: StyledLine::default(),
+        used: 0,
+        has_text: false,
+        prefix,
+        indent: &indent,
+    };
+    rows.start();
+
+    for word in words {
+        let word_len = word
+            .iter()
+            .map(|span| display_width(&span.text))
+            .sum::<usize>();
+        if rows.has_text && rows.used + 1 + word_len <= width {
+            rows.push_text(&word, " ");
+            continue;
+        }
+        if !rows.has_text && rows.used + word_len <= width {
+            rows.push_text(&word, "");
+            continue;
+        }
+        if rows.has_text && display_width(&indent) + word_len <= width {
+            rows.next_row();
+            rows.push_text(&word, "");
+            continue;
+        }
+        rows.break_word(&word, width);
+    }
+
+    let RowBuilder {
+        mut lines, current, ..
+    } = rows;
+    if !current.spans.is_empty() {
+        lines.push(current);
+    }
+
+    if lines.is_empty() {
+        lines.push(StyledLine {
+            spans: vec![Span::new(prefix, SpanStyle::Plain)],
+            kind: LineKind::Body,
+        });
+    }
+
+    lines
+}
+
+/// Fits `text` into `max_width` terminal columns, ending with `…` when it had to be cut. Cuts
+/// fall between clusters, so a wide character is never halved and accents stay on their letter.
+fn clamp_text(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    let mut clipped = take_columns(text, max_width.saturating_sub(1));
+    if max_width > 0 {
+        clipped.push('…');
+    }
+    clipped
+}
+
+/// A `›`-marked list row clamped to `width` columns. Selected rows are padded out to the full
+/// width so the highlight is one even bar whatever the label's length.
+fn list_row(label: &str, width: usize, selected: bool) -> String {
+    let marker = if selected { "›" } else { " " };
+    let row = format!("{} {}", marker, clamp_text(label, width.saturating_sub(2)));
+    if !selected {
+        return row;
+    }
+    let padding = width.saturating_sub(display_width(&row));
+    format!("{}{}", row, " ".repeat(padding))
+}
+
+/// The longest leading part of `text` that fits in `columns`, cut between clusters.
+fn take_columns(text: &str, columns: usize) -> String {
+    let mut taken = String::new();
+    let mut used = 0;
+    for cluster in clusters(text) {
+        used += cluster_width(cluster);
+        if used > columns {
+            break;
+        }
+        taken.push_str(cluster);
+    }
+    taken
+}
+
+/// Columns `text` takes up in a terminal.
+fn display_width(text: &str) -> usize {
+    clusters(text).map(cluster_width).sum()
+}
+
+/// A zero-width-joiner sequence (👨‍👩‍👧) draws as one glyph as wide as its first emoji.
+fn cluster_width(cluster: &str) -> usize {
+    if cluster.contains('\u{200D}') {
+        cluster.chars().next().map_or(0, char_width)
+    } else {
+        cluster.chars().map(char_width).sum()
+    }
+}
+
+/// Terminal columns for one character: 2 for East Asian wide characters (Hangul, CJK, kana,
+/// fullwidth forms) and emoji, 0 for combining marks, joiners, variation selectors and controls,
+/// 1 for everything else. A hand-kept table of the common ranges rather than the full Unicode
+/// data, which is plenty for titles and prose.
+fn char_width(ch: char) -> usize {
+    let code = ch as u32;
+    if ch.is_control() {
+        return 0;
+    }
+    let zero = [
+        0x0300..=0x036F,
+        0x0483..=0x0489,
+        0x0591..=0x05BD,
+        0x0610..=0x061A,
+        0x064B..=0x065F,
+        0x1160..=0x11FF,
+        0x1AB0..=0x1AFF,
+        0x1DC0..=0x1DFF,
+        0x200B..=0x200F,
+        0x20D0..=0x20FF,
+        0xD7B0..=0xD7FF,
+        0xFE00..=0xFE0F,
+        0xFE20..=0xFE2F,
+        0xFEFF..=0xFEFF,
+        0x1F3FB..=0x1F3FF,
+        0xE0000..=0xE01EF,
+    ];
+    if zero.iter().any(|range| range.contains(&code)) {
+        return 0;
+    }
+    let wide = [
+        0x1100..=0x115F,
+        0x231A..=0x231B,
+        0x23E9..=0x23EC,
+        0x25FD..=0x25FE,
+        0x2614..=0x2615,
+        0x26A1..=0x26A1,
+        0x26AA..=0x26AB,
+        0x26BD..=0x26BE,
+        0x26C4..=0x26C5,
+        0x26D4..=0x26D4,
+        0x26EA..=0x26EA,
+        0x26F2..=0x26F5,
+        0x26FA..=0x26FD,
+        0x2705..=0x2705,
+        0x270A..=0x270B,
+        0x2728..=0x2728,
+        0x274C..=0x274C,
+        0x2753..=0x2757,
+        0x2795..=0x2797,
+        0x2B1B..=0x2B1C,
+        0x2B50..=0x2B55,
+        0x2E80..=0x303E,
+        0x3041..=0x33FF,
+        0x3400..=0x4DBF,
+        0x4E00..=0x9FFF,
+        0xA000..=0xA4CF,
+        0xA960..=0xA97F,
+        0xAC00..=0xD7A3,
+        0xF900..=0xFAFF,
+        0xFE30..=0xFE4F,
+        0xFF00..=0xFF60,
+        0xFFE0..=0xFFE6,
+        0x1F004..=0x1F004,
+        0x1F18E..=0x1F18E,
+        0x1F191..=0x1F19A,
+        0x1F200..=0x1F251,
+        0x1F300..=0x1F64F,
+        0x1F680..=0x1F6FF,
+        0x1F7E0..=0x1F7EB,
+        0x1F90C..=0x1F9FF,
+        0x1FA70..=0x1FAFF,
+        0x20000..=0x3FFFD,
+    ];
+    if wide.iter().any(|range| range.contains(&code)) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Splits `text` into user-perceived characters, closely enough for cutting text: a character
+/// plus any zero-width marks after it, with zero-width-joiner emoji sequences and flag pairs
+/// kept together.
+fn clusters(text: &str) -> impl Iterator<Item = &str> + '_ {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let mut chars = rest.char_indices().peekable();
+        let (_, first) = chars.next()?;
+        let mut previous = first;
+        let mut end = first.len_utf8();
+        while let Some(&(index, ch)) = chars.peek() {
+            let regional = |ch: char| ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch);
+            let joins = char_width(ch) == 0
+                || previous == '\u{200D}'
+                || (regional(ch) && regional(previous) && index == first.len_utf8());
+            if !joins {
+                break;
+            }
+            previous = ch;
+            end = index + ch.len_utf8();
+            chars.next();
+        }
+        let (cluster, tail) = rest.split_at(end);
+        rest = tail;
+        Some(cluster)
+    })
+}
+
+/// A post date in UTC, as `created_at` gives it: a day with an optional time of day.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct PostDate {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl PostDate {
+    const EPOCH: PostDate = PostDate {
+        year: 1970,
+        month: 1,
+        day: 1,
+        hour: 0,
+        minute: 0,
+        second: 0,
+    };
+
+    /// The UTC date and time `seconds` after the Unix epoch.
+    fn from_unix(seconds: i64) -> PostDate {
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let time = seconds.rem_euclid(86_400) as u32;
+        PostDate {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: time / 60 % 60,
+            second: time % 60,
+        }
+    }
+
+    /// `2024-06-03` with `separator` between the parts, as daily logs are named.
+    fn day_slug(&self, separator: char) -> String {
+        format!(
+            "{:04}{}{:02}{}{:02}",
+            self.year, separator, self.month, separator, self.day
+        )
+    }
+
+    /// `Mon, 03 Jun 2024 00:00:00 +0000`, for RSS.
+    fn rfc822(&self) -> String {
+        const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+        const MONTHS: [&str; 12] = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+        let weekday = days_from_civil(self.year, self.month, self.day).rem_euclid(7) as usize;
+        format!(
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} +0000",
+            WEEKDAYS[weekday],
+            self.day,
+            MONTHS[self.month as usize - 1],
+            self.year,
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+
+    /// `2024-06-03T00:00:00Z`, for Atom.
+    fn rfc3339(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// Reads `2024-06-03`, `2024_06_03`, or either followed by a `T` or space and `HH:MM[:SS]`.
+/// Anything after the time (fractions, offsets) is ignored. `None` when there is no real
+/// calendar day at the start.
+fn parse_date(date: &str) -> Option<PostDate> {
+    let date = date.trim();
+    let number = |range: std::ops::Range<usize>| -> Option<u32> {
+        let digits = date.get(range)?;
+        digits
+            .bytes()
+            .all(|byte| byte.is_ascii_digit())
+            .then(|| digits.parse().ok())
+            .flatten()
+    };
+    let separator = |index: usize| matches!(date.as_bytes().get(index), Some(b'-' | b'_'));
+    if !separator(4) || !separator(7) {
+        return None;
+    }
+    let year = number(0..4)? as i64;
+    let month = number(5..7)?;
+    let day = number(8..10)?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+    let mut parsed = PostDate {
+        year,
+        month,
+        day,
+        ..PostDate::EPOCH
+    };
+    if matches!(date.as_bytes().get(10), Some(b'T' | b't' | b' ')) && date.get(13..14) == Some(":")
+    {
+        let hour = number(11..13)?;
+        let minute = number(14..16)?;
+        let second = if date.get(16..17) == Some(":") {
+            number(17..19)?
+        } else {
+            0
+        };
+        if hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+        parsed.hour = hour;
+        parsed.minute = minute;
+        parsed.second = second.min(59);
+    }
+    Some(parsed)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Today's date in the local time zone.
+#[cfg(unix)]
+fn local_today() -> PostDate {
+    // SAFETY: `time` with a null pointer only returns the time, and `localtime_r` writes only
+    // into the `tm` it's given.
+    let tm = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&now, &mut tm).is_null() {
+            return utc_today();
+        }
+        tm
+    };
+    PostDate {
+        year: tm.tm_year as i64 + 1900,
+        month: tm.tm_mon as u32 + 1,
+        day: tm.tm_mday as u32,
+        ..PostDate::EPOCH
+    }
+}
+
+/// Today's date. Without libc there's no time zone database to ask, so this is UTC.
+#[cfg(not(unix))]
+fn local_today() -> PostDate {
+    utc_today()
+}
+
+fn utc_today() -> PostDate {
+    let (year, month, day) = civil_from_days((unix_now() / 86_400) as i64);
+    PostDate {
+        year,
+        month,
+        day,
+        ..PostDate::EPOCH
+    }
+}
+
+/// The inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn format_date(date: &str) -> String {
+    if date.len() >= 10 {
+        date.chars().take(10).collect()
+    } else {
+        date.to_string()
+    }
+}
+
+/// `browser` from the config, set in `main`.
+static BROWSER: OnceLock<Option<String>> = OnceLock::new();
+
+/// Opens `url` with the config's `browser`, else `$BROWSER`, else the platform opener, and
+/// returns the name of the program that ran. Only the spawn is waited for, not the browser.
+fn open_url(url: &str) -> io::Result<String> {
+    assert_off_ui_thread("opening a URL");
+    let configured = BROWSER.get().cloned().flatten();
+    match configured.or_else(|| env::var("BROWSER").ok()) {
+        Some(template) => {
+            let (program, args) = browser_command(&template, url)
+                .ok_or_else(|| io::Error::other("the browser command is empty"))?;
+            spawn_opener(Command::new(&program).args(args))?;
+            Ok(program)
+        }
+        None => open_with_platform(url),
+    }
+}
+
+/// Splits a browser command like `firefox --new-tab` on whitespace into a program and its
+/// arguments. The URL replaces any `%s`, or else goes last. `None` for a blank command.
+fn browser_command(template: &str, url: &str) -> Option<(String, Vec<String>)> {
+    let mut words = template.split_whitespace();
+    let program = words.next()?.replace("%s", url);
+    let mut args: Vec<String> = words.map(|word| word.replace("%s", url)).collect();
+    if !template.contains("%s") {
+        args.push(url.to_string());
+    }
+    Some((program, args))
+}
+
+/// `open`, `start`, or on Linux the first opener that is installed, whatever the user's browser
+/// settings say. Returns the opener's name.
+fn open_with_platform(url: &str) -> io::Result<String> {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // cmd.exe ends the command at a bare `&`, and Rust only quotes arguments with spaces,
+        // so the URL is passed quoted by hand to keep query strings whole.
+        let line = format!("/C start \"\" \"{}\"", url.replace('"', "%22"));
+        spawn_opener(Command::new("cmd").raw_arg(line)).map(|()| "start".to_string())
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        spawn_opener(Command::new("open").arg(url)).map(|()| "open".to_string())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let openers: &[&[&str]] = if is_wsl() {
+            &[&["wslview"], &["cmd.exe", "/C", "start", ""]]
+        } else {
+            &[
+                &["xdg-open"],
+                &["gio", "open"],
+                &["kde-open5"],
+                &["wslview"],
+            ]
+        };
+        for opener in openers {
+            // WSL's interop doesn't quote `&` for cmd.exe either, so it is escaped instead.
+            let target = if opener[0] == "cmd.exe" {
+                cmd_escape(url)
+            } else {
+                url.to_string()
+            };
+            match spawn_opener(Command::new(opener[0]).args(&opener[1..]).arg(target)) {
+                Ok(()) => return Ok(opener[0].to_string()),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        let tried: Vec<&str> = openers.iter().map(|opener| opener[0]).collect();
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no browser opener found; tried {}", tried.join(", ")),
+        ))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "open not supported on this OS",
+        ))
+    }
+}
+
+/// Escapes cmd.exe's special characters with `^` for an unquoted argument.
+#[cfg(target_os = "linux")]
+fn cmd_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '&' | '|' | '<' | '>' | '^' | '(' | ')') {
+            escaped.push('^');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// How long an opener has to fail before it counts as having worked.
+const OPENER_GRACE: Duration = Duration::from_millis(500);
+
+/// Spawns an opener and watches it briefly. Most openers exit at once, and a non-zero exit then
+/// is the only sign the link didn't open. One still running afterwards (a browser started
+/// directly) is left to a thread that waits on it, so it doesn't linger as a zombie. Its output
+/// is dropped so it can't scribble over the UI.
+fn spawn_opener(command: &mut Command) -> io::Result<()> {
+    let name = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(io::Error::other(format!("{} exited with {}", name, status)))
+            };
+        }
+        if started.elapsed() >= OPENER_GRACE {
+            thread::spawn(move || child.wait());
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Windows Subsystem for Linux, where `xdg-open` is usually missing and links have to go to
+/// the Windows side.
+#[cfg(target_os = "linux")]
+fn is_wsl() -> bool {
+    env::var_os("WSL_DISTRO_NAME").is_some()
+        || fs::read_to_string("/proc/version")
+            .is_ok_and(|version| version.to_lowercase().contains("microsoft"))
+}
+
+/// The most base64 a single OSC 52 sequence carries. Some terminals and multiplexers silently
+/// drop larger ones, so bigger copies go through the clipboard tool instead.
+const OSC52_LIMIT: usize = 100_000;
+
+/// Hands `text` to the terminal's clipboard with OSC 52. Written and flushed straight to stdout
+/// rather than queued with a frame, so the next render's clear can't swallow it. There's no
+/// reply to wait for, so `Ok` means it was sent, not that the terminal took it.
+fn copy_with_osc52(text: &str) -> io::Result<()> {
+    let encoded = base64_encode(text.as_bytes());
+    if encoded.len() > OSC52_LIMIT {
+        return Err(io::Error::other("too large for OSC 52"));
+    }
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded)?;
+    stdout.flush()
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let value = chunk.iter().enumerate().fold(0u32, |value, (index, byte)| {
+            value | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(value >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Whether copies try OSC 52 before the clipboard tool. Set in `main` from `Config::osc52` or
+/// `terminal_supports_osc52`.
+fn osc52() -> bool {
+    OSC52.get().copied().unwrap_or(false)
+}
+
+static OSC52: OnceLock<bool> = OnceLock::new();
+
+/// Over SSH the local clipboard tools can't reach the user's machine, so OSC 52 is the only way
+/// there. Locally it's used only on terminals that accept it out of the box; iTerm2, for one,
+/// ignores it unless a setting is turned on.
+fn terminal_supports_osc52() -> bool {
+    let program = env::var("TERM_PROGRAM").unwrap_or_default();
+    let term = env::var("TERM").unwrap_or_default();
+    env::var_os("SSH_TTY").is_some()
+        || env::var_os("SSH_CONNECTION").is_some()
+        || matches!(program.as_str(), "WezTerm" | "ghostty")
+        || term.contains("kitty")
+        || term.contains("foot")
+        || term.contains("alacritty")
+        || env::var_os("KITTY_WINDOW_ID").is_some()
+        || env::var_os("WT_SESSION").is_some()
+}
+
+/// Pipes `text` into the platform clipboard tool; on Linux the first of `wl-copy`, `xclip`,
+/// and `xsel` that is installed.
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    assert_off_ui_thread("copying to the clipboard");
+    let candidates: &[&[&str]] = if cfg!(target_os = "macos") {
+        &[&["pbcopy"]]
+    } else if cfg!(target_os = "windows") {
+        &[&["clip"]]
+    } else {
+        &[
+            &["wl-copy"],
+            &["xclip", "-selection", "clipboard"],
+            &["xsel", "--clipboard", "--input"],
+        ]
+    };
+    for command in candidates {
+        let mut child = match Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child.wait()?;
+        return if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{} exited with {}",
+                command[0], status
+            )))
+        };
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no clipboard tool found",
+    ))
+}
+
+/// Prints `tab/slug` (or bare slugs for a single tab) newest first, for shell completion.
+///
+/// Slugs come from the slug index, so only files changed since the last run have their
+/// frontmatter read, and bodies never are. It never fetches remote content: a missing content
+/// root prints nothing.
+fn print_completion_slugs(config: &Config, tab: Option<&str>) {
+    let Some(root) =
+        find_local_content_root(config).or_else(|| content_cache_dir().filter(|dir| dir.is_dir()))
+    else {
+        return;
+    };
+
+    let dirs: Vec<&str> = match tab {
+        Some(tab) => CONTENT_DIRS
+            .iter()
+            .copied()
+            .filter(|dir| dir.eq_ignore_ascii_case(tab.trim_end_matches('/')))
+            .collect(),
+        None => CONTENT_DIRS.to_vec(),
+    };
+
+    // Index entries are keyed by absolute path, whatever directory this runs from.
+    let root = fs::canonicalize(&root).unwrap_or(root);
+    let (cached, damaged) = load_slug_index();
+    if damaged > 0 {
+        // Completion scripts send stderr nowhere, so this only shows when run by hand.
+        eprintln!(
+            "Skipped {} damaged slug index entr{}; those files were read again.",
+            damaged,
+            if damaged == 1 { "y" } else { "ies" }
+        );
+    }
+    let mut fresh = HashMap::new();
+    let mut lines = Vec::new();
+    for dir in &dirs {
+        let mut entries =
+            completion_entries(&root.join(dir), *dir == "gallery", &cached, &mut fresh);
+        entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        lines.extend(entries.into_iter().map(|(_, slug)| {
+            if tab.is_some() {
+                slug
+            } else {
+                format!("{}/{}", dir, slug)
+            }
+        }));
+    }
+
+    let mut stdout = io::stdout().lock();
+    for line in lines {
+        if writeln!(stdout, "{}", line).is_err() {
+            break;
+        }
+    }
+    // Files gone from the directories just listed drop out of the index.
+    let scanned = dirs.iter().map(|dir| root.join(dir)).collect::<Vec<_>>();
+    let mut index = cached.clone();
+    index.retain(|path, _| {
+        path.parent()
+            .is_none_or(|parent| !scanned.iter().any(|dir| dir == parent))
+    });
+    index.extend(fresh);
+    if index != cached || damaged > 0 {
+        // Completion must never complain mid-typing, so a failed save only costs speed.
+        let _ = save_slug_index(&index);
+    }
+}
+
+/// What `--complete-slugs` read from one markdown file's frontmatter, trusted while the file's
+/// size and modification time stay the same.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct SlugIndexEntry {
+    modified: u64,
+    size: u64,
+    sort_key: String,
+    slug: String,
+    unpublished: bool,
+}
+
+/// The slug index, kept with the other caches in the state directory. Entries are keyed by the
+/// file's absolute path, so every content root shares one file.
+fn slug_index_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("slugs"))
+}
+
+/// The index and how many of its entries were damaged and skipped. A damaged entry is only a
+/// miss: its file is read again and the entry rewritten.
+fn load_slug_index() -> (HashMap<PathBuf, SlugIndexEntry>, usize) {
+    slug_index_path()
+        .and_then(|path| fs::read(path).ok())
+        .map(|bytes| parse_slug_index(&bytes))
+        .unwrap_or_default()
+}
+
+/// One line per file: its length and checksum, then `path, modified, size, unpublished,
+/// sort_key, slug`, tab-separated. A line whose length or checksum doesn't match what follows,
+/// as a torn or corrupted write leaves it, is counted and skipped on its own.
+fn parse_slug_index(bytes: &[u8]) -> (HashMap<PathBuf, SlugIndexEntry>, usize) {
+    let mut index = HashMap::new();
+    let mut damaged = 0;
+    for line in bytes.split(|&byte| byte == b'\n') {
+        if line.is_empty() {
+            continue;
+        }
+        match parse_slug_index_line(line) {
+            Some((path, entry)) => {
+                index.insert(path, entry);
+            }
+            None => damaged += 1,
+        }
+    }
+    (index, damaged)
+}
+
+fn parse_slug_index_line(line: &[u8]) -> Option<(PathBuf, SlugIndexEntry)> {
+    let line = std::str::from_utf8(line).ok()?;
+    let (check, payload) = line.split_once('\t')?;
+    let (length, checksum) = check.split_once(':')?;
+    if usize::from_str_radix(length, 16).ok()? != payload.len()
+        || u64::from_str_radix(checksum, 16).ok()? != fnv1a(payload.as_bytes())
+    {
+        return None;
+    }
+    let mut fields = payload.split('\t');
+    let path = PathBuf::from(fields.next()?);
+    let entry = SlugIndexEntry {
+        modified: fields.next()?.parse().ok()?,
+        size: fields.next()?.parse().ok()?,
+        unpublished: fields.next()? == "1",
+        sort_key: fields.next()?.to_string(),
+        slug: fields.next()?.to_string(),
+    };
+    fields.next().is_none().then_some((path, entry))
+}
+
+fn serialize_slug_index(index: &HashMap<PathBuf, SlugIndexEntry>) -> String {
+    let mut lines = index
+        .iter()
+        .filter_map(|(path, entry)| {
+            let path = path.to_str()?;
+            let fields = [path, &entry.sort_key, &entry.slug];
+            if fields.iter().any(|field| field.contains(['\t', '\n'])) {
+                return None;
+            }
+            let payload = format!(
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                path,
+                entry.modified,
+                entry.size,
+                if entry.unpublished { "1" } else { "0" },
+                entry.sort_key,
+                entry.slug
+            );
+            Some(format!(
+                "{:x}:{:016x}\t{}\n",
+                payload.len(),
+                fnv1a(payload.as_bytes()),
+                payload
+            ))
+        })
+        .collect::<Vec<_>>();
+    lines.sort();
+    lines.concat()
+}
+
+/// Replaces the whole file at once, so another instance completing at the same moment reads
+/// either the old index or the new one.
+fn save_slug_index(index: &HashMap<PathBuf, SlugIndexEntry>) -> io::Result<()> {
+    let path = slug_index_path().ok_or_else(|| io::Error::other("no state directory"))?;
+    if let Some(parent) = path.parent() {
+        create_dirs(parent)?;
+    }
+    write_atomic(&path, serialize_slug_index(index).as_bytes())
+}
+
+/// `(sort_key, slug)` for every post in `dir`. Markdown files are looked up in `cached` by size
+/// and modification time and only read when they've changed; either way their entry goes into
+/// `fresh`.
+fn completion_entries(
+    dir: &Path,
+    is_gallery: bool,
+    cached: &HashMap<PathBuf, SlugIndexEntry>,
+    fresh: &mut HashMap<PathBuf, SlugIndexEntry>,
+) -> Vec<(String, String)> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        let ext = path
+            .extension()
+            .and_then(|value| value.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let Some(slug) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        if is_gallery {
+            if !matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp") {
+                continue;
+            }
+            let sort_key = entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| format!("{:020}", duration.as_secs()))
+                .unwrap_or_else(|| slug.to_string());
+            entries.push((sort_key, slug.to_string()));
+            continue;
+        }
+
+        if ext != "md" || is_section_index(&path) {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_secs());
+        let size = meta.len();
+        let unchanged = cached
+            .get(&path)
+            .filter(|cached| cached.modified == modified && cached.size == size);
+        let indexed = match unchanged {
+            Some(cached) => cached.clone(),
+            None => {
+                let frontmatter = read_frontmatter(&path).unwrap_or_default();
+                SlugIndexEntry {
+                    modified,
+                    size,
+                    sort_key: frontmatter
+                        .get("created_at")
+                        .cloned()
+                        .or_else(|| date_from_slug(slug))
+                        .unwrap_or_else(|| slug.to_string()),
+                    slug: post_slug(slug, &frontmatter),
+                    unpublished: is_unpublished(&frontmatter),
+                }
+            }
+        };
+        fresh.insert(path, indexed.clone());
+        if indexed.unpublished && dir.file_name().and_then(|name| name.to_str()) == Some("essays") {
+            continue;
+        }
+        entries.push((indexed.sort_key, indexed.slug));
+    }
+    entries
+}
+
+/// Reads a content file as text. UTF-8 (with or without a BOM) is taken as is; UTF-16 with a
+/// BOM and Korean EUC-KR/CP949 are converted, the latter with `iconv`. Anything else comes back
+/// empty and marked `Undecodable` rather than as replacement characters.
+fn read_text(path: &Path) -> io::Result<(String, Option<Decoding>)> {
+    assert_off_ui_thread("reading content");
+    let bytes = match String::from_utf8(fs::read(path)?) {
+        Ok(text) => {
+            let text = match text.strip_prefix('\u{feff}') {
+                Some(rest) => rest.to_string(),
+                None => text,
+            };
+            return Ok((text, None));
+        }
+        Err(err) => err.into_bytes(),
+    };
+
+    let utf16 = match bytes.get(..2) {
+        Some([0xff, 0xfe]) => Some(("UTF-16LE", u16::from_le_bytes as fn([u8; 2]) -> u16)),
+        Some([0xfe, 0xff]) => Some(("UTF-16BE", u16::from_be_bytes as fn([u8; 2]) -> u16)),
+        _ => None,
+    };
+    if let Some((name, unit)) = utf16 {
+        let units = bytes[2..]
+            .chunks_exact(2)
+            .map(|pair| unit([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        if let Ok(text) = String::from_utf16(&units) {
+            return Ok((text, Some(Decoding::Transcoded(name))));
+        }
+    }
+
+    if let Some(name) = detect_korean(&bytes) {
+        if let Some(text) = transcode("CP949", &bytes) {
+            return Ok((text, Some(Decoding::Transcoded(name))));
+        }
+    }
+    Ok((String::new(), Some(Decoding::Undecodable)))
+}
+
+/// `Some("EUC-KR")` (or `"CP949"` when its extended lead bytes show up) if every non-ASCII byte
+/// pairs up as Korean and most pairs fall in the KS X 1001 Hangul block. Other legacy encodings
+/// rarely get through both checks.
+fn detect_korean(bytes: &[u8]) -> Option<&'static str> {
+    let (mut pairs, mut hangul, mut extended) = (0, 0, false);
+    let mut index = 0;
+    while index < bytes.len() {
+        let lead = bytes[index];
+        if lead < 0x80 {
+            index += 1;
+            continue;
+        }
+        let trail = *bytes.get(index + 1)?;
+        let strict = (0xa1..=0xfe).contains(&lead) && (0xa1..=0xfe).contains(&trail);
+        let cp949 = (0x81..=0xc6).contains(&lead)
+            && matches!(trail, 0x41..=0x5a | 0x61..=0x7a | 0x81..=0xfe);
+        if !strict && !cp949 {
+            return None;
+        }
+        extended |= !strict;
+        pairs += 1;
+        if (0xb0..=0xc8).contains(&lead) && (0xa1..=0xfe).contains(&trail) {
+            hangul += 1;
+        }
+        index += 2;
+    }
+    (pairs > 0 && hangul * 2 >= pairs).then_some(if extended { "CP949" } else { "EUC-KR" })
+}
+
+/// Converts `bytes` from `encoding` to UTF-8 with `iconv`, or `None` if it's missing or fails.
+fn transcode(encoding: &str, bytes: &[u8]) -> Option<String> {
+    let mut child = Command::new("iconv")
+        .args(["-f", encoding, "-t", "UTF-8"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdin = child.stdin.take()?;
+    let input = bytes.to_vec();
+    // Written from another thread so a large file can't fill both pipes and stall.
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output().ok()?;
+    let written = writer.join().ok()?.is_ok();
+    if !written || !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Reads just the frontmatter block at the top of a markdown file, stopping at the closing `---`.
+fn read_frontmatter(path: &Path) -> io::Result<HashMap<String, String>> {
+    let mut map = HashMap::new();
+    let mut lines = BufReader::new(fs::File::open(path)?).lines();
+    if lines.next().transpose()?.as_deref() != Some("---") {
+        return Ok(map);
+    }
+    for line in lines {
+        let line = line?;
+        if line == "---" {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            map.insert(
+                key.trim().to_string(),
+                clean_frontmatter_value(value.trim()),
+            );
+        }
+    }
+    Ok(map)
+}
+
+fn print_completion_script(shell: Option<&str>) -> io::Result<()> {
+    let script = match shell {
+        Some("bash") => bash_completion(),
+        Some("zsh") => zsh_completion(),
+        Some("fish") => fish_completion(),
+        _ => {
+            eprintln!("Usage: johnjeong --completions <bash|zsh|fish>");
+            std::process::exit(2);
+        }
+    };
+    let mut out = io::stdout().lock();
+    match out.write_all(script.as_bytes()).and_then(|()| out.flush()) {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// What a shell should offer for an option value or positional, going by its name in the
+/// usage line: `tab`, `slug`, a path, or a `a|b|c` list of literal choices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum CompletionKind {
+    Nothing,
+    Tabs,
+    Slugs,
+    Files,
+    Words(Vec<&'static str>),
+}
+
+fn completion_kind(name: &'static str) -> CompletionKind {
+    let name = name.trim_matches(['<', '>', '[', ']', '.']);
+    match name {
+        "tab" => CompletionKind::Tabs,
+        "slug" => CompletionKind::Slugs,
+        "path" | "file" => CompletionKind::Files,
+        _ if name.split('|').any(|part| part == "file") => CompletionKind::Files,
+        _ if name.contains('|') => CompletionKind::Words(name.split('|').collect()),
+        _ => CompletionKind::Nothing,
+    }
+}
+
+fn option_value_kind(spec: &OptionSpec) -> Option<CompletionKind> {
+    match spec.value {
+        OptionValue::Switch => None,
+        OptionValue::Required(name) | OptionValue::Optional(name) => Some(completion_kind(name)),
+    }
+}
+
+/// Every option that takes a value, global or per-command, once each.
+fn valued_options() -> Vec<&'static OptionSpec> {
+    let mut seen = HashSet::new();
+    GLOBAL_OPTIONS
+        .iter()
+        .chain(COMMANDS.iter().flat_map(|command| command.options.iter()))
+        .filter(|spec| !matches!(spec.value, OptionValue::Switch) && seen.insert(spec.flag))
+        .collect()
+}
+
+fn option_words(specs: &[OptionSpec]) -> Vec<&'static str> {
+    specs
+        .iter()
+        .flat_map(|spec| spec.short.into_iter().chain([spec.flag]))
+        .collect()
+}
+
+/// Options offered after a subcommand: its own, then the global ones it also accepts.
+fn command_option_words(command: &CommandSpec) -> String {
+    let mut words = option_words(command.options);
+    for word in option_words(&GLOBAL_OPTIONS) {
+        if !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words.join(" ")
+}
+
+/// `(position, kind)` for each positional of a subcommand that has something to complete.
+fn positional_kinds(command: &CommandSpec) -> Vec<(usize, CompletionKind)> {
+    command
+        .positionals
+        .split_whitespace()
+        .map(completion_kind)
+        .enumerate()
+        .filter(|(_, kind)| *kind != CompletionKind::Nothing)
+        .collect()
+}
+
+fn command_names() -> String {
+    COMMANDS.map(|command| command.name).join(" ")
+}
+
+fn posix_words(words: &str, bash: bool) -> String {
+    if bash {
+        format!("COMPREPLY+=($(compgen -W \"{words}\" -- \"$cur\"))")
+    } else {
+        format!("compadd -- {words}")
+    }
+}
+
+/// The shell statement that adds `kind`'s candidates; bash and zsh share the helper names.
+fn posix_completion(kind: &CompletionKind, bash: bool) -> String {
+    match kind {
+        CompletionKind::Nothing => ":".to_string(),
+        CompletionKind::Tabs => posix_words(&CONTENT_DIRS.join(" "), bash),
+        CompletionKind::Slugs => "_johnjeong_slugs".to_string(),
+        CompletionKind::Files => "_johnjeong_files".to_string(),
+        CompletionKind::Words(choices) => posix_words(&choices.join(" "), bash),
+    }
+}
+
+/// The word loop both bash and zsh scripts use to find the subcommand and count the
+/// positionals before the cursor, skipping option values.
+fn posix_word_scan(words: &str, first: usize, current: &str) -> String {
+    let valued = valued_options()
+        .iter()
+        .map(|spec| spec.flag)
+        .collect::<Vec<_>>()
+        .join("|");
+    format!(
+        r#"    local command="" positional=0 skip="" i word
+    for ((i = {first}; i < {current}; i++)); do
+        word="${{{words}[i]}}"
+        if [[ -n "$skip" ]]; then
+            skip=""
+            continue
+        fi
+        case "$word" in
+            {valued}) skip=1 ;;
+            -*) ;;
+            *)
+                if [[ -z "$command" ]]; then
+                    command="$word"
+                else
+                    positional=$((positional + 1))
+                fi
+                ;;
+        esac
+    done
+"#
+    )
+}
+
+fn posix_completion_body(script: &mut String, previous: &str, prefix: &str, bash: bool) {
+    script.push_str(&format!("    case \"{previous}\" in\n"));
+    for spec in valued_options() {
+        let kind = option_value_kind(spec).unwrap_or(CompletionKind::Nothing);
+        script.push_str(&format!(
+            "        {}) {}; return ;;\n",
+            spec.flag,
+            posix_completion(&kind, bash)
+        ));
+    }
+    script.push_str("    esac\n");
+
+    script.push_str(&format!(
+        "    if [[ {prefix} == -* ]]; then\n        case \"$command\" in\n"
+    ));
+    for command in &COMMANDS {
+        script.push_str(&format!(
+            "            {}) {} ;;\n",
+            command.name,
+            posix_words(&command_option_words(command), bash)
+        ));
+    }
+    script.push_str(&format!(
+        "            *) {} ;;\n        esac\n        return\n    fi\n",
+        posix_words(&option_words(&GLOBAL_OPTIONS).join(" "), bash)
+    ));
+
+    script.push_str("    case \"$command:$positional\" in\n");
+    script.push_str(&format!(
+        "        :0)\n            {}\n            _johnjeong_slugs\n            ;;\n",
+        posix_words(&command_names(), bash)
+    ));
+    for command in &COMMANDS {
+        for (position, kind) in positional_kinds(command) {
+            script.push_str(&format!(
+                "        {}:{position}) {} ;;\n",
+                command.name,
+                posix_completion(&kind, bash)
+            ));
+        }
+    }
+    script.push_str("    esac\n}\n");
+}
+
+/// Bash completion, generated from `COMMANDS` and `GLOBAL_OPTIONS` so it can't drift from the
+/// parser.
+fn bash_completion() -> String {
+    let mut script = String::from(
+        r#"_johnjeong_slugs() {
+    local IFS=$'\n'
+    COMPREPLY+=($(compgen -W "$(johnjeong --complete-slugs 2>/dev/null)" -- "$cur"))
+}
+
+_johnjeong_files() {
+    local IFS=$'\n'
+    COMPREPLY+=($(compgen -f -- "$cur"))
+}
+
+_johnjeong() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" previous="${COMP_WORDS[COMP_CWORD-1]}"
+    COMPREPLY=()
+"#,
+    );
+    script.push_str(&posix_word_scan("COMP_WORDS", 1, "COMP_CWORD"));
+    posix_completion_body(&mut script, "$previous", "\"$cur\"", true);
+    script.push_str("complete -o nosort -F _johnjeong johnjeong\n");
+    script
+}
+
+/// Zsh completion, generated the same way as [`bash_completion`].
+fn zsh_completion() -> String {
+    let mut script = String::from(
+        r#"#compdef johnjeong
+
+_johnjeong_slugs() {
+    local -a slugs
+    slugs=(${(f)"$(johnjeong --complete-slugs 2>/dev/null)"})
+    compadd -V posts -a slugs
+}
+
+_johnjeong_files() {
+    _files
+}
+
+_johnjeong() {
+"#,
+    );
+    script.push_str(&posix_word_scan("words", 2, "CURRENT"));
+    posix_completion_body(&mut script, "${words[CURRENT-1]}", "$PREFIX", false);
+    script.push_str(
+        r#"
+if [[ "$funcstack[1]" == "_johnjeong" ]]; then
+    _johnjeong "$@"
+else
+    compdef _johnjeong johnjeong
+fi
+"#,
+    );
+    script
+}
+
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn fish_option(script: &mut String, condition: &str, spec: &OptionSpec) {
+    script.push_str("complete -c johnjeong");
+    script.push_str(condition);
+    if let Some(short) = spec.short {
+        script.push_str(&format!(" -s {}", short.trim_start_matches('-')));
+    }
+    script.push_str(&format!(" -l {}", spec.flag.trim_start_matches('-')));
+    match option_value_kind(spec) {
+        None => {}
+        Some(CompletionKind::Files) => script.push_str(" -r -F"),
+        Some(kind) => script.push_str(&format!(" -x -a {}", fish_arguments(&kind))),
+    }
+    script.push_str(&format!(" -d {}\n", fish_quote(spec.help)));
+}
+
+fn fish_arguments(kind: &CompletionKind) -> String {
+    match kind {
+        CompletionKind::Tabs => fish_quote(&CONTENT_DIRS.join(" ")),
+        CompletionKind::Slugs => fish_quote("(johnjeong --complete-slugs 2>/dev/null)"),
+        CompletionKind::Words(choices) => fish_quote(&choices.join(" ")),
+        CompletionKind::Nothing | CompletionKind::Files => "''".to_string(),
+    }
+}
+
+/// Fish completion, generated the same way as [`bash_completion`]; fish tracks option values
+/// itself, so this is one `complete` line per command, option and positional.
+fn fish_completion() -> String {
+    let mut script = String::from("complete -c johnjeong -f\n");
+    for command in &COMMANDS {
+        script.push_str(&format!(
+            "complete -c johnjeong -n __fish_use_subcommand -a {} -d {}\n",
+            command.name,
+            fish_quote(command.help)
+        ));
+    }
+    script.push_str(
+        "complete -c johnjeong -n __fish_use_subcommand -k -a '(johnjeong --complete-slugs 2>/dev/null)'\n",
+    );
+    for spec in &GLOBAL_OPTIONS {
+        fish_option(&mut script, "", spec);
+    }
+    for command in &COMMANDS {
+        let condition = format!(" -n '__fish_seen_subcommand_from {}'", command.name);
+        for spec in command.options {
+            fish_option(&mut script, &condition, spec);
+        }
+        // Fish can't count positionals here; the first one's candidates are what matter.
+        if let Some((_, kind)) = positional_kinds(command).into_iter().next() {
+            match kind {
+                CompletionKind::Files => {
+                    script.push_str(&format!("complete -c johnjeong{condition} -F\n"))
+                }
+                kind => script.push_str(&format!(
+                    "complete -c johnjeong{condition} -k -a {}\n",
+                    fish_arguments(&kind)
+                )),
+            }
+        }
+    }
+    script
+}
+
+/// Opens a local file with the platform viewer. macOS `open` takes the raw path; elsewhere
+/// the opener gets a percent-encoded `file://` URL so spaces, `#` and non-ASCII names survive.
+fn open_path(path: &Path) -> io::Result<String> {
+    assert_off_ui_thread("opening a file");
+    if !path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "file no longer exists",
+        ));
+    }
+    #[cfg(target_os = "macos")]
+    {
+        spawn_opener(Command::new("open").arg(path)).map(|()| "open".to_string())
+    }
+
+    // Files go to the platform opener even when a browser is configured, so a photo opens in
+    // the image viewer rather than a browser tab.
+    #[cfg(not(target_os = "macos"))]
+    {
+        open_with_platform(&file_url(path))
+    }
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn file_url(path: &Path) -> String {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    };
+    let bytes = path_bytes(&absolute);
+
+    let mut url = String::from("file://");
+    if bytes.first() != Some(&b'/') {
+        url.push('/');
+    }
+    for byte in bytes {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    url
+}
+
+/// The path's bytes for percent-encoding. On Unix these are the file name's own bytes, so a
+/// name that isn't UTF-8 still points at the file; elsewhere paths are Unicode, with `\`
+/// turned into `/`.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_os_str().as_bytes().to_vec()
+    }
+    #[cfg(not(unix))]
+    {
+        path.to_string_lossy().replace('\\', "/").into_bytes()
+    }
+}
+
+/// `--verify-urls [sitemap]`: compares every post URL with the live site's sitemap (or a local
+/// sitemap or plain list of URLs) and prints the ones the site doesn't have. A live URL whose
+/// last segment differs only in punctuation or case is shown as the likely intended one.
+fn run_verify_urls(
+    data: &AppData,
+    status: Option<&str>,
+    source: Option<&str>,
+    site: &str,
+    tab_filter: Option<&str>,
+) -> io::Result<bool> {
+    if let Some(status) = status {
+        eprintln!("{}", status);
+    }
+    let live = match source {
+        Some(source) => read_url_list(source),
+        None => read_url_list(&format!("{}/sitemap-index.xml", site))
+            .or_else(|_| read_url_list(&format!("{}/sitemap.xml", site))),
+    };
+    let live = match live {
+        Ok(live) if !live.is_empty() => live,
+        Ok(_) => {
+            eprintln!("No URLs found in the sitemap.");
+            std::process::exit(2);
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    let by_segment = live
+        .iter()
+        .map(|url| (loose_segment(url), url.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    let mut matched = true;
+    let mut stdout = io::stdout().lock();
+    for tab in data.tabs.iter().filter_map(|tab| match tab {
+        TabData::Content(tab) if !is_gallery_tab(tab) => Some(tab),
+        _ => None,
+    }) {
+        if tab_filter.is_some_and(|filter| !tab_matches(tab, filter)) {
+            continue;
+        }
+        for post in &tab.posts {
+            let url = post.url.trim_end_matches('/');
+            if live.contains(url) {
+                continue;
+            }
+            matched = false;
+            match by_segment.get(&loose_segment(url)) {
+                Some(guess) => writeln!(stdout, "missing\t{}\t→ {}", url, guess)?,
+                None => writeln!(stdout, "missing\t{}", url)?,
+            }
+        }
+    }
+    Ok(matched)
+}
+
+/// URLs from a sitemap (following one level of sitemap index) or from a list with one URL per
+/// line. `source` is a URL, fetched with `curl`, or a local file.
+fn read_url_list(source: &str) -> Result<std::collections::HashSet<String>, String> {
+    let fetch = |source: &str| -> Result<String, String> {
+        if !source.starts_with("http://") && !source.starts_with("https://") {
+            return fs::read_to_string(source)
+                .map_err(|err| format!("Cannot read {} ({})", source, err));
+        }
+        let output = Command::new("curl")
+            .args(["-sSfL", "--max-time", "15"])
+            .arg(source)
+            .output()
+            .map_err(|_| "curl not found; install curl to verify URLs.".to_string())?;
+        if !output.status.success() {
+            return Err(format!(
+                "Cannot fetch {} ({})",
+                source,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    let contents = fetch(source)?;
+    let mut urls = std::collections::HashSet::new();
+    if !contents.contains("<loc>") {
+        urls.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with("http"))
+                .map(|line| line.trim_end_matches('/').to_string()),
+        );
+        return Ok(urls);
+    }
+    for loc in sitemap_locs(&contents) {
+        if loc.ends_with(".xml") {
+            urls.extend(sitemap_locs(&fetch(&loc)?));
+        } else {
+            urls.insert(loc);
+        }
+    }
+    Ok(urls)
+}
+
+fn sitemap_locs(xml: &str) -> Vec<String> {
+    xml.split("<loc>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</loc>"))
+        .map(|(loc, _)| {
+            loc.trim()
+                .replace("&amp;", "&")
+                .trim_end_matches('/')
+                .to_string()
+        })
+        .collect()
+}
+
+/// The last path segment with everything but letters and digits dropped, for matching a
+/// generated URL to the live one it was meant to be.
+fn loose_segment(url: &str) -> String {
+    let segment = url.trim_end_matches('/').rsplit('/').next().unwrap_or(url);
+    slugify(segment, SlugPreset::Astro)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// Checks every post URL with `curl` (HEAD, falling back to GET for servers that refuse it)
+/// and stores the results for the TUI. This is the only place link status touches the network.
+fn run_link_check(
+    data: &AppData,
+    status: Option<&str>,
+    tab_filter: Option<&str>,
+) -> io::Result<bool> {
+    if let Some(status) = status {
+        eprintln!("{}", status);
+    }
+    if Command::new("curl").arg("--version").output().is_err() {
+        eprintln!("curl not found; install curl to check links.");
+        std::process::exit(2);
+    }
+
+    let mut results = load_link_status_file();
+    let now = unix_now();
+    let mut healthy = true;
+    let mut stdout = io::stdout().lock();
+    for tab in data.tabs.iter().filter_map(|tab| match tab {
+        TabData::Content(tab) if !is_gallery_tab(tab) => Some(tab),
+        _ => None,
+    }) {
+        if tab_filter.is_some_and(|filter| !tab_matches(tab, filter)) {
+            continue;
+        }
+        for post in &tab.posts {
+            let Some((code, location)) = check_link(&post.url) else {
+                writeln!(stdout, "???\t{}", post.url)?;
+                continue;
+            };
+            let link = LinkStatus {
+                code,
+                checked_at: now,
+                location,
+            };
+            healthy &= !link.is_dead();
+            match link.moved_to() {
+                Some(location) => writeln!(stdout, "{}\t{}\t→ {}", code, post.url, location)?,
+                None => writeln!(stdout, "{}\t{}", code, post.url)?,
+            }
+            results.insert(post.url.clone(), link);
+        }
+    }
+
+    if let Err(message) = check_capability(Capability::WriteState) {
+        eprintln!("{} Results were not cached.", message);
+        return Ok(healthy);
+    }
+    if let Some(path) = link_status_path() {
+        let mut urls = results.keys().collect::<Vec<_>>();
+        urls.sort();
+        let contents = urls
+            .into_iter()
+            .map(|url| {
+                let link = &results[url];
+                format!(
+                    "{}\t{}\t{}\t{}\n",
+                    url,
+                    link.code,
+                    link.checked_at,
+                    link.location.as_deref().unwrap_or("")
+                )
+            })
+            .collect::<String>();
+        if let Some(parent) = path.parent() {
+            create_dirs(parent)?;
+        }
+        write_atomic(&path, contents.as_bytes())?;
+    }
+    Ok(healthy)
+}
+
+fn check_link(url: &str) -> Option<(u16, Option<String>)> {
+    assert_off_ui_thread("checking a link");
+    let probe = |head: bool| {
+        let mut command = Command::new("curl");
+        command.args(["-sS", "-o", "/dev/null", "--max-time", "15"]);
+        if head {
+            command.arg("-I");
+        }
+        let output = command
+            .args(["-w", "%{http_code} %{redirect_url}"])
+            .arg(url)
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&output.stdout).to_string();
+        let (code, location) = text.trim().split_once(' ').unwrap_or((text.trim(), ""));
+        let code = code.parse::<u16>().ok().filter(|code| *code != 0)?;
+        let location = (!location.is_empty()).then(|| location.to_string());
+        Some((code, location))
+    };
+    match probe(true) {
+        Some((405, _)) | None => probe(false),
+        result => result,
+    }
+}
+
+/// Cached link results, minus any older than `max_age_days`.
+fn load_link_status(max_age_days: u64) -> HashMap<String, LinkStatus> {
+    let cutoff = unix_now().saturating_sub(max_age_days * 24 * 60 * 60);
+    let mut results = load_link_status_file();
+    results.retain(|_, link| link.checked_at >= cutoff);
+    results
+}
+
+fn load_link_status_file() -> HashMap<String, LinkStatus> {
+    let Some(contents) = link_status_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return HashMap::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let url = fields.next()?;
+            let code = fields.next()?.parse().ok()?;
+            let checked_at = fields.next()?.parse().ok()?;
+            let location = fields
+                .next()
+                .filter(|value| !value.is_empty())
+                .map(str::to_string);
+            Some((
+                url.to_string(),
+                LinkStatus {
+                    code,
+                    checked_at,
+                    location,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn link_status_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("link-status"))
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Graphics {
+    Kitty,
+    Iterm,
+    Sixel,
+    Blocks,
+    Off,
+}
+
+#[derive(Clone, Copy)]
+enum GraphicsSource {
+    Flag,
+    Cached,
+    Detected,
+}
+
+impl Graphics {
+    const ALL: [Graphics; 5] = [
+        Graphics::Kitty,
+        Graphics::Iterm,
+        Graphics::Sixel,
+        Graphics::Blocks,
+        Graphics::Off,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Graphics::Kitty => "kitty",
+            Graphics::Iterm => "iterm",
+            Graphics::Sixel => "sixel",
+            Graphics::Blocks => "blocks",
+            Graphics::Off => "off",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Graphics> {
+        Graphics::ALL
+            .into_iter()
+            .find(|protocol| protocol.name() == name)
+    }
+
+    /// `Some(None)` is `auto`: probe as if no flag were given.
+    fn parse_override(value: &str) -> Option<Option<Graphics>> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Some(None),
+            name => Graphics::from_name(name).map(Some),
+        }
+    }
+}
+
+impl GraphicsSource {
+    fn label(self) -> &'static str {
+        match self {
+            GraphicsSource::Flag => "--graphics",
+            GraphicsSource::Cached => "cached",
+            GraphicsSource::Detected => "detected",
+        }
+    }
+}
+
+/// Set from `--graphics=<mode>`; `None` inside means `auto`.
+static GRAPHICS_OVERRIDE: OnceLock<Option<Graphics>> = OnceLock::new();
+
+/// The image protocol the gallery draws with, decided once per run: the `--graphics` flag, else
+/// the cached answer for this `TERM`/`TERM_PROGRAM`, else a live probe. Only a probe the
+/// terminal answered is cached; a timeout over a slow link is asked again next run.
+fn graphics() -> (Graphics, GraphicsSource) {
+    static GRAPHICS: OnceLock<(Graphics, GraphicsSource)> = OnceLock::new();
+    *GRAPHICS.get_or_init(|| {
+        if let Some(Some(protocol)) = GRAPHICS_OVERRIDE.get() {
+            return (*protocol, GraphicsSource::Flag);
+        }
+        let key = graphics_cache_key();
+        if let Some(protocol) = load_graphics_cache().get(&key) {
+            return (*protocol, GraphicsSource::Cached);
+        }
+        let (protocol, answered) = detect_graphics();
+        if answered {
+            save_graphics_cache(&key, protocol);
+        }
+        (protocol, GraphicsSource::Detected)
+    })
+}
+
+fn graphics_cache_key() -> String {
+    format!(
+        "{}|{}",
+        env::var("TERM").unwrap_or_default(),
+        env::var("TERM_PROGRAM").unwrap_or_default()
+    )
+}
+
+fn graphics_cache_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("graphics"))
+}
+
+fn load_graphics_cache() -> HashMap<String, Graphics> {
+    graphics_cache_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|contents| parse_graphics_cache(&contents))
+        .unwrap_or_default()
+}
+
+/// One `TERM|TERM_PROGRAM<tab>protocol` line per terminal; unknown protocols are skipped.
+fn parse_graphics_cache(contents: &str) -> HashMap<String, Graphics> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (key, name) = line.rsplit_once('\t')?;
+            Some((key.to_string(), Graphics::from_name(name)?))
+        })
+        .collect()
+}
+
+fn save_graphics_cache(key: &str, protocol: Graphics) {
+    if check_capability(Capability::WriteState).is_err() {
+        return;
+    }
+    let Some(path) = graphics_cache_path() else {
+        return;
+    };
+    let mut cache = load_graphics_cache();
+    cache.insert(key.to_string(), protocol);
+    if let Some(parent) = path.parent() {
+        let _ = create_dirs(parent);
+    }
+    let _ = write_atomic(&path, serialize_graphics_cache(&cache).as_bytes());
+}
+
+fn serialize_graphics_cache(cache: &HashMap<String, Graphics>) -> String {
+    let mut lines = cache
+        .iter()
+        .map(|(key, protocol)| format!("{}\t{}\n", key, protocol.name()))
+        .collect::<Vec<_>>();
+    lines.sort();
+    lines.concat()
+}
+
+/// Kitty and iTerm2 are recognized by viuer's own checks; sixel by the `4` attribute in the
+/// terminal's primary device attributes reply. Anything else, or no terminal at all, gets blocks.
+/// The flag says whether that's the terminal's answer rather than a fallback.
+fn detect_graphics() -> (Graphics, bool) {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return (Graphics::Blocks, false);
+    }
+    if viuer::get_kitty_support() != viuer::KittySupport::None {
+        return (Graphics::Kitty, true);
+    }
+    if viuer::is_iterm_supported() {
+        return (Graphics::Iterm, true);
+    }
+    match query_device_attributes(Duration::from_millis(150)) {
+        Some(reply) if reply.split(';').skip(1).any(|attribute| attribute == "4") => {
+            (Graphics::Sixel, true)
+        }
+        Some(_) => (Graphics::Blocks, true),
+        None => (Graphics::Blocks, false),
+    }
+}
+
+/// Sends DA1 (`ESC [ c`) and returns the parameters of the `ESC [ ? … c` reply, or `None` if the
+/// terminal stays quiet past `timeout`.
+fn query_device_attributes(timeout: Duration) -> Option<String> {
+    let reply = query_terminal(b"", timeout)?;
+    let start = reply.rfind("\x1b[?")? + 3;
+    Some(reply[start..].trim_end_matches('c').to_string())
+}
+
+/// Writes `request` followed by DA1 and returns everything the terminal sends back up to the
+/// end of its DA1 reply. Every terminal answers DA1, so a query it ignores doesn't leave the
+/// read waiting for keys. If even DA1 goes unanswered past `timeout`, whatever did arrive is
+/// drained and nothing keeps reading stdin, so later keys reach the event loop.
+fn query_terminal(request: &[u8], timeout: Duration) -> Option<String> {
+    let was_raw = terminal::is_raw_mode_enabled().unwrap_or(false);
+    if !was_raw {
+        terminal::enable_raw_mode().ok()?;
+    }
+    let mut stdout = io::stdout();
+    let sent = stdout
+        .write_all(request)
+        .and_then(|_| stdout.write_all(b"\x1b[c"))
+        .and_then(|_| stdout.flush())
+        .is_ok();
+    let reply = sent.then(|| read_terminal_reply(timeout)).flatten();
+    if !was_raw {
+        let _ = terminal::disable_raw_mode();
+    }
+    String::from_utf8(reply?).ok()
+}
+
+/// Reads the tty a byte at a time, polling with what's left of `timeout`, until the `ESC [ ? … c`
+/// DA1 reply ends. Reads go to the fd directly so nothing is left in `Stdin`'s buffer.
+#[cfg(unix)]
+fn read_terminal_reply(timeout: Duration) -> Option<Vec<u8>> {
+    let deadline = Instant::now() + timeout;
+    let mut reply = Vec::new();
+    let mut in_device_attributes = false;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match stdin_readable(left) {
+            Some(true) => {}
+            Some(false) => {
+                drain_stdin();
+                return None;
+            }
+            None => return None,
+        }
+        let mut byte = 0u8;
+        // SAFETY: reads at most one byte into `byte`, which outlives the call.
+        let read = unsafe { libc::read(libc::STDIN_FILENO, (&mut byte as *mut u8).cast(), 1) };
+        if read <= 0 {
+            return None;
+        }
+        reply.push(byte);
+        in_device_attributes |= reply.ends_with(b"\x1b[?");
+        if in_device_attributes && byte == b'c' {
+            return Some(reply);
+        }
+    }
+}
+
+/// Without `poll` there's no way to give up on a read, so the terminal isn't asked.
+#[cfg(not(unix))]
+fn read_terminal_reply(_timeout: Duration) -> Option<Vec<u8>> {
+    None
+}
+
+/// Whether stdin has bytes within `timeout`; `None` if polling failed.
+#[cfg(unix)]
+fn stdin_readable(timeout: Duration) -> Option<bool> {
+    loop {
+        let mut fd = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+        // SAFETY: `fd` is a single valid pollfd for the duration of the call.
+        match unsafe { libc::poll(&mut fd, 1, millis) } {
+            0 => return Some(false),
+            ready if ready > 0 => return Some(fd.revents & libc::POLLIN != 0),
+            _ if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+            _ => return None,
+        }
+    }
+}
+
+/// Throws away a partial reply already waiting, so it doesn't arrive as keystrokes.
+#[cfg(unix)]
+fn drain_stdin() {
+    let mut buffer = [0u8; 64];
+    while stdin_readable(Duration::ZERO) == Some(true) {
+        // SAFETY: reads at most `buffer.len()` bytes into `buffer`.
+        let read =
+            unsafe { libc::read(libc::STDIN_FILENO, buffer.as_mut_ptr().cast(), buffer.len()) };
+        if read <= 0 {
+            break;
+        }
+    }
+}
+
+/// Whether the terminal's background looks light: from `COLORFGBG` (`fg;bg`, set by rxvt,
+/// Konsole, and others) when present, else by asking the terminal for its background color
+/// with OSC 11. Anything unclear counts as dark.
+fn light_background() -> bool {
+    if let Ok(value) = env::var("COLORFGBG") {
+        return value
+            .rsplit(';')
+            .next()
+            .and_then(|bg| bg.trim().parse::<u8>().ok())
+            .is_some_and(|bg| bg == 7 || bg >= 9);
+    }
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return false;
+    }
+    query_terminal(b"\x1b]11;?\x1b\\", Duration::from_millis(150))
+        .and_then(|reply| background_luminance(&reply))
+        .is_some_and(|luminance| luminance > 0.5)
+}
+
+/// Relative luminance (0 to 1) from an OSC 11 reply like `ESC ] 11 ; rgb:ffff/ffff/ffff ESC \`.
+fn background_luminance(reply: &str) -> Option<f64> {
+    let start = reply.find("rgb:")? + 4;
+    let channels = reply[start..]
+        .split('/')
+        .take(3)
+        .map(|channel| {
+            let digits = channel
+                .chars()
+                .take_while(char::is_ascii_hexdigit)
+                .collect::<String>();
+            let value = u32::from_str_radix(&digits, 16).ok()?;
+            let max = 16u32.checked_pow(digits.len() as u32)?.checked_sub(1)?;
+            Some(value as f64 / max as f64)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    match channels[..] {
+        [r, g, b] => Some(0.2126 * r + 0.7152 * g + 0.0722 * b),
+        _ => None,
+    }
+}
+
+/// Whether animated affordances should be replaced by static text. Every animation call-site
+/// asks this instead of reading the environment itself.
+fn reduced_motion() -> bool {
+    *REDUCED_MOTION.get_or_init(|| env_flag("JOHNJEONG_REDUCED_MOTION"))
+}
+
+/// Set from `Config` in `main`.
+static REDUCED_MOTION: OnceLock<bool> = OnceLock::new();
+
+/// Whether titles and About links are printed as OSC 8 hyperlinks. Never under `--restricted`,
+/// where a click would skip the URL confirmation, nor on terminals that can't show color.
+fn hyperlinks() -> bool {
+    HYPERLINKS.get().copied().unwrap_or(false)
+}
+
+/// Set in `main` from `Config::hyperlinks` or `terminal_supports_hyperlinks`.
+static HYPERLINKS: OnceLock<bool> = OnceLock::new();
+
+/// Terminals known to handle OSC 8. Others may print the sequence as garbage, so they only get
+/// hyperlinks with `hyperlinks = true`.
+fn terminal_supports_hyperlinks() -> bool {
+    let program = env::var("TERM_PROGRAM").unwrap_or_default();
+    let term = env::var("TERM").unwrap_or_default();
+    matches!(
+        program.as_str(),
+        "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+    ) || term.contains("kitty")
+        || term.contains("foot")
+        || env::var_os("KITTY_WINDOW_ID").is_some()
+        || env::var_os("WT_SESSION").is_some()
+        || env::var("VTE_VERSION")
+            .ok()
+            .and_then(|version| version.parse::<u32>().ok())
+            .is_some_and(|version| version >= 5000)
+}
+
+/// Wraps already clamped `text` in an OSC 8 hyperlink to `url` when `hyperlinks()` allows it.
+/// Opening and closing go out in one `Print`, so nothing can cut the link off unterminated.
+fn hyperlink(text: &str, url: &str) -> String {
+    let linkable = url.contains("://") || url.starts_with("mailto:");
+    if !hyperlinks() || !linkable || url.chars().any(char::is_control) {
+        return text.to_string();
+    }
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+fn env_flag(name: &str) -> bool {
+    env::var(name).is_ok_and(|value| {
+        matches!(
+            value.trim().to_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// Prints how the environment resolved, for bug reports.
+fn print_doctor(config: &Config) {
+    println!("johnjeong {}", env!("CARGO_PKG_VERSION"));
+    println!();
+    let file = match config_path() {
+        Some(path) if path.is_file() => path.display().to_string(),
+        Some(path) => format!("{} (not found)", path.display()),
+        None => "unavailable".to_string(),
+    };
+    println!("config          {}", file);
+    let content = find_local_content_root(config)
+        .or_else(|| content_cache_dir().filter(|dir| dir.is_dir()))
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| "not found (will fetch from GitHub)".to_string());
+    println!("content root    {}", content);
+    println!(
+        "git             {}",
+        if git_available() { "found" } else { "missing" }
+    );
+    let state = state_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| "unavailable".to_string());
+    println!("state dir       {}", state);
+    println!("reduced motion  {}", reduced_motion());
+    println!("hyperlinks      {}", hyperlinks());
+    let (protocol, source) = graphics();
+    let note = if protocol == Graphics::Sixel {
+        ", drawn as blocks"
+    } else {
+        ""
+    };
+    println!(
+        "graphics        {} ({}{})",
+        protocol.name(),
+        source.label(),
+        note
+    );
+}
+
+/// Every key binding, by category, for both the `?` overlay and `--help`: the number keys
+/// that pick tabs, then `ACTIONS` with whatever keys `keys` gives them. Unbound actions are
+/// left out.
+fn key_bindings(keys: &Keymap, tab_count: usize) -> Vec<(&'static str, Vec<(String, String)>)> {
+    let mut categories: Vec<(&'static str, Vec<(String, String)>)> = Vec::new();
+    for (action, _, category, _, description) in ACTIONS {
+        let label = keys.label(action);
+        if label.is_empty() {
+            continue;
+        }
+        if categories.last().is_none_or(|(last, _)| *last != category) {
+            categories.push((category, Vec::new()));
+        }
+        if let Some((_, entries)) = categories.last_mut() {
+            entries.push((label, description.to_string()));
+        }
+    }
+    if let Some((_, tabs)) = categories.iter_mut().find(|(name, _)| *name == "Tabs") {
+        tabs.insert(
+            0,
+            (
+                tab_hotkey_range(tab_count),
+                format!(
+                    "switch tabs ({} browses tags)",
+                    tab_hotkey(tab_count.saturating_sub(1)).unwrap_or('0')
+                ),
+            ),
+        );
+    }
+    categories
+}
+
+/// `--help`, generated from `GLOBAL_OPTIONS` and `COMMANDS`; with a subcommand, just its usage
+/// and options. A reader that stops early (`| head`) isn't an error.
+fn print_help(command: Option<&CommandSpec>) -> io::Result<()> {
+    match write_help(&mut io::stdout().lock(), command) {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => Err(err),
+        _ => Ok(()),
+    }
+}
+
+fn write_help(out: &mut impl Write, command: Option<&CommandSpec>) -> io::Result<()> {
+    if let Some(spec) = command {
+        writeln!(out, "Usage: {}", command_usage(spec))?;
+        writeln!(out)?;
+        writeln!(out, "{}.", capitalize(spec.help))?;
+        if !spec.options.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "Options:")?;
+            write_options(out, spec.options)?;
+        }
+        return Ok(());
+    }
+    writeln!(out, "johnjeong - terminal edition")?;
+    writeln!(out)?;
+    writeln!(out, "Usage:")?;
+    writeln!(out, "  johnjeong [options]")?;
+    for spec in &COMMANDS {
+        writeln!(out, "  {}", command_usage(spec))?;
+    }
+    writeln!(out)?;
+    writeln!(out, "Commands:")?;
+    let width = COMMANDS
+        .iter()
+        .map(|spec| display_width(spec.name))
+        .max()
+        .unwrap_or(0);
+    for spec in &COMMANDS {
+        writeln!(
+            out,
+            "  {}{}  {}",
+            spec.name,
+            " ".repeat(width - display_width(spec.name)),
+            spec.help
+        )?;
+    }
+    writeln!(out)?;
+    writeln!(out, "Options:")?;
+    write_options(out, &GLOBAL_OPTIONS)?;
+    writeln!(out)?;
+    // About, one tab per content directory, then Tags.
+    // Bindings from the config when it has valid ones, so this matches the app.
+    let keys = Keymap::from_config(load_config().get("keys"))
+        .or_else(|_| Keymap::from_config(None))
+        .unwrap_or(Keymap {
+            bindings: Vec::new(),
+        });
+    let bindings = key_bindings(&keys, CONTENT_DIRS.len() + 2);
+    let key_width = bindings
+        .iter()
+        .flat_map(|(_, keys)| keys.iter().map(|(key, _)| display_width(key)))
+        .max()
+        .unwrap_or(0);
+    for (category, keys) in bindings {
+        writeln!(out, "{}:", category)?;
+        for (key, description) in keys {
+            let padding = " ".repeat(key_width - display_width(&key));
+            writeln!(out, "  {}{}  {}", key, padding, description)?;
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "Content:")?;
+    writeln!(
+        out,
+        "  Read from the first of these that is set (~ and $VARS expand):"
+    )?;
+    writeln!(out, "    1. --content-dir <path>, which must exist")?;
+    writeln!(out, "    2. JOHNJEONG_CONTENT_DIR")?;
+    writeln!(out, "    3. content.dir in the config file")?;
+    writeln!(
+        out,
+        "    4. a part-of-my-brain directory here or up to five levels above"
+    )?;
+    writeln!(out, "    5. a copy fetched from GitHub (requires git)")?;
+    writeln!(out)?;
+    writeln!(out, "Environment:")?;
+    writeln!(
+        out,
+        "  JOHNJEONG_SITE_URL          base URL for post links (default https://johnjeong.com)"
+    )?;
+    writeln!(
+        out,
+        "  JOHNJEONG_REDUCED_MOTION=1  disable animations and time-based redraws"
+    )?;
+    writeln!(
+        out,
+        "  JOHNJEONG_HIGH_CONTRAST=1   start with high-contrast focus indicators"
+    )?;
+    writeln!(
+        out,
+        "  JOHNJEONG_HYPERLINKS=0|1    turn clickable titles and links off or on"
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory under the system temp dir, unique to this test run.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("johnjeong-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Setup for a child test process: content with one essay under the fixture directory the
+    /// parent passed in `JOHNJEONG_TEST_FIXTURE`, every XDG directory and the working directory
+    /// pointed into it, and `flag` turned on, which only happens once per process. Returns the
+    /// fixture root.
+    fn child_fixture(flag: &OnceLock<bool>) -> PathBuf {
+        let root = PathBuf::from(env::var_os("JOHNJEONG_TEST_FIXTURE").unwrap());
+        let essays = root.join("content").join("essays");
+        fs::create_dir_all(&essays).unwrap();
+        fs::create_dir_all(root.join("content").join("journals")).unwrap();
+        fs::write(essays.join("hello.md"), "---\ntitle: Hello\n---\n\nBody\n").unwrap();
+        for (name, dir) in [
+            ("XDG_CACHE_HOME", "cache"),
+            ("XDG_STATE_HOME", "state"),
+            ("XDG_DATA_HOME", "data"),
+            ("XDG_CONFIG_HOME", "config"),
+        ] {
+            env::set_var(name, root.join(dir));
+        }
+        env::set_current_dir(&root).unwrap();
+        flag.set(true).unwrap();
+        root
+    }
+
+    fn index_entry(number: u64) -> (PathBuf, SlugIndexEntry) {
+        (
+            PathBuf::from(format!("/notes/essays/post-{}.md", number)),
+            SlugIndexEntry {
+                modified: 1_700_000_000 + number,
+                size: 100 + number,
+                sort_key: format!("2024-01-{:02}", number % 28 + 1),
+                slug: format!("post-{}", number),
+                unpublished: number.is_multiple_of(3),
+            },
+        )
+    }
+
+    #[test]
+    fn slug_index_round_trips() {
+        let index = (0..20).map(index_entry).collect::<HashMap<_, _>>();
+        let bytes = serialize_slug_index(&index);
+        assert_eq!(parse_slug_index(bytes.as_bytes()), (index, 0));
+    }
+
+    #[test]
+    fn slug_index_recovers_from_corrupted_bytes() {
+        let index = (0..200).map(index_entry).collect::<HashMap<_, _>>();
+        let clean = serialize_slug_index(&index).into_bytes();
+        // A fixed xorshift sequence, so a failure can be replayed.
+        let mut seed = 0x2545f4914f6cdd1d_u64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for _ in 0..50 {
+            let mut bytes = clean.clone();
+            let flips = 1 + next() % 8;
+            for _ in 0..flips {
+                let at = next() as usize % bytes.len();
+                bytes[at] = next() as u8;
+            }
+            let (recovered, damaged) = parse_slug_index(&bytes);
+            for (path, entry) in &recovered {
+                assert_eq!(index.get(path), Some(entry), "{}", path.display());
+            }
+            // Each flipped byte damages at most the two lines around it.
+            assert!(recovered.len() + 2 * flips as usize >= index.len());
+            assert!(damaged <= 2 * flips as usize);
+        }
+
+        // A write torn off mid-line loses only that line.
+        let torn = &clean[..clean.len() - 10];
+        let (recovered, damaged) = parse_slug_index(torn);
+        assert_eq!((recovered.len(), damaged), (index.len() - 1, 1));
+    }
+
+    #[test]
+    fn damaged_slug_index_entries_are_read_again() {
+        let dir = temp_dir("slug-index");
+        let essays = dir.join("essays");
+        fs::create_dir_all(&essays).unwrap();
+        fs::write(
+            essays.join("first.md"),
+            "---\ntitle: First\ncreated_at: 2024-02-01\n---\nbody\n",
+        )
+        .unwrap();
+        fs::write(
+            essays.join("second.md"),
+            "---\ntitle: Second\ncreated_at: 2024-03-01\nslug: custom\n---\nbody\n",
+        )
+        .unwrap();
+
+        let mut fresh = HashMap::new();
+        let entries = completion_entries(&essays, false, &HashMap::new(), &mut fresh);
+        assert_eq!(entries.len(), 2);
+
+        // Garble one entry's slug; the checksum catches it and that file is reread.
+        let saved = serialize_slug_index(&fresh).replace("custom", "cust0m");
+        let (cached, damaged) = parse_slug_index(saved.as_bytes());
+        assert_eq!((cached.len(), damaged), (1, 1));
+        let mut rebuilt = HashMap::new();
+        let mut entries = completion_entries(&essays, false, &cached, &mut rebuilt);
+        entries.sort();
+        assert_eq!(
+            entries,
+            [
+                ("2024-02-01".to_string(), "first".to_string()),
+                ("2024-03-01".to_string(), "custom".to_string()),
+            ]
+        );
+        assert_eq!(rebuilt, fresh);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn completion_scripts_cover_every_command_and_option() {
+        for script in [bash_completion(), zsh_completion(), fish_completion()] {
+            for command in &COMMANDS {
+                assert!(script.contains(command.name), "missing {}", command.name);
+                for spec in command.options {
+                    assert!(script.contains(spec.flag.trim_start_matches('-')));
+                }
             }
-            let date = if post.date.is_empty() {
-                "".to_string()
+            for spec in &GLOBAL_OPTIONS {
+                assert!(script.contains(spec.flag.trim_start_matches('-')));
+            }
+            assert!(script.contains("--complete-slugs"));
+        }
+    }
+
+    #[test]
+    fn completion_kinds_follow_the_usage_names() {
+        assert_eq!(completion_kind("[tab]"), CompletionKind::Tabs);
+        assert_eq!(completion_kind("<slug>"), CompletionKind::Slugs);
+        assert_eq!(completion_kind("sitemap-url|file"), CompletionKind::Files);
+        assert_eq!(
+            completion_kind("default|light|mono"),
+            CompletionKind::Words(vec!["default", "light", "mono"])
+        );
+        assert_eq!(completion_kind("<query>..."), CompletionKind::Nothing);
+    }
+
+    /// Every file and directory under `root` with its mtime, and each file's bytes.
+    fn tree_snapshot(
+        root: &Path,
+    ) -> std::collections::BTreeMap<PathBuf, (std::time::SystemTime, Vec<u8>)> {
+        let mut snapshot = std::collections::BTreeMap::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            let meta = fs::metadata(&path).unwrap();
+            let bytes = if meta.is_dir() {
+                pending.extend(
+                    fs::read_dir(&path)
+                        .unwrap()
+                        .map(|entry| entry.unwrap().path()),
+                );
+                Vec::new()
             } else {
-                format!("{} ", format_date(&post.date))
+                fs::read(&path).unwrap()
             };
-            let label = clamp_text(&format!("{}{}", date, post.title), list_width - 2);
-            queue!(
-                stdout,
-                Print(format!("{} {}", if is_selected { "›" } else { " " }, label)),
-                ResetColor
-            )?;
+            snapshot.insert(path, (meta.modified().unwrap(), bytes));
         }
+        snapshot
     }
 
-    let content_x = (list_x + list_width as u16 + 2).min(max_width as u16);
-    let content_width = max_width.saturating_sub(content_x as usize + 1).max(10);
+    /// `READ_ONLY` is set once per process, so the check runs in a child test process with
+    /// the flag on; see `read_only_child`.
+    #[test]
+    fn read_only_leaves_fixtures_untouched() {
+        let dir = temp_dir("read-only");
+        let output = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "tests::read_only_child", "--ignored", "--quiet"])
+            .env("JOHNJEONG_TEST_FIXTURE", &dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
 
-    if let Some(post) = tab.posts.get(state.list_index) {
-        if is_gallery_tab(tab) {
-            state.content_scroll = 0;
-            state.content_scroll_max = 0;
-            render_gallery_preview(
-                stdout,
-                state,
-                post,
-                content_x,
-                list_y - 1,
-                content_width,
-                rows,
-            )?;
-        } else {
-            let content_top = list_y - 1;
-            let mut y = content_top;
+    #[test]
+    #[ignore = "run by read_only_leaves_fixtures_untouched"]
+    fn read_only_child() {
+        let root = child_fixture(&READ_ONLY);
+        let essays = root.join("content").join("essays");
+        let journals = root.join("content").join("journals");
+        let repo = root
+            .join("cache")
+            .join("johnjeong")
+            .join("part-of-my-brain");
+        let state = root.join("state").join("johnjeong");
+        for dir in [&repo.join(".git"), &state] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(journals.join("2024-01-01.md"), "---\n---\n").unwrap();
+        fs::write(state.join("slugs"), "").unwrap();
+        fs::create_dir_all(root.join("config").join("johnjeong")).unwrap();
+        fs::write(
+            root.join("config").join("johnjeong").join("config.toml"),
+            "",
+        )
+        .unwrap();
+        let before = tree_snapshot(&root);
 
-            let title = clamp_text(&post.title, content_width);
-            queue!(
-                stdout,
-                MoveTo(content_x, y),
-                SetAttribute(Attribute::Bold),
-                Print(title),
-                SetAttribute(Attribute::Reset)
-            )?;
-            y += 1;
+        let hello = essays.join("hello.md");
+        assert!(write_file(&hello, b"changed").is_err());
+        assert!(write_atomic(&hello, b"changed").is_err());
+        assert!(create_file(&hello, false).is_err());
+        assert!(remove_file(&hello).is_err());
+        assert!(remove_dir_all(&essays).is_err());
+        assert!(rename(&hello, &essays.join("moved.md")).is_err());
+        assert!(create_dirs(&root.join("new")).is_err());
+        assert!(create_post(&essays.join("new.md"), "New").is_err());
+        assert!(create_daily_log(&journals, local_today()).is_err());
+        assert!(save_copy(&hello, Path::new("hello.md"), true).is_err());
+        assert!(save_snapshot(&state.join("snapshots").join("x"), "body").is_err());
+        assert!(save_slug_index(&HashMap::from([index_entry(1)])).is_err());
+        assert!(set_config_value("", "theme", "mono").is_err());
+        assert!(SessionStore::open().is_none());
+        assert!(UpdateLock::acquire(&repo).is_none());
+        assert!(git_pull(&repo).is_err());
+        assert!(clone_content("https://example.invalid/content.git", None).is_err());
+        ensure_remote_content(&mut None);
 
-            if !post.date.is_empty() {
-                queue!(
-                    stdout,
-                    MoveTo(content_x, y),
-                    SetForegroundColor(Color::DarkGrey),
-                    Print(post.date.clone()),
-                    ResetColor
-                )?;
-                y += 1;
-            }
+        assert!(
+            before == tree_snapshot(&root),
+            "read-only mode changed the fixture"
+        );
+    }
 
-            let lines = wrap_markdown(&post.body, content_width);
-            let available = rows.saturating_sub(y + 2) as usize;
-            state.content_scroll_max = lines.len().saturating_sub(available);
-            if state.content_scroll > state.content_scroll_max {
-                state.content_scroll = state.content_scroll_max;
-            }
+    fn post(slug: &str, date: &str, tags: &[&str]) -> Post {
+        Post {
+            title: slug.replace('-', " "),
+            date: date.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            fields: HashMap::new(),
+            body: String::new(),
+            url: format!("https://johnjeong.com/essays/{}", slug),
+            slug: slug.to_string(),
+            sort_key: date.to_string(),
+            dir: None,
+            file: None,
+            decoding: None,
+            words: 0,
+            draft: false,
+        }
+    }
 
-            for line in lines.iter().skip(state.content_scroll).take(available) {
-                queue!(
-                    stdout,
-                    MoveTo(content_x, y),
-                    Print(clamp_text(line, content_width))
-                )?;
-                y += 1;
-                if y >= rows.saturating_sub(2) {
-                    break;
-                }
-            }
+    fn essays_tab(posts: Vec<Post>) -> ContentTab {
+        ContentTab {
+            name: "Essays",
+            dir: "essays",
+            web_url: None,
+            local_dir: None,
+            description: String::new(),
+            posts,
+            filter: None,
         }
-    } else {
-        state.content_scroll_max = 0;
-        queue!(
-            stdout,
-            MoveTo(content_x, list_y),
-            SetForegroundColor(Color::DarkGrey),
-            Print("Select a post to read."),
-            ResetColor
-        )?;
     }
 
-    Ok(())
-}
+    fn app_data(tabs: Vec<ContentTab>) -> AppData {
+        AppData {
+            header: HeaderData {
+                title: "Test".to_string(),
+                subtitle: String::new(),
+            },
+            tabs: tabs.into_iter().map(TabData::Content).collect(),
+            link_status: HashMap::new(),
+            gallery_dates_pending: false,
+        }
+    }
 
-fn clamp_scroll(scroll: usize, index: usize, height: usize, total: usize) -> usize {
-    if total <= height {
-        return 0;
+    fn app_state() -> AppState {
+        AppState::new(DEFAULT_THEME, Keymap::from_config(None).unwrap())
     }
-    if index < scroll {
-        return index;
+
+    /// Three tabs of three, two, and no posts.
+    fn three_tabs() -> AppData {
+        let mut logs = essays_tab(vec![
+            post("2024-02-02", "2024-02-02", &[]),
+            post("2024-02-01", "2024-02-01", &[]),
+        ]);
+        logs.name = "Daily Logs";
+        logs.dir = "journals";
+        let mut lessons = essays_tab(Vec::new());
+        lessons.name = "Lessons";
+        lessons.dir = "lessons";
+        app_data(vec![
+            essays_tab(vec![
+                post("third", "2024-03-01", &[]),
+                post("second", "2024-02-01", &[]),
+                post("first", "2024-01-01", &[]),
+            ]),
+            logs,
+            lessons,
+        ])
     }
-    if index >= scroll + height {
-        return index.saturating_sub(height.saturating_sub(1));
+
+    fn press(data: &mut AppData, state: &mut AppState, keys: &str) {
+        for key in keys.split(' ') {
+            let event = match key {
+                "tab" => KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
+                "esc" => KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+                "enter" => KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+                "down" => KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
+                "backtab" => KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT),
+                "left" => KeyEvent::new(KeyCode::Left, KeyModifiers::NONE),
+                "right" => KeyEvent::new(KeyCode::Right, KeyModifiers::NONE),
+                "pgdn" => KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE),
+                "ctrl-d" => KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+                "ctrl-u" => KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL),
+                key => KeyEvent::new(
+                    KeyCode::Char(key.chars().next().unwrap()),
+                    KeyModifiers::NONE,
+                ),
+            };
+            let outcome = update(AppEvent::Key(event), data, state).unwrap();
+            assert!(!outcome.quit, "{} quit", key);
+        }
     }
-    scroll.min(total.saturating_sub(height))
-}
 
-fn list_length(data: &AppData, tab_index: usize) -> usize {
-    match data.tabs.get(tab_index) {
-        Some(TabData::About(about)) => about.links.len(),
-        Some(TabData::Content(tab)) => tab.posts.len(),
-        None => 0,
+    #[test]
+    fn keys_switch_tabs_and_wrap_around() {
+        let mut data = three_tabs();
+        let mut state = app_state();
+        state.list_index = 2;
+        state.content_scroll = 5;
+        press(&mut data, &mut state, "2");
+        assert_eq!(
+            (state.tab_index, state.list_index, state.content_scroll),
+            (1, 0, 0)
+        );
+        press(&mut data, &mut state, "tab");
+        assert_eq!(state.tab_index, 2);
+        press(&mut data, &mut state, "right");
+        assert_eq!(state.tab_index, 0);
+        press(&mut data, &mut state, "backtab");
+        assert_eq!(state.tab_index, 2);
+        press(&mut data, &mut state, "left left");
+        assert_eq!(state.tab_index, 0);
+        // A hotkey past the last tab does nothing.
+        press(&mut data, &mut state, "9");
+        assert_eq!(state.tab_index, 0);
     }
-}
 
-fn tab_name(tab: &TabData) -> &'static str {
-    match tab {
-        TabData::About(_) => "About",
-        TabData::Content(tab) => tab.name,
+    #[test]
+    fn selection_wraps_at_both_ends_of_the_list() {
+        let mut data = three_tabs();
+        let mut state = app_state();
+        press(&mut data, &mut state, "k");
+        assert_eq!(state.list_index, 2);
+        press(&mut data, &mut state, "j");
+        assert_eq!(state.list_index, 0);
+        press(&mut data, &mut state, "j j");
+        assert_eq!(state.list_index, 2);
+        state.content_scroll = 4;
+        press(&mut data, &mut state, "j");
+        assert_eq!((state.list_index, state.content_scroll), (0, 0));
+        // An empty tab has nothing to move through.
+        press(&mut data, &mut state, "3 j k");
+        assert_eq!((state.tab_index, state.list_index), (2, 0));
     }
-}
-fn is_gallery_tab(tab: &ContentTab) -> bool {
-    tab.name == "Gallery"
-}
-fn header_text<'a>(data: &'a AppData, state: &AppState) -> (&'a str, &'a str) {
-    match data.tabs.get(state.tab_index) {
-        Some(TabData::Content(tab)) => (tab.name, tab.description),
-        Some(TabData::About(_)) | None => (&data.header.title, &data.header.subtitle),
+
+    #[test]
+    fn content_scrolling_stays_within_the_post() {
+        let mut data = three_tabs();
+        let mut state = app_state();
+        state.content_height = 6;
+        state.content_scroll_max = 10;
+        press(&mut data, &mut state, "w ctrl-d");
+        assert_eq!(state.content_scroll, 3);
+        press(&mut data, &mut state, "pgdn");
+        assert_eq!(state.content_scroll, 7);
+        press(&mut data, &mut state, "j j j j j");
+        assert_eq!(state.content_scroll, 10);
+        press(&mut data, &mut state, "ctrl-u");
+        assert_eq!(state.content_scroll, 7);
+        press(&mut data, &mut state, "G");
+        assert_eq!(state.content_scroll, 10);
+        press(&mut data, &mut state, "t k");
+        assert_eq!(state.content_scroll, 0);
+        // Back on the list, j moves the selection instead.
+        press(&mut data, &mut state, "w j");
+        assert_eq!((state.list_index, state.content_scroll), (1, 0));
     }
-}
 
-fn tab_label(index: usize, tab: &TabData) -> String {
-    format!("{}. {}", index + 1, tab_name(tab))
-}
+    #[test]
+    fn tab_view_filter_hides_posts_from_the_list_but_keeps_them() {
+        let mut tab = essays_tab(vec![
+            post("older", "2024-01-01", &[]),
+            post("meta-notes", "2024-03-01", &["meta"]),
+            post("newer", "2024-02-01", &["rust"]),
+        ]);
+        let section = HashMap::from([
+            ("sort".to_string(), "date desc".to_string()),
+            ("filter".to_string(), "tag != meta".to_string()),
+        ]);
+        TabView::parse(&section).unwrap().apply(&mut tab);
+        let listed = tab.listed_posts().map(|post| post.slug.as_str());
+        assert_eq!(listed.collect::<Vec<_>>(), ["newer", "older"]);
+        assert_eq!(tab.posts.len(), 3);
+        assert_eq!(tag_counts(std::iter::once(&tab)), [("rust".to_string(), 1)]);
+    }
 
-fn resolve_content_root(status: &mut Option<String>) -> Option<PathBuf> {
-    if let Ok(path) = env::var("JOHNJEONG_CONTENT_DIR") {
-        let candidate = PathBuf::from(path);
-        if candidate.is_dir() {
-            return Some(candidate);
+    #[test]
+    fn session_lock_left_by_a_dead_process_is_a_crash() {
+        let dir = temp_dir("session-lock");
+        let store = SessionStore {
+            path: dir.join("session"),
+            lock: dir.join("session.lock"),
+        };
+        let age = |seconds| {
+            fs::File::options()
+                .write(true)
+                .open(&store.lock)
+                .unwrap()
+                .set_modified(std::time::SystemTime::now() - Duration::from_secs(seconds))
+                .unwrap();
+        };
+        assert!(!store.previous_run_crashed());
+
+        // A pid that just exited: a crash even though the lock was touched moments ago.
+        let mut child = Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+        fs::write(&store.lock, child.id().to_string()).unwrap();
+        assert!(store.previous_run_crashed());
+
+        // A live instance that keeps its heartbeat isn't one, however long it has sat idle.
+        store.heartbeat().unwrap();
+        assert!(!store.previous_run_crashed());
+        // A live pid behind a lock with no heartbeat for a while belongs to someone else now.
+        age(120);
+        assert!(store.previous_run_crashed());
+
+        // Without a readable pid, the age decides.
+        fs::write(&store.lock, "garbage").unwrap();
+        assert!(!store.previous_run_crashed());
+        age(120);
+        assert!(store.previous_run_crashed());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn config_flag_is_read_in_both_spellings() {
+        let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+        for line in ["--config /x/cfg.toml list", "--config=/x/cfg.toml list"] {
+            let args = args(line);
+            assert_eq!(
+                flag_value(&args, "--config"),
+                Some("/x/cfg.toml"),
+                "{}",
+                line
+            );
+            let cli = Cli::parse(&args).unwrap();
+            assert_eq!(
+                cli.options.value("--config").as_deref(),
+                Some("/x/cfg.toml")
+            );
         }
+        assert_eq!(flag_value(&args("--config"), "--config"), None);
+        assert_eq!(flag_value(&args("--configs=x"), "--config"), None);
+        assert_eq!(flag_value(&args("cat -- --config=x"), "--config"), None);
     }
 
-    let mut dir = env::current_dir().ok()?;
-    for _ in 0..6 {
-        let candidate = dir.join("part-of-my-brain");
-        if candidate.is_dir() {
-            return Some(candidate);
+    #[test]
+    fn graphics_override_names_every_protocol_and_auto() {
+        for protocol in Graphics::ALL {
+            assert_eq!(
+                Graphics::parse_override(protocol.name()),
+                Some(Some(protocol))
+            );
         }
-        if !dir.pop() {
-            break;
+        assert_eq!(
+            Graphics::parse_override(" Sixel "),
+            Some(Some(Graphics::Sixel))
+        );
+        assert_eq!(Graphics::parse_override("auto"), Some(None));
+        assert_eq!(Graphics::parse_override("ascii"), None);
+    }
+
+    #[test]
+    fn graphics_cache_round_trips() {
+        let cache = HashMap::from([
+            ("xterm-256color|".to_string(), Graphics::Sixel),
+            ("xterm-kitty|".to_string(), Graphics::Kitty),
+            ("xterm-256color|iTerm.app".to_string(), Graphics::Iterm),
+            ("screen|tmux".to_string(), Graphics::Blocks),
+        ]);
+        let contents = serialize_graphics_cache(&cache);
+        assert_eq!(parse_graphics_cache(&contents), cache);
+        // Lines from a newer version with a protocol this one doesn't know are dropped.
+        let mixed = format!("{}vt340|\tregis\nbroken line\n", contents);
+        assert_eq!(parse_graphics_cache(&mixed), cache);
+    }
+
+    /// `RESTRICTED` is set once per process, like `READ_ONLY`, so each check runs in a child test
+    /// process: the TUI actions in `restricted_child`, and each network subcommand, which exits.
+    #[test]
+    fn restricted_mode_blocks_every_gated_action() {
+        let dir = temp_dir("restricted");
+        let run = |part: &str| {
+            Command::new(env::current_exe().unwrap())
+                .args([
+                    "--exact",
+                    "tests::restricted_child",
+                    "--ignored",
+                    "--nocapture",
+                ])
+                .env("JOHNJEONG_TEST_FIXTURE", &dir)
+                .env("JOHNJEONG_TEST_PART", part)
+                .output()
+                .unwrap()
+        };
+        let output = run("actions");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+        for part in ["verify-urls", "check-links"] {
+            let output = run(part);
+            assert_eq!(output.status.code(), Some(2), "{}", part);
+            assert!(String::from_utf8_lossy(&output.stderr)
+                .contains("Network access for content is disabled in restricted mode."));
         }
+        let _ = fs::remove_dir_all(&dir);
     }
-    ensure_remote_content(status)
-}
 
-fn load_header_data() -> HeaderData {
-    let mut title = env::var("JOHNJEONG_TITLE").unwrap_or_else(|_| "John Jeong".to_string());
-    let mut subtitle = env::var("JOHNJEONG_SUBTITLE")
-        .unwrap_or_else(|_| "Co-founder & Co-CEO at Hyprnote".to_string());
+    #[test]
+    #[ignore = "run by restricted_mode_blocks_every_gated_action"]
+    fn restricted_child() {
+        let root = child_fixture(&RESTRICTED);
+        let essays = root.join("content").join("essays");
+        let journals = root.join("content").join("journals");
+        let file = essays.join("hello.md");
 
-    if let Some(header_path) = resolve_header_path() {
-        if let Ok(contents) = fs::read_to_string(header_path) {
-            if let Some(value) = extract_quoted_value(&contents, "title =") {
-                title = value;
+        match env::var("JOHNJEONG_TEST_PART").unwrap().as_str() {
+            "verify-urls" => {
+                let command = Subcommand::VerifyUrls {
+                    source: None,
+                    tab: None,
+                };
+                let _ = run_subcommand(command, &Config::load().unwrap());
+                panic!("verify-urls ran in restricted mode");
             }
-            if let Some(value) = extract_quoted_value(&contents, "subtitle =") {
-                subtitle = strip_html_tags(&value);
+            "check-links" => {
+                let _ = run_subcommand(
+                    Subcommand::CheckLinks { tab: None },
+                    &Config::load().unwrap(),
+                );
+                panic!("check-links ran in restricted mode");
             }
+            _ => {}
+        }
+
+        let mut hello = post("hello", "2024-01-01", &[]);
+        hello.body = "Body".to_string();
+        hello.file = Some(file.clone());
+        let mut tab = essays_tab(vec![hello]);
+        tab.local_dir = Some(essays.clone());
+        let data = app_data(vec![tab]);
+        let theme = Theme::preset("default").unwrap();
+        let blocked = |action: fn(&AppData, &mut AppState), message: &str| {
+            let mut state = AppState::new(theme, Keymap::from_config(None).unwrap());
+            action(&data, &mut state);
+            assert_eq!(state.status.as_deref(), Some(message));
+            assert!(state.hand_off.is_none());
+            assert!(state.save_pending.is_none());
+        };
+        blocked(open_editor, "Opening files is disabled in restricted mode.");
+        blocked(
+            open_pager,
+            "Starting other programs is disabled in restricted mode.",
+        );
+        blocked(
+            save_selected,
+            "Writing files is disabled in restricted mode.",
+        );
+        blocked(
+            open_tab_dir,
+            "Revealing directories is disabled in restricted mode.",
+        );
+        let clipboard = "Copying to the clipboard is disabled in restricted mode.";
+        blocked(copy_selected_url, clipboard);
+        blocked(copy_selected_body, clipboard);
+        let err = create_daily_log(&journals, local_today()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Writing files is disabled in restricted mode."
+        );
+        assert_eq!(fs::read_dir(&journals).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(&essays).unwrap().count(), 1);
+    }
+
+    fn edit(editor: &mut LineEditor, keys: &[(KeyCode, KeyModifiers)]) -> (String, usize) {
+        for &(code, modifiers) in keys {
+            assert!(
+                editor.handle_key(KeyEvent::new(code, modifiers)),
+                "{:?}",
+                code
+            );
         }
+        (editor.text.clone(), editor.cursor)
     }
 
-    HeaderData { title, subtitle }
-}
+    const NONE: KeyModifiers = KeyModifiers::NONE;
+    const CTRL: KeyModifiers = KeyModifiers::CONTROL;
 
-fn resolve_header_path() -> Option<PathBuf> {
-    let mut dir = env::current_dir().ok()?;
-    for _ in 0..6 {
-        let candidate = dir.join("src/components/Header.astro");
-        if candidate.is_file() {
-            return Some(candidate);
+    #[test]
+    fn line_editor_moves_by_whole_characters() {
+        // 한 and 글 are three bytes each, é two.
+        let mut editor = LineEditor::new("a한글é");
+        assert_eq!(editor.cursor, 9);
+        assert_eq!(edit(&mut editor, &[(KeyCode::Left, NONE)]).1, 7);
+        assert_eq!(edit(&mut editor, &[(KeyCode::Left, NONE)]).1, 4);
+        assert_eq!(edit(&mut editor, &[(KeyCode::Left, NONE)]).1, 1);
+        assert_eq!(edit(&mut editor, &[(KeyCode::Left, NONE)]).1, 0);
+        assert_eq!(edit(&mut editor, &[(KeyCode::Left, NONE)]).1, 0);
+        assert_eq!(edit(&mut editor, &[(KeyCode::Right, NONE)]).1, 1);
+        assert_eq!(edit(&mut editor, &[(KeyCode::Right, NONE)]).1, 4);
+        assert_eq!(
+            edit(&mut editor, &[(KeyCode::Char('x'), NONE)]),
+            ("a한x글é".to_string(), 5)
+        );
+        assert_eq!(
+            edit(&mut editor, &[(KeyCode::End, NONE), (KeyCode::Right, NONE)]).1,
+            10
+        );
+    }
+
+    #[test]
+    fn line_editor_jumps_to_either_end() {
+        let mut editor = LineEditor::new("hello");
+        assert_eq!(edit(&mut editor, &[(KeyCode::Home, NONE)]).1, 0);
+        assert_eq!(edit(&mut editor, &[(KeyCode::End, NONE)]).1, 5);
+        assert_eq!(edit(&mut editor, &[(KeyCode::Char('a'), CTRL)]).1, 0);
+        assert_eq!(edit(&mut editor, &[(KeyCode::Char('e'), CTRL)]).1, 5);
+        assert_eq!(editor.text, "hello");
+    }
+
+    #[test]
+    fn line_editor_deletes_at_both_ends() {
+        let mut editor = LineEditor::new("ab한");
+        // Nothing after the cursor to delete, then one whole character before it.
+        assert_eq!(
+            edit(&mut editor, &[(KeyCode::Delete, NONE)]),
+            ("ab한".to_string(), 5)
+        );
+        assert_eq!(
+            edit(&mut editor, &[(KeyCode::Backspace, NONE)]),
+            ("ab".to_string(), 2)
+        );
+        assert_eq!(
+            edit(
+                &mut editor,
+                &[(KeyCode::Home, NONE), (KeyCode::Backspace, NONE)]
+            ),
+            ("ab".to_string(), 0)
+        );
+        assert_eq!(
+            edit(&mut editor, &[(KeyCode::Delete, NONE)]),
+            ("b".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn line_editor_deletes_words_and_lines() {
+        let mut editor = LineEditor::new("foo bar   ");
+        assert_eq!(
+            edit(&mut editor, &[(KeyCode::Char('w'), CTRL)]),
+            ("foo ".to_string(), 4)
+        );
+        assert_eq!(
+            edit(&mut editor, &[(KeyCode::Char('w'), CTRL)]),
+            (String::new(), 0)
+        );
+        // Only the word before the cursor goes; the space after it and the rest stay.
+        let mut editor = LineEditor::new("one two three");
+        assert_eq!(
+            edit(
+                &mut editor,
+                &[
+                    (KeyCode::Left, NONE),
+                    (KeyCode::Left, NONE),
+                    (KeyCode::Left, NONE),
+                    (KeyCode::Left, NONE),
+                    (KeyCode::Left, NONE),
+                    (KeyCode::Left, NONE),
+                    (KeyCode::Char('w'), CTRL),
+                ]
+            ),
+            ("one  three".to_string(), 4)
+        );
+        assert_eq!(
+            edit(&mut editor, &[(KeyCode::Char('u'), CTRL)]),
+            (String::new(), 0)
+        );
+    }
+
+    #[test]
+    fn line_editor_pastes_onto_one_line() {
+        let mut editor = LineEditor::new("[]");
+        editor.cursor = 1;
+        editor.insert("line one\nline\ttwo\u{7}\u{1b}[0m\r");
+        assert_eq!(editor.text, "[line one line two[0m]");
+        assert_eq!(editor.cursor, editor.text.len() - 1);
+        assert!(!editor.handle_key(KeyEvent::new(KeyCode::Enter, NONE)));
+    }
+
+    #[test]
+    fn clamp_text_fits_hangul_emoji_and_ascii_by_columns() {
+        let cases: [(&str, usize, &str); 17] = [
+            ("hello world", 11, "hello world"),
+            ("hello world", 5, "hell…"),
+            ("hello world", 1, "…"),
+            ("hello world", 0, ""),
+            // Ten columns of Hangul, two per syllable.
+            ("안녕하세요", 10, "안녕하세요"),
+            ("안녕하세요", 9, "안녕하세…"),
+            // Cutting through 하 would leave half a glyph, so it goes whole.
+            ("안녕하세요", 6, "안녕…"),
+            ("안녕하세요", 2, "…"),
+            ("안녕하세요", 1, "…"),
+            ("안녕하세요", 0, ""),
+            ("👍 ok 🎉", 8, "👍 ok 🎉"),
+            ("👍 ok 🎉", 7, "👍 ok …"),
+            ("👍 ok 🎉", 6, "👍 ok…"),
+            ("👍 ok 🎉", 2, "…"),
+            ("Rust 한국어 guide", 17, "Rust 한국어 guide"),
+            ("Rust 한국어 guide", 9, "Rust 한…"),
+            ("Rust 한국어 guide", 8, "Rust 한…"),
+        ];
+        for (text, width, clamped) in cases {
+            assert_eq!(clamp_text(text, width), clamped, "{} at {}", text, width);
         }
-        if !dir.pop() {
-            break;
+        // A joined emoji family is one two-column glyph and is never split.
+        assert_eq!(
+            clamp_text("👨\u{200D}👩\u{200D}👧 hi", 3),
+            "👨\u{200D}👩\u{200D}👧…"
+        );
+        for text in [
+            "안녕하세요",
+            "👍 ok 🎉",
+            "Rust 한국어 guide",
+            "e\u{301}te\u{301}",
+        ] {
+            for width in 0..=display_width(text) + 1 {
+                let clamped = clamp_text(text, width);
+                assert!(display_width(&clamped) <= width, "{} at {}", text, width);
+                assert!(clamped == text || clamped.ends_with('…') || width == 0);
+            }
         }
     }
-    None
-}
 
-fn extract_quoted_value(contents: &str, marker: &str) -> Option<String> {
-    let index = contents.find(marker)?;
-    let after_marker = &contents[index + marker.len()..];
-    let quote_pos = after_marker.find(['"', '\''])?;
-    let quote_char = after_marker.chars().nth(quote_pos)?;
-    let rest = &after_marker[quote_pos + 1..];
-    let end = rest.find(quote_char)?;
-    Some(rest[..end].trim().to_string())
-}
+    /// Each wrapped row of `text` at `width` as plain text.
+    fn wrapped(text: &str, width: usize) -> Vec<String> {
+        wrap_markdown(text, width, &[], &mut Vec::new(), "")
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.text.as_str()).collect())
+            .collect()
+    }
 
-fn strip_html_tags(input: &str) -> String {
-    let mut output = String::new();
-    let mut in_tag = false;
-    for ch in input.chars() {
-        match ch {
-            '<' => in_tag = true,
-            '>' => in_tag = false,
-            _ if !in_tag => output.push(ch),
-            _ => {}
+    #[test]
+    fn wrap_line_fits_hangul_and_mixed_text_in_columns() {
+        let hangul = "다람쥐 헌 쳇바퀴에 타고파 키스의 고유조건은 입술끼리 만나야 하고 \
+                      특별한 기술은 필요치 않다 동해 물과 백두산이 마르고 닳도록";
+        let mixed = "Rust와 TypeScript로 작은 CLI를 만들면서 배운 것: 터미널은 \
+                     display width를 셀 때 Hangul을 두 칸으로 본다, so wrap by columns.";
+        for text in [hangul, mixed] {
+            let rows = wrapped(text, 30);
+            assert!(rows.len() > 2);
+            for row in &rows {
+                assert!(
+                    display_width(row) <= 30,
+                    "{:?} is {} wide",
+                    row,
+                    display_width(row)
+                );
+            }
+            // Nothing is lost or split inside a word.
+            assert_eq!(rows.join(" "), text);
+        }
+
+        let rows = wrapped(&format!("- {}", hangul), 30);
+        assert!(rows[0].starts_with("- 다람쥐"));
+        for row in &rows[1..] {
+            assert!(
+                row.starts_with("  ") && !row.starts_with("   "),
+                "{:?}",
+                row
+            );
+        }
+        for row in &rows {
+            assert!(display_width(row) <= 30, "{:?}", row);
         }
     }
-    output.split_whitespace().collect::<Vec<_>>().join(" ")
-}
 
-fn load_posts(dir: &Path, base_url: &str, published_only: bool) -> io::Result<Vec<Post>> {
-    if !dir.is_dir() {
-        return Ok(Vec::new());
+    #[test]
+    fn wrap_line_breaks_a_long_url_under_its_bullet() {
+        let url = format!("https://example.com/{}", "a1b2c3d4e5".repeat(18));
+        assert_eq!(url.len(), 200);
+        let rows = wrapped(&format!("- links\n  - {}", url), 40);
+        assert_eq!(rows[0], "- links");
+        assert!(rows[1].starts_with("  - https://"));
+        for row in &rows[1..] {
+            assert!(display_width(row) <= 40, "{:?}", row);
+        }
+        // Continuation rows hang under the nested item's text.
+        for row in &rows[2..] {
+            assert!(
+                row.starts_with("    ") && !row.starts_with("     "),
+                "{:?}",
+                row
+            );
+        }
+        let pieces = rows[1..]
+            .iter()
+            .map(|row| row.trim_start().trim_start_matches("- "))
+            .collect::<String>();
+        assert_eq!(pieces, url);
+
+        // The cut is only in the rows: `L` reads links from the body and gets the whole URL.
+        let body = format!("- links\n  - {}", url);
+        let links = body_links(&body, None);
+        assert!(links == [(url.clone(), LinkTarget::Web(url.clone()))]);
+    }
+
+    #[test]
+    fn numbered_items_hang_under_their_own_marker() {
+        let list = (1..=10)
+            .map(|n| format!("{}. item number {} wraps here", n, n))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let rows = wrapped(&list, 20);
+        for row in &rows {
+            assert!(display_width(row) <= 20, "{:?}", row);
+        }
+        let item = |n: usize| {
+            let start = rows
+                .iter()
+                .position(|row| row.starts_with(&format!("{}. ", n)))
+                .unwrap();
+            let end = rows[start + 1..]
+                .iter()
+                .position(|row| !row.starts_with(' '))
+                .map_or(rows.len(), |offset| start + 1 + offset);
+            rows[start..end].to_vec()
+        };
+        let nine = item(9);
+        let ten = item(10);
+        assert!(nine.len() > 1 && ten.len() > 1);
+        let hang = |row: &String| row.len() - row.trim_start().len();
+        assert!(nine[1..].iter().all(|row| hang(row) == 3), "{:?}", nine);
+        assert!(ten[1..].iter().all(|row| hang(row) == 4), "{:?}", ten);
+
+        assert_eq!(list_marker("1) item"), Some(3));
+        assert_eq!(list_marker("10) item"), Some(4));
+        assert_eq!(list_marker("1.5 million"), None);
+        assert_eq!(
+            wrapped("1) first item wraps past twenty", 20),
+            ["1) first item wraps", "   past twenty"]
+        );
+    }
+
+    #[test]
+    fn snippets_keep_whole_words_around_the_match() {
+        let text = "Simple tools last longer because there is less in them to break, \
+                    and the parts that remain are the ones people actually reach for \
+                    every day, which keeps them honest and keeps them small";
+        let at = |needle| extract_snippet(text, needle).unwrap().marked("[", "]");
+        assert_eq!(
+            at("simple"),
+            "[Simple] tools last longer because there is less in them to break,…"
+        );
+        assert_eq!(
+            at("parts"),
+            "…in them to break, and the [parts] that remain are the ones people actually \
+             reach for every…"
+        );
+        assert_eq!(at("small"), "…them honest and keeps them [small]");
+        assert!(extract_snippet(text, "missing").is_none());
+    }
+
+    #[test]
+    fn snippets_cut_multibyte_text_between_characters() {
+        let text = "오늘은 비가 와서 집에서 커피를 마시며 오래된 노트를 다시 읽었다. \
+                    Café 이야기가 나오는 부분에서 잠시 멈췄고, 그 뒤로는 천천히 \
+                    정리하면서 새 글의 초안을 적어 내려갔다.";
+        let snippet = extract_snippet(text, "CAFÉ").unwrap();
+        assert_eq!(
+            &snippet.text[snippet.match_start..snippet.match_end],
+            "Café"
+        );
+        assert_eq!(
+            snippet.marked("[", "]"),
+            "…집에서 커피를 마시며 오래된 노트를 다시 읽었다. [Café] 이야기가 나오는 부분에서 \
+             잠시 멈췄고, 그 뒤로는 천천히 정리하면서 새 글의 초안을 적어 내려갔다."
+        );
+    }
+
+    #[test]
+    fn search_without_hits_reports_failure() {
+        let mut hello = post("hello", "2024-01-01", &[]);
+        hello.body = "Nothing to see here.".to_string();
+        let data = app_data(vec![essays_tab(vec![hello])]);
+        assert_eq!(search_posts(&data, "nowhere", None).len(), 0);
+        // `false` is what makes `--search` exit 1; with no hits it prints nothing.
+        assert!(!run_search(&data, None, "nowhere", None, false).unwrap());
+        assert_eq!(search_posts(&data, "see", None).len(), 1);
+    }
+
+    #[test]
+    fn file_urls_escape_spaces_hashes_percents_and_unicode() {
+        assert_eq!(
+            file_url(Path::new("/photos/my trip #1/100% café.jpg")),
+            "file:///photos/my%20trip%20%231/100%25%20caf%C3%A9.jpg"
+        );
+        assert_eq!(
+            file_url(Path::new("/사진/서울 야경.png")),
+            "file:///%EC%82%AC%EC%A7%84/%EC%84%9C%EC%9A%B8%20%EC%95%BC%EA%B2%BD.png"
+        );
+        assert_eq!(
+            file_url(Path::new("/a/b-c_d.e~f?g&h")),
+            "file:///a/b-c_d.e~f%3Fg%26h"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let latin1 = Path::new(std::ffi::OsStr::from_bytes(b"/photos/caf\xe9.jpg"));
+            assert_eq!(file_url(latin1), "file:///photos/caf%E9.jpg");
+        }
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts_and_folds_case() {
+        let boundary = fuzzy_score("Terminal user interface", "tui").unwrap();
+        let scattered = fuzzy_score("Notes on structuring ideas", "tui").unwrap();
+        assert!(boundary > scattered, "{} <= {}", boundary, scattered);
+        // Inside a word, a run of hits beats the same letters spread out.
+        assert!(fuzzy_score("trust", "rust") > fuzzy_score("arxuxsxt", "rust"));
+
+        assert_eq!(fuzzy_score("terminal", "tx"), None);
+        assert_eq!(fuzzy_score("ut", "tu"), None);
+        assert_eq!(fuzzy_score("", "a"), None);
+
+        assert_eq!(
+            fuzzy_score("Terminal UI", "tui"),
+            fuzzy_score("terminal ui", "TUI")
+        );
+        assert!(fuzzy_score("Écrire tous les jours", "éc").is_some());
+        // Spaces in the query are ignored.
+        assert_eq!(
+            fuzzy_score("terminal ui", "t u i"),
+            fuzzy_score("terminal ui", "tui")
+        );
+    }
+
+    #[test]
+    fn picker_esc_leaves_the_tab_selection_and_scroll_alone() {
+        let mut data = three_tabs();
+        let mut state = app_state();
+        press(&mut data, &mut state, "2 j");
+        state.list_scroll = 1;
+        state.content_scroll = 7;
+        let before = (
+            state.tab_index,
+            state.list_index,
+            state.list_scroll,
+            state.content_scroll,
+        );
+        press(&mut data, &mut state, "p t h i r d down esc");
+        assert!(state.picker.is_none());
+        assert_eq!(
+            (
+                state.tab_index,
+                state.list_index,
+                state.list_scroll,
+                state.content_scroll
+            ),
+            before
+        );
+        // Enter, by contrast, goes to the pick.
+        press(&mut data, &mut state, "p t h i r d enter");
+        assert_eq!((state.tab_index, state.list_index), (0, 0));
+    }
+
+    /// The tab bar and post list as written to the terminal, escapes and all.
+    fn snapshot(data: &AppData, state: &mut AppState) -> (String, String) {
+        let mut nav = Vec::new();
+        render_nav(&mut nav, data, state, 0, 80).unwrap();
+        let TabData::Content(tab) = &data.tabs[state.tab_index] else {
+            panic!("not a content tab");
+        };
+        let posts = visible_posts(tab, state);
+        let mut list = Vec::new();
+        render_post_list(&mut list, state, tab, &posts, &HashMap::new(), 2, 3, 24, 5).unwrap();
+        (
+            String::from_utf8(nav).unwrap(),
+            String::from_utf8(list).unwrap(),
+        )
+    }
+
+    #[test]
+    fn focus_indicators_in_normal_and_high_contrast_modes() {
+        let data = three_tabs();
+        let rows = "\x1b[5;3H\x1b[38;5;15m  2024-02-01 second\x1b[38;5;8m\x1b[0m\
+                    \x1b[6;3H\x1b[38;5;15m  2024-01-01 first\x1b[38;5;8m\x1b[0m";
+
+        // Normal mode: an underlined tab, a plain heading, and the theme's selection colors.
+        let mut state = app_state();
+        let (nav, list) = snapshot(&data, &mut state);
+        assert_eq!(
+            nav,
+            "\x1b[1;3H\x1b[4m1. Essays\x1b[0m\x1b[1;15H2. Daily Logs\x1b[1;31H3. Lessons"
+        );
+        assert_eq!(
+            list,
+            format!(
+                "\x1b[3;3H\x1b[38;5;15mPosts\x1b[0m\
+                 \x1b[4;3H\x1b[38;5;0m\x1b[48;5;15m› 2024-03-01 third      \x1b[0m{}",
+                rows
+            )
+        );
+
+        // High contrast: an inverted tab badge, a bracketed heading, and a bold inverted bar.
+        state.high_contrast = true;
+        let (nav, list) = snapshot(&data, &mut state);
+        assert_eq!(
+            nav,
+            "\x1b[1;3H\x1b[1m\x1b[7m1. Essays\x1b[0m\x1b[1;15H2. Daily Logs\x1b[1;31H3. Lessons"
+        );
+        assert_eq!(
+            list,
+            format!(
+                "\x1b[3;3H\x1b[38;5;15m[ Posts ]\x1b[0m\
+                 \x1b[4;3H\x1b[1m\x1b[7m› 2024-03-01 third      \x1b[0m{}",
+                rows
+            )
+        );
+
+        // Only the focused pane is bracketed.
+        state.focus = Focus::Content;
+        let (_, list) = snapshot(&data, &mut state);
+        assert!(list.contains("mPosts\x1b[0m"), "{:?}", list);
     }
 
-    let mut posts = Vec::new();
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
-            continue;
+    #[test]
+    fn slugify_matches_the_site_for_each_preset() {
+        let cases = [
+            // (file name, astro URL segment)
+            ("hello-world", "hello-world"),
+            ("Hello World", "hello-world"),
+            ("Why I Left Big Tech", "why-i-left-big-tech"),
+            ("Café Society", "cafe-society"),
+            ("Straße nach Köln", "strasse-nach-koln"),
+            ("What's next?", "whats-next"),
+            ("Rust & Go", "rust--go"),
+            ("über_alles", "uber_alles"),
+            ("2024-01-15", "2024-01-15"),
+            ("  Padded  ", "padded"),
+            ("한국어 노트", "한국어-노트"),
+            ("Ship it! 🚀", "ship-it-"),
+        ];
+        for (stem, astro) in cases {
+            assert_eq!(slugify(stem, SlugPreset::Astro), astro, "astro: {:?}", stem);
+            assert_eq!(slugify(stem, SlugPreset::Plain), stem, "plain: {:?}", stem);
         }
 
-        let content = fs::read_to_string(&path)?;
-        let (frontmatter, body) = split_frontmatter(&content);
+        // A frontmatter slug beats either preset.
+        let frontmatter = HashMap::from([("slug".to_string(), " /my-slug/ ".to_string())]);
+        assert_eq!(post_slug("Anything At All", &frontmatter), "my-slug");
+    }
 
-        if published_only {
-            if let Some(published) = frontmatter.get("published") {
-                if published.to_lowercase() != "true" {
-                    continue;
-                }
-            }
+    #[test]
+    fn slug_from_title_keeps_letters_in_any_script() {
+        let cases = [
+            ("Why simple wins", "why-simple-wins"),
+            ("Café Society", "cafe-society"),
+            ("Don't Panic", "dont-panic"),
+            ("It’s fine", "its-fine"),
+            ("Rust & Go: a comparison", "rust-go-a-comparison"),
+            ("  too -- many   separators  ", "too-many-separators"),
+            ("...leading and trailing!!!", "leading-and-trailing"),
+            ("한국어로 쓴 글", "한국어로-쓴-글"),
+            ("Über 2024", "uber-2024"),
+            ("", ""),
+            ("?!", ""),
+            ("🚀", ""),
+        ];
+        for (title, slug) in cases {
+            assert_eq!(slug_from_title(title), slug, "{:?}", title);
         }
 
-        let slug = path
-            .file_stem()
-            .and_then(|stem| stem.to_str())
-            .unwrap_or("post");
+        // Plain titles survive the trip through a file name and back.
+        for title in ["Why Simple Wins", "Notes On Rust 2024", "한국어 노트"] {
+            assert_eq!(title_from_slug(&slug_from_title(title)), title);
+        }
+        for slug in ["why-simple-wins", "a-b-c", "2024-recap"] {
+            assert_eq!(slug_from_title(&title_from_slug(slug)), slug);
+        }
+    }
 
-        let date = frontmatter
-            .get("created_at")
-            .cloned()
-            .or_else(|| date_from_slug(slug));
+    #[test]
+    fn new_posts_never_overwrite_a_file() {
+        let dir = temp_dir("new-post");
+        let path = dir.join("why-simple-wins.md");
+        create_post(&path, "Why \"simple\" wins").unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("---\ntitle: 'Why \"simple\" wins'\ncreated_at: "));
+        assert!(written.contains("\npublished: false\n---\n"));
 
-        let title = frontmatter
-            .get("title")
-            .cloned()
-            .unwrap_or_else(|| title_from_slug(slug));
+        let err = create_post(&path, "Something else").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), written);
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
-        let description = frontmatter.get("description").cloned().unwrap_or_default();
-        let body_text = body.trim();
-        let body = if body_text.is_empty() && !description.is_empty() {
-            description
-        } else {
-            body_text.to_string()
+    #[test]
+    fn xml_escape_covers_markup_quotes_and_control_characters() {
+        assert_eq!(
+            xml_escape(r#"Rust & Go <3 "fast" 'safe'>"#),
+            "Rust &amp; Go &lt;3 &quot;fast&quot; &apos;safe&apos;&gt;"
+        );
+        assert_eq!(xml_escape("&amp;"), "&amp;amp;");
+        assert_eq!(xml_escape("tab\there\u{7}bell"), "tab\therebell");
+        assert_eq!(xml_escape("한국어 ✓"), "한국어 ✓");
+    }
+
+    #[test]
+    fn feeds_escape_titles_and_leave_out_bad_dates() {
+        let header = HeaderData {
+            title: "Notes & <Drafts>".to_string(),
+            subtitle: String::new(),
         };
+        let mut dated = post("dated", "2024-03-01", &[]);
+        dated.title = r#"Rust & Go <3 "fast""#.to_string();
+        dated.url = "https://johnjeong.com/essays/a?b=1&c=2".to_string();
+        dated
+            .fields
+            .insert("description".to_string(), "<b>bold</b> & more".to_string());
+        let mut undated = post("undated", "someday", &[]);
+        undated.body = "Plain & simple.".to_string();
+        let impossible = post("impossible", "2024-02-30", &[]);
+        let tab = essays_tab(vec![dated, undated, impossible]);
 
-        let sort_key = date.clone().unwrap_or_else(|| slug.to_string());
-        let url = format!("{}/{}", base_url.trim_end_matches('/'), slug);
+        let rss = rss_feed(&header, &tab, "https://johnjeong.com", None);
+        assert!(rss.contains("<title>Notes &amp; &lt;Drafts&gt; — Essays</title>"));
+        assert!(rss.contains("<title>Rust &amp; Go &lt;3 &quot;fast&quot;</title>"));
+        assert!(rss.contains("<link>https://johnjeong.com/essays/a?b=1&amp;c=2</link>"));
+        assert!(rss.contains("<description>&lt;b&gt;bold&lt;/b&gt; &amp; more</description>"));
+        assert!(rss.contains("<description>Plain &amp; simple.</description>"));
+        // Only the real date makes it out, as RFC 822.
+        assert_eq!(rss.matches("<pubDate>").count(), 1);
+        assert!(rss.contains("<pubDate>Fri, 01 Mar 2024 00:00:00 +0000</pubDate>"));
+        assert!(!rss.contains("someday") && !rss.contains("2024-02-30"));
+        assert_eq!(rss.matches("<item>").count(), 3);
 
-        posts.push(Post {
-            title,
-            date: date.unwrap_or_default(),
-            body,
-            url,
-            sort_key,
-        });
+        let atom = atom_feed(&header, &tab, "https://johnjeong.com", None);
+        assert!(atom.contains("<title>Rust &amp; Go &lt;3 &quot;fast&quot;</title>"));
+        assert!(atom.contains("<link href=\"https://johnjeong.com/essays/a?b=1&amp;c=2\"/>"));
+        assert!(atom.contains("<name>Notes &amp; &lt;Drafts&gt;</name>"));
+        // Atom needs an `updated` per entry, so undated ones borrow the feed's.
+        assert_eq!(
+            atom.matches("<updated>2024-03-01T00:00:00Z</updated>")
+                .count(),
+            4
+        );
+        assert_eq!(atom.matches("<published>").count(), 1);
     }
 
-    posts.sort_by(|a, b| b.sort_key.cmp(&a.sort_key));
-    Ok(posts)
-}
+    #[test]
+    fn sort_keys_take_an_optional_direction() {
+        let keys = |value: &str| parse_sort(value).unwrap();
+        assert_eq!(keys("date"), [("date".to_string(), false)]);
+        assert_eq!(
+            keys(" date DESC , title asc,order "),
+            [
+                ("date".to_string(), true),
+                ("title".to_string(), false),
+                ("order".to_string(), false),
+            ]
+        );
+        assert_eq!(parse_sort("date,").unwrap_err(), "empty sort key");
+        assert_eq!(
+            parse_sort("date newest").unwrap_err(),
+            "unexpected `newest` after `date`"
+        );
+        assert_eq!(parse_sort("date desc now").unwrap_err(), "unexpected `now`");
+    }
 
-fn split_frontmatter(contents: &str) -> (HashMap<String, String>, &str) {
-    let mut map = HashMap::new();
-    if !contents.starts_with("---") {
-        return (map, contents);
+    #[test]
+    fn filters_bind_not_then_and_then_or() {
+        let mut featured = post("featured", "2024-01-01", &["rust"]);
+        featured
+            .fields
+            .insert("featured".to_string(), "true".to_string());
+        featured
+            .fields
+            .insert("series".to_string(), "Notes On Rust".to_string());
+        let plain = post("plain", "2024-01-02", &[]);
+        let matches =
+            |expression: &str, post: &Post| PostFilter::parse(expression).unwrap().matches(post);
+
+        // `&&` binds tighter than `||`: this is `featured || (hidden && draft)`.
+        assert!(matches("featured || hidden && draft", &featured));
+        assert!(!matches("(featured || hidden) && draft", &featured));
+        // `!` binds tighter than `&&`.
+        assert!(matches("!hidden && featured", &featured));
+        assert!(!matches("!(hidden || featured)", &featured));
+        assert!(matches("!!featured", &featured));
+
+        assert!(matches(r#"series == "notes on rust""#, &featured));
+        assert!(matches("tag == RUST && tags", &featured));
+        assert!(matches("tag != rust", &plain));
+        assert!(!matches("tags", &plain));
+        assert!(matches("title==plain&&!featured", &plain));
     }
 
-    let mut offset = 0usize;
-    let mut lines = contents.lines();
-    let first = lines.next();
-    if first != Some("---") {
-        return (map, contents);
+    #[test]
+    fn filter_errors_name_the_token() {
+        let error = |expression: &str| match PostFilter::parse(expression) {
+            Ok(_) => panic!("{:?} parsed", expression),
+            Err(message) => message,
+        };
+        assert_eq!(error("featured &"), "unexpected `&`");
+        assert_eq!(error("a = b"), "unexpected `=`");
+        assert_eq!(error(r#"title == "open"#), r#"unterminated string `"open`"#);
+        assert_eq!(error("featured )"), "unexpected `)`");
+        assert_eq!(error("(featured draft"), "expected `)` but found `draft`");
+        assert_eq!(error("(featured"), "missing `)`");
+        assert_eq!(
+            error("tag == && draft"),
+            "expected a value after `==` but found `&&`"
+        );
+        assert_eq!(error("tag !="), "expected a value after `!=`");
+        assert_eq!(error("featured &&"), "unexpected end of expression");
+        assert_eq!(error("|| draft"), "unexpected `||`");
+        assert_eq!(error(""), "unexpected end of expression");
     }
-    offset += first.unwrap_or("").len() + 1;
 
-    for line in lines {
-        if line == "---" {
-            offset += line.len() + 1;
-            break;
-        }
-        if let Some((key, value)) = line.split_once(':') {
-            map.insert(
-                key.trim().to_string(),
-                clean_frontmatter_value(value.trim()),
-            );
+    #[test]
+    fn atom_updated_ignores_posts_the_tab_hides() {
+        let header = HeaderData {
+            title: "Test".to_string(),
+            subtitle: String::new(),
+        };
+        let mut hidden = post("hidden", "2025-06-01", &[]);
+        hidden
+            .fields
+            .insert("hidden".to_string(), "true".to_string());
+        let mut tab = essays_tab(vec![hidden, post("shown", "2024-03-01", &[])]);
+        tab.filter = Some(PostFilter::parse("!hidden").unwrap());
+        let atom = atom_feed(&header, &tab, "https://johnjeong.com", None);
+        assert!(atom.contains("  <updated>2024-03-01T00:00:00Z</updated>\n"));
+        assert!(!atom.contains("2025-06-01"));
+    }
+
+    #[test]
+    fn content_root_prefers_flag_then_env_then_config_then_search() {
+        let root = temp_dir("content-root");
+        for dir in ["flag", "env", "config", "part-of-my-brain", "work/site/src"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
         }
-        offset += line.len() + 1;
+        let start = root.join("work/site/src");
+        let file = HashMap::from([(
+            "content".to_string(),
+            HashMap::from([(
+                "dir".to_string(),
+                root.join("config").to_string_lossy().into_owned(),
+            )]),
+        )]);
+        let env_dir = root.join("env").to_string_lossy().into_owned();
+        let with_env = |name: &str| (name == "JOHNJEONG_CONTENT_DIR").then(|| env_dir.clone());
+        let without_env = |_: &str| None;
+        let located = |config: Config, flag: Option<&str>| {
+            let configured = match flag {
+                Some(flag) => Some(content_dir_flag(flag).unwrap()),
+                None => config.content_dir,
+            };
+            locate_content_root(configured.as_deref(), Some(&start))
+        };
+
+        let flag = root.join("flag").to_string_lossy().into_owned();
+        let config = Config::resolve(&file, with_env).unwrap();
+        assert_eq!(located(config, Some(&flag)), Some(root.join("flag")));
+        let config = Config::resolve(&file, with_env).unwrap();
+        assert_eq!(located(config, None), Some(root.join("env")));
+        let config = Config::resolve(&file, without_env).unwrap();
+        assert_eq!(located(config, None), Some(root.join("config")));
+        let config = Config::resolve(&HashMap::new(), without_env).unwrap();
+        assert_eq!(located(config, None), Some(root.join("part-of-my-brain")));
+
+        // A configured directory that's gone falls through to the search, but a bad flag is an
+        // error that names the path.
+        fs::remove_dir(root.join("config")).unwrap();
+        let config = Config::resolve(&file, without_env).unwrap();
+        assert_eq!(located(config, None), Some(root.join("part-of-my-brain")));
+        let missing = root.join("missing");
+        assert_eq!(
+            content_dir_flag(&missing.to_string_lossy()),
+            Err(format!(
+                "--content-dir: {} is not a directory.",
+                missing.display()
+            ))
+        );
+        fs::remove_dir(root.join("part-of-my-brain")).unwrap();
+        assert_eq!(locate_content_root(None, Some(&start)), None);
+        fs::remove_dir_all(&root).unwrap();
     }
 
-    let body = contents.get(offset..).unwrap_or("");
-    (map, body)
-}
+    #[test]
+    fn browser_commands_take_the_url_at_percent_s_or_last() {
+        let url = "https://johnjeong.com/essays/a?b=1&c=2";
+        let expect = |program: &str, args: &[&str]| {
+            Some((
+                program.to_string(),
+                args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>(),
+            ))
+        };
 
-fn clean_frontmatter_value(value: &str) -> String {
-    let trimmed = value.trim();
-    if (trimmed.starts_with('"') && trimmed.ends_with('"'))
-        || (trimmed.starts_with('\'') && trimmed.ends_with('\''))
-    {
-        trimmed[1..trimmed.len().saturating_sub(1)].to_string()
-    } else {
-        trimmed.to_string()
+        assert_eq!(browser_command("firefox", url), expect("firefox", &[url]));
+        assert_eq!(
+            browser_command("firefox --new-tab", url),
+            expect("firefox", &["--new-tab", url])
+        );
+        assert_eq!(
+            browser_command("  firefox \t -P   work  ", url),
+            expect("firefox", &["-P", "work", url])
+        );
+        let app = format!("--app={}", url);
+        assert_eq!(
+            browser_command("chromium --app=%s --incognito", url),
+            expect("chromium", &[&app, "--incognito"])
+        );
+        // With a `%s` the URL isn't appended again, and every `%s` is filled in.
+        assert_eq!(
+            browser_command("open-both %s %s", url),
+            expect("open-both", &[url, url])
+        );
+        assert_eq!(browser_command("", url), None);
+        assert_eq!(browser_command(" \t ", url), None);
     }
-}
 
-fn load_gallery(dir: &Path) -> io::Result<Vec<Post>> {
-    if !dir.is_dir() {
-        return Ok(Vec::new());
+    #[test]
+    fn exports_and_feeds_are_byte_identical_across_runs() {
+        let posts = [
+            (
+                "essays/second-take.md",
+                "---\ntitle: Second Take\ncreated_at: 2024-03-01\ntags: [rust, notes]\nseries: b\norder: 2\ndescription: Same day & more\n---\nBody two.\n",
+            ),
+            (
+                "essays/first-take.md",
+                "---\ntitle: First Take\ncreated_at: 2024-03-01\ntags: [notes]\nseries: a\norder: 1\n---\nBody one with <markup>.\n",
+            ),
+            (
+                "essays/undated.md",
+                "---\ntitle: Undated\nzeta: z\nalpha: a\n---\nNo date here.\n",
+            ),
+            ("journals/2024-02-01.md", "A log.\n"),
+            ("journals/2024-02-02.md", "Another log.\n"),
+        ];
+        // The same content written in opposite orders, so directory order can't line up by luck.
+        let render = |name: &str, reversed: bool| {
+            let root = temp_dir(name);
+            fs::create_dir_all(root.join("essays")).unwrap();
+            fs::create_dir_all(root.join("journals")).unwrap();
+            let mut files = posts.to_vec();
+            if reversed {
+                files.reverse();
+            }
+            for (path, contents) in files {
+                fs::write(root.join(path), contents).unwrap();
+            }
+            let file = HashMap::from([(
+                "content".to_string(),
+                HashMap::from([("dir".to_string(), root.to_string_lossy().into_owned())]),
+            )]);
+            let config = Config::resolve(&file, |_| None).unwrap();
+            let (data, _) = build_app_data(&config);
+            let mut out = Vec::new();
+            run_export(&mut out, &data, true).unwrap();
+            for tab in ["essays", "journals"] {
+                let tab = find_tab(&data, tab).unwrap();
+                out.extend(rss_feed(&data.header, tab, &config.site_url, None).into_bytes());
+                out.extend(atom_feed(&data.header, tab, &config.site_url, None).into_bytes());
+            }
+            fs::remove_dir_all(&root).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let first = render("export-a", false);
+        assert!(first.contains("\"title\": \"First Take\""));
+        assert!(first.contains("<title>Second Take</title>"));
+        assert_eq!(render("export-b", true), first);
+        assert_eq!(render("export-a", false), first);
     }
 
-    let mut posts = Vec::new();
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        let ext = path
-            .extension()
-            .and_then(|value| value.to_str())
-            .unwrap_or("");
-        let is_image = matches!(ext.to_lowercase().as_str(), "jpg" | "jpeg" | "png" | "webp");
-        if !is_image {
-            continue;
+    #[test]
+    fn paths_complete_to_directories_with_spaces_and_unicode() {
+        let root = temp_dir("complete");
+        for dir in [
+            "My Notes",
+            "My Photos",
+            "노트",
+            "notes",
+            "notes archive",
+            ".hidden",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
         }
+        fs::write(root.join("notes.md"), "").unwrap();
+        fs::write(root.join("My Notes/essay.md"), "").unwrap();
+        fs::create_dir(root.join("My Notes/2024 회고")).unwrap();
+        let root = format!("{}/", root.display());
+        let completions = |typed: &str| {
+            path_completions(&format!("{}{}", root, typed))
+                .into_iter()
+                .map(|path| path[root.len()..].to_string())
+                .collect::<Vec<_>>()
+        };
 
-        let filename = path
-            .file_stem()
-            .and_then(|stem| stem.to_str())
-            .unwrap_or("image");
+        assert_eq!(completions("My "), ["My Notes/", "My Photos/"]);
+        assert_eq!(completions("My N"), ["My Notes/"]);
+        assert_eq!(completions("노"), ["노트/"]);
+        // Files never complete; hidden directories only when asked for.
+        assert_eq!(completions("notes"), ["notes archive/", "notes/"]);
+        assert_eq!(completions("."), [".hidden/"]);
+        assert_eq!(completions("My Notes/"), ["My Notes/2024 회고/"]);
+        assert!(completions("missing/").is_empty());
+        assert!(completions("zzz").is_empty());
 
-        let sort_key = match entry.metadata().and_then(|meta| meta.modified()) {
-            Ok(modified) => match modified.duration_since(std::time::UNIX_EPOCH) {
-                Ok(duration) => format!("{:020}", duration.as_secs()),
-                Err(_) => filename.to_string(),
-            },
-            Err(_) => filename.to_string(),
+        // Tab grows the prompt to what every completion shares, and no further.
+        let mut editor = LineEditor::new(&format!("export {}My N", root));
+        let export = |before: &str| {
+            before
+                .strip_prefix("export ")
+                .map(path_completions)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|path| format!("export {}", path))
+                .collect()
         };
+        assert!(editor.complete(export));
+        assert_eq!(editor.text, format!("export {}My Notes/", root));
+        assert!(editor.complete(export));
+        assert_eq!(editor.text, format!("export {}My Notes/2024 회고/", root));
+        let mut editor = LineEditor::new(&format!("export {}notes", root));
+        assert!(!editor.complete(export));
+        assert_eq!(editor.cursor, editor.text.len());
 
-        posts.push(Post {
-            title: filename.to_string(),
-            date: String::new(),
-            body: format!("Image file: {}", path.display()),
-            url: path.to_string_lossy().to_string(),
-            sort_key,
-        });
+        fs::remove_dir_all(&root).unwrap();
     }
 
-    posts.sort_by(|a, b| b.sort_key.cmp(&a.sort_key));
-    Ok(posts)
-}
+    #[test]
+    fn editor_completes_only_at_the_cursor() {
+        let source = |before: &str| match before {
+            "ab" => vec!["abcde".to_string(), "abcdf".to_string()],
+            "x" => vec!["xé한".to_string(), "xé한글".to_string()],
+            _ => Vec::new(),
+        };
+        let mut editor = LineEditor::new("ab");
+        assert!(editor.complete(source));
+        assert_eq!((editor.text.as_str(), editor.cursor), ("abcd", 4));
+        assert!(!editor.complete(source));
 
-fn title_from_slug(slug: &str) -> String {
-    slug.replace(['-', '_'], " ")
-        .split_whitespace()
-        .map(capitalize)
-        .collect::<Vec<String>>()
-        .join(" ")
-}
+        // Completion replaces what's before the cursor and keeps the rest.
+        let mut editor = LineEditor::new("ab tail");
+        editor.cursor = 2;
+        assert!(editor.complete(source));
+        assert_eq!((editor.text.as_str(), editor.cursor), ("abcd tail", 4));
 
-fn capitalize(word: &str) -> String {
-    let mut chars = word.chars();
-    match chars.next() {
-        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
-        None => String::new(),
+        // The shared part ends between characters, not inside one.
+        let mut editor = LineEditor::new("x");
+        assert!(editor.complete(source));
+        assert_eq!(editor.text, "xé한");
     }
-}
 
-fn date_from_slug(slug: &str) -> Option<String> {
-    if slug.len() >= 10
-        && slug
-            .chars()
-            .all(|c| c.is_ascii_digit() || c == '_' || c == '-')
-    {
-        let normalized = slug.replace('_', "-");
-        return Some(normalized);
-    }
-    None
-}
+    #[test]
+    fn command_prompt_recalls_completes_and_checks_paths() {
+        let root = temp_dir("command-prompt");
+        fs::create_dir(root.join("Exports 한")).unwrap();
+        let mut data = three_tabs();
+        let mut state = app_state();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let run = |state: &mut AppState, line: &str| {
+            state.command = Some(LineEditor::new(line));
+            handle_command_key(key(KeyCode::Enter), &three_tabs(), state);
+        };
 
-fn wrap_markdown(text: &str, width: usize) -> Vec<String> {
-    let mut lines = Vec::new();
-    let width = width.max(10);
-    for raw in text.lines() {
-        if raw.trim().is_empty() {
-            lines.push(String::new());
-            continue;
-        }
+        run(&mut state, "timer 5m");
+        run(&mut state, "  timer off ");
+        run(&mut state, "timer off");
+        run(&mut state, "");
+        assert_eq!(state.command_history, ["timer 5m", "timer off"]);
 
-        let trimmed = raw.trim_end();
-        if trimmed.starts_with("- ") || trimmed.starts_with("* ") {
-            let prefix = &trimmed[..2];
-            let content = trimmed[2..].trim();
-            lines.extend(wrap_line(content, width, prefix));
-        } else {
-            lines.extend(wrap_line(trimmed, width, ""));
-        }
+        press(&mut data, &mut state, ":");
+        let text = |state: &AppState| state.command.as_ref().unwrap().text.clone();
+        handle_command_key(key(KeyCode::Up), &data, &mut state);
+        assert_eq!(text(&state), "timer off");
+        handle_command_key(key(KeyCode::Up), &data, &mut state);
+        handle_command_key(key(KeyCode::Up), &data, &mut state);
+        assert_eq!(text(&state), "timer 5m");
+        handle_command_key(key(KeyCode::Down), &data, &mut state);
+        assert_eq!(text(&state), "timer off");
+        handle_command_key(key(KeyCode::Down), &data, &mut state);
+        assert_eq!(text(&state), "");
+
+        // Tab completes the export path; a missing directory holds Enter back. Without an IO
+        // worker the jobs' events are dropped, so they are handed to `update` as the loop would.
+        let checked = |line: &str| AppEvent::CommandChecked {
+            line: line.to_string(),
+            problem: command_problem(line),
+        };
+        state.command = Some(LineEditor::new(&format!("export {}/Ex", root.display())));
+        handle_command_key(key(KeyCode::Tab), &data, &mut state);
+        assert_eq!(text(&state), format!("export {}/Ex", root.display()));
+        let completions = complete_path_command(text(&state)).unwrap();
+        update(completions, &mut data, &mut state).unwrap();
+        assert_eq!(
+            text(&state),
+            format!("export {}/Exports 한/", root.display())
+        );
+        let missing = format!("export {}/nowhere/posts.json", root.display());
+        assert_eq!(
+            command_problem(&missing),
+            Some(format!("no directory {}/nowhere", root.display()))
+        );
+        state.command = Some(LineEditor::new(&missing));
+        update(checked(&missing), &mut data, &mut state).unwrap();
+        handle_command_key(key(KeyCode::Enter), &data, &mut state);
+        assert_eq!(text(&state), missing);
+        // A completion for a line that has changed since Tab is dropped.
+        update(
+            complete_path_command(format!("export {}/Ex", root.display())).unwrap(),
+            &mut data,
+            &mut state,
+        )
+        .unwrap();
+        assert_eq!(text(&state), missing);
+        let content = format!("content {}/Exports", root.display());
+        assert_eq!(
+            command_problem(&content),
+            Some(format!("no directory {}/Exports", root.display()))
+        );
+        assert_eq!(command_problem(&format!("{} 한", content)), None);
+        assert_eq!(
+            complete_path_command(format!("content {}/E", root.display()))
+                .map(|event| match event {
+                    AppEvent::Completions { completions, .. } => completions,
+                    _ => Vec::new(),
+                })
+                .unwrap(),
+            [format!("content {}/Exports 한/", root.display())]
+        );
+        assert_eq!(
+            command_problem(&format!("export {}/Exports 한/posts.json", root.display())),
+            None
+        );
+        assert_eq!(command_problem("export posts.json"), None);
+        let exported = root.join("Exports 한/posts.json");
+        run(&mut state, &format!("export {}", exported.display()));
+        let json = fs::read_to_string(&exported).unwrap();
+        assert!(
+            json.starts_with("{\n  \"header\": {\"title\": \"Test\""),
+            "{}",
+            json
+        );
+        assert_eq!(command_problem("timer 5m"), None);
+        fs::remove_dir_all(&root).unwrap();
     }
-    lines
-}
 
-fn wrap_line(text: &str, width: usize, prefix: &str) -> Vec<String> {
-    let mut lines = Vec::new();
-    let indent = " ".repeat(prefix.len());
-    let mut current = String::new();
-    let mut first = true;
+    #[test]
+    fn expand_path_expands_home_and_variables() {
+        let home = env::var("HOME").unwrap();
+        let expanded = |input: &str| expand_path(input).to_string_lossy().into_owned();
+        assert_eq!(expanded("~"), home);
+        assert_eq!(expanded("~/notes"), format!("{}/notes", home));
+        assert_eq!(expanded("$HOME/a b"), format!("{}/a b", home));
+        assert_eq!(expanded("${HOME}x/노트"), format!("{}x/노트", home));
+        assert_eq!(expanded("/tmp/$JOHNJEONG_TEST_UNSET_VARIABLE/x"), "/tmp//x");
+        // Only a leading `~/` is home, and a `$` without a name stays.
+        assert_eq!(expanded("~user/notes"), "~user/notes");
+        assert_eq!(expanded("a~/b"), "a~/b");
+        assert_eq!(expanded("a$/b $"), "a$/b $");
+        assert_eq!(expanded("${unclosed"), "${unclosed");
+    }
 
-    for word in text.split_whitespace() {
-        let prefix_now = if first { prefix } else { &indent };
-        if current.is_empty() {
-            current = format!("{}{}", prefix_now, word);
-            first = false;
-            continue;
-        }
+    #[test]
+    fn notifications_are_cleaned_and_wrapped_for_each_terminal() {
+        assert_eq!(Notify::parse(""), Some(Notify::default()));
+        assert_eq!(Notify::parse("off"), Some(Notify::default()));
+        assert_eq!(
+            Notify::parse("true"),
+            Some(Notify {
+                bell: true,
+                ..Notify::default()
+            })
+        );
+        let all = Notify::parse("Bell, osc9 osc777").unwrap();
+        assert_eq!(
+            all,
+            Notify {
+                bell: true,
+                osc9: true,
+                osc777: true
+            }
+        );
+        assert_eq!(Notify::parse("bell, popup"), None);
 
-        if current.len() + 1 + word.len() <= width {
-            current.push(' ');
-            current.push_str(word);
-        } else {
-            lines.push(current);
-            current = format!("{}{}", prefix_now, word);
-        }
+        assert_eq!(
+            all.sequence("2 new Daily Logs", false),
+            "\x1b]9;2 new Daily Logs\x07\x1b]777;notify;johnjeong;2 new Daily Logs\x07\x07"
+        );
+        // tmux passes a sequence on when it is wrapped and its escapes doubled.
+        let osc9 = Notify::parse("osc9").unwrap();
+        assert_eq!(
+            osc9.sequence("1 new Essays", true),
+            "\x1bPtmux;\x1b\x1b]9;1 new Essays\x07\x1b\\"
+        );
+        assert_eq!(Notify::parse("bell").unwrap().sequence("x", true), "\x07");
+
+        // A title can't end the sequence early or start another, and OSC 777 fields stay apart.
+        let hostile = "Evil\x07\x1b]52;c;aGk=\x07 post;\n\ttitle\u{9c}";
+        assert_eq!(notification_text(hostile), "Evil ]52;c;aGk= post; title");
+        assert_eq!(
+            Notify::parse("osc777").unwrap().sequence(hostile, false),
+            "\x1b]777;notify;johnjeong;Evil ]52,c,aGk= post, title\x07"
+        );
+        let long = notification_text(&"word ".repeat(40));
+        assert_eq!(long.chars().count(), NOTIFY_MAX_CHARS);
+        assert!(long.ends_with("word…"), "{:?}", long);
     }
 
-    if !current.is_empty() {
-        lines.push(current);
+    #[test]
+    fn watched_changes_coalesce_and_skip_own_edits() {
+        let mut data = three_tabs();
+        let mut state = app_state();
+        let TabData::Content(logs) = &mut data.tabs[1] else {
+            panic!("not a content tab");
+        };
+        logs.local_dir = Some(PathBuf::from("/notes/journals"));
+        for post in &mut logs.posts {
+            post.file = Some(PathBuf::from(format!("/notes/journals/{}.md", post.slug)));
+        }
+        let TabData::Content(essays) = &mut data.tabs[0] else {
+            panic!("not a content tab");
+        };
+        essays.local_dir = Some(PathBuf::from("/notes/essays"));
+        essays.posts[0].file = Some(PathBuf::from("/notes/essays/third.md"));
+
+        let log = |slug: &str| {
+            (
+                PathBuf::from(format!("/notes/journals/{}.md", slug)),
+                Some(Box::new(post(slug, slug, &[]))),
+            )
+        };
+        apply_content_changes(&mut data, &mut state, vec![log("2024-02-03")]);
+        assert_eq!(state.status.as_deref(), Some("1 new Daily Logs"));
+        apply_content_changes(
+            &mut data,
+            &mut state,
+            vec![
+                log("2024-02-04"),
+                log("2024-02-01"),
+                (PathBuf::from("/notes/essays/third.md"), None),
+            ],
+        );
+        let notice = state.notice.as_ref().unwrap();
+        assert_eq!(
+            notice.message(),
+            "2 new Daily Logs, 1 updated in Daily Logs, 1 removed from Essays"
+        );
+        assert!(!notice.due());
+
+        // New posts land where they sort: newest first.
+        let TabData::Content(logs) = &data.tabs[1] else {
+            panic!("not a content tab");
+        };
+        let slugs = logs
+            .posts
+            .iter()
+            .map(|post| post.slug.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            slugs,
+            ["2024-02-04", "2024-02-03", "2024-02-02", "2024-02-01"]
+        );
+
+        // Saving a file opened with `e` updates the list without a notification.
+        state.notice = None;
+        state.status = None;
+        let edited = PathBuf::from("/notes/journals/2024-02-02.md");
+        state.own_edits.insert(edited.clone(), Instant::now());
+        apply_content_changes(
+            &mut data,
+            &mut state,
+            vec![(
+                edited,
+                Some(Box::new(post("2024-02-02", "2024-02-02", &[]))),
+            )],
+        );
+        assert!(state.notice.is_none() && state.status.is_none());
+
+        // Files outside every tab are ignored.
+        apply_content_changes(
+            &mut data,
+            &mut state,
+            vec![(PathBuf::from("/elsewhere/x.md"), None)],
+        );
+        assert!(state.notice.is_none());
     }
 
-    if lines.is_empty() {
-        lines.push(prefix.to_string());
+    #[test]
+    fn sync_status_tells_full_partial_and_offline_apart() {
+        let age = || Some("3 hours ago".to_string());
+        assert_eq!(SyncStatus::of(Ok(())), SyncStatus::Full);
+        assert_eq!(SyncStatus::Full.message(age()), None);
+
+        let limited = "fatal: unable to access 'https://github.com/a/b/': \
+                       The requested URL returned error: 429";
+        let partial = SyncStatus::of(Err(limited.to_string()));
+        assert_eq!(partial, SyncStatus::Partial(limited.to_string()));
+        assert_eq!(
+            partial.message(age()).unwrap(),
+            "Partial sync: GitHub rate limited the update, showing content last synced 3 hours \
+             ago. Set GITHUB_TOKEN to raise the limit."
+        );
+        assert!(matches!(
+            SyncStatus::of(Err("The requested URL returned error: 403".into())),
+            SyncStatus::Partial(_)
+        ));
+
+        let offline = SyncStatus::of(Err("Could not resolve host: github.com".into()));
+        assert_eq!(
+            offline.message(None).unwrap(),
+            "Offline cache, last synced at an unknown time (Could not resolve host: github.com)"
+        );
     }
 
-    lines
-}
+    #[test]
+    fn rate_limited_fetches_back_off_then_give_up() {
+        let mut calls = 0;
+        let mut waits = Vec::new();
+        let result = retry_rate_limited(
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Err("returned error: 429".to_string())
+                } else {
+                    Ok(())
+                }
+            },
+            |delay| waits.push(delay.as_secs()),
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(waits, [2, 4]);
 
-fn clamp_text(text: &str, max_width: usize) -> String {
-    if text.len() <= max_width {
-        return text.to_string();
+        let mut calls = 0;
+        let mut waits = Vec::new();
+        let result = retry_rate_limited(
+            || {
+                calls += 1;
+                Err("returned error: 403".to_string())
+            },
+            |delay| waits.push(delay.as_secs()),
+        );
+        assert!(result.is_err());
+        assert_eq!((calls, waits), (4, vec![2, 4, 8]));
+
+        // Other failures aren't retried.
+        let mut calls = 0;
+        let result = retry_rate_limited(
+            || {
+                calls += 1;
+                Err("Could not resolve host".to_string())
+            },
+            |_| panic!("slept on an offline error"),
+        );
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
     }
-    let mut clipped = text
-        .chars()
-        .take(max_width.saturating_sub(1))
-        .collect::<String>();
-    clipped.push('…');
-    clipped
-}
 
-fn format_date(date: &str) -> String {
-    if date.len() >= 10 {
-        date.chars().take(10).collect()
-    } else {
-        date.to_string()
+    #[test]
+    fn github_token_goes_in_through_the_environment() {
+        let command = git_in(Path::new("/repo"), Some("ghp_secret"));
+        let args = command.get_args().collect::<Vec<_>>();
+        assert_eq!(args, ["-C", "/repo"]);
+        let envs = command
+            .get_envs()
+            .map(|(key, value)| {
+                (
+                    key.to_string_lossy().into_owned(),
+                    value.unwrap().to_string_lossy().into_owned(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(envs["GIT_CONFIG_COUNT"], "1");
+        assert_eq!(
+            envs["GIT_CONFIG_KEY_0"],
+            "http.https://github.com/.extraheader"
+        );
+        assert_eq!(
+            envs["GIT_CONFIG_VALUE_0"],
+            format!(
+                "AUTHORIZATION: basic {}",
+                base64_encode(b"x-access-token:ghp_secret")
+            )
+        );
+        assert_eq!(git_in(Path::new("/repo"), None).get_envs().count(), 0);
     }
-}
 
-fn open_url(url: &str) -> io::Result<()> {
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("cmd")
-            .args(["/C", "start", "", url])
-            .spawn()
-            .map(|_| ())
+    #[test]
+    fn interrupted_clone_resumes_and_then_pulls() {
+        if !git_available() {
+            return;
+        }
+        let root = temp_dir("resume-clone");
+        let source = root.join("source");
+        let commit = |name: &str| {
+            fs::write(source.join(name), name).unwrap();
+            for args in [
+                &["add", "."][..],
+                &[
+                    "-c",
+                    "user.name=t",
+                    "-c",
+                    "user.email=t@t",
+                    "commit",
+                    "-qm",
+                    name,
+                ],
+            ] {
+                let status = Command::new("git")
+                    .arg("-C")
+                    .arg(&source)
+                    .args(args)
+                    .status()
+                    .unwrap();
+                assert!(status.success());
+            }
+        };
+        fs::create_dir_all(&source).unwrap();
+        assert!(Command::new("git")
+            .args(["init", "-q"])
+            .arg(&source)
+            .status()
+            .unwrap()
+            .success());
+        commit("one.md");
+
+        let repo = root.join("cache").join("content");
+        fs::create_dir_all(repo.parent().unwrap()).unwrap();
+        let missing = format!("file://{}", root.join("missing").display());
+        assert!(git_clone_atomic(&missing, &repo, |_| ()).is_err());
+        assert!(!repo.exists());
+        assert!(root
+            .join("cache")
+            .join("content.partial")
+            .join(".git")
+            .is_dir());
+
+        let url = format!("file://{}", source.display());
+        git_clone_atomic(&url, &repo, |_| ()).unwrap();
+        assert!(repo.join("one.md").is_file());
+        assert!(!root.join("cache").join("content.partial").exists());
+
+        commit("two.md");
+        git_pull(&repo).unwrap();
+        assert!(repo.join("two.md").is_file());
+        let _ = fs::remove_dir_all(&root);
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        Command::new("open").arg(url).spawn().map(|_| ())
+    #[test]
+    fn source_date_epoch_dates_the_feeds() {
+        let env = |value: &'static str| {
+            move |name: &str| (name == "SOURCE_DATE_EPOCH").then(|| value.to_string())
+        };
+        assert!(matches!(source_date_epoch(|_| None), Ok(None)));
+        assert!(source_date_epoch(env("yesterday")).is_err());
+        let built = source_date_epoch(env("1709294400")).unwrap();
+        assert_eq!(built.unwrap().rfc3339(), "2024-03-01T12:00:00Z");
+
+        let header = HeaderData {
+            title: "Notes".to_string(),
+            subtitle: String::new(),
+        };
+        let tab = essays_tab(vec![
+            post("dated", "2024-02-01", &[]),
+            post("undated", "someday", &[]),
+        ]);
+        let atom = atom_feed(&header, &tab, "https://johnjeong.com", built);
+        assert!(atom.contains(
+            "<id>https://johnjeong.com</id>\n  <updated>2024-03-01T12:00:00Z</updated>\n"
+        ));
+        assert!(atom.contains("<updated>2024-02-01T00:00:00Z</updated>"));
+        // The undated entry borrows the build date, not the newest post's.
+        assert_eq!(
+            atom.matches("<updated>2024-03-01T12:00:00Z</updated>")
+                .count(),
+            2
+        );
+
+        let rss = rss_feed(&header, &tab, "https://johnjeong.com", built);
+        assert!(rss.contains("<lastBuildDate>Fri, 01 Mar 2024 12:00:00 +0000</lastBuildDate>"));
+        assert!(!rss_feed(&header, &tab, "https://johnjeong.com", None).contains("lastBuildDate"));
+        assert_eq!(PostDate::from_unix(-1).rfc3339(), "1969-12-31T23:59:59Z");
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        Command::new("xdg-open").arg(url).spawn().map(|_| ())
+    #[test]
+    fn config_values_land_in_their_section() {
+        let file = "# mine\ntheme = \"mono\"\n\n[content]\nsite_url = \"https://x.dev\"\n\n[behavior]\nwatch = false\n";
+        assert_eq!(
+            config_with_value(file, "content", "dir", "~/notes"),
+            "# mine\ntheme = \"mono\"\n\n[content]\nsite_url = \"https://x.dev\"\ndir = \"~/notes\"\n\n[behavior]\nwatch = false\n"
+        );
+        assert_eq!(
+            config_with_value(file, "behavior", "watch", "true"),
+            file.replace("watch = false", "watch = \"true\"")
+        );
+        assert_eq!(
+            config_with_value(file, "", "theme", "light"),
+            file.replace("mono", "light")
+        );
+        assert_eq!(
+            config_with_value("theme = \"mono\"\n", "content", "dir", "/a \"b\""),
+            "theme = \"mono\"\n\n[content]\ndir = \"/a \\\"b\\\"\"\n"
+        );
+        assert_eq!(
+            config_with_value("", "content", "dir", "/a"),
+            "[content]\ndir = \"/a\"\n"
+        );
+        let written = config_with_value(file, "content", "dir", "~/notes");
+        let parsed = parse_config(&written).unwrap();
+        assert_eq!(parsed["content"]["dir"], "~/notes");
+        assert_eq!(parsed["behavior"]["watch"], "false");
     }
 
-    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-    {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            "open not supported on this OS",
-        ))
+    #[test]
+    fn git_progress_splits_on_carriage_returns() {
+        let output = "Cloning into 'content'...\nReceiving objects:  50% (1/2)\rReceiving objects: 100% (2/2), done.\r\nResolving deltas: 100% (1/1)";
+        let mut lines = Vec::new();
+        progress_lines(output.as_bytes(), &mut |line| lines.push(line.to_string()));
+        assert_eq!(
+            lines,
+            [
+                "Cloning into 'content'...",
+                "Receiving objects:  50% (1/2)",
+                "Receiving objects: 100% (2/2), done.",
+                "Resolving deltas: 100% (1/1)",
+            ]
+        );
+
+        // Progress replaces the status line while it runs and isn't logged.
+        let mut data = three_tabs();
+        let mut state = app_state();
+        state.status = Some("Cloning…".to_string());
+        update(
+            AppEvent::Progress(Some(lines[1].clone())),
+            &mut data,
+            &mut state,
+        )
+        .unwrap();
+        assert_eq!(
+            state.progress.as_deref(),
+            Some("Receiving objects:  50% (1/2)")
+        );
+        assert_eq!(state.status.as_deref(), Some("Cloning…"));
+        update(AppEvent::Progress(None), &mut data, &mut state).unwrap();
+        assert!(state.progress.is_none());
     }
-}
 
-fn print_help() {
-    println!("johnjeong - terminal edition");
-    println!();
-    println!("Usage:");
-    println!("  johnjeong");
-    println!("  johnjeong --help");
-    println!("  johnjeong --version");
-    println!();
-    println!("Keys:");
-    println!("  1-6    switch tabs");
-    println!("  g      gallery tab");
-    println!("  ↑/↓    move selection");
-    println!("  pgup/dn scroll content");
-    println!("  o/enter open link");
-    println!("  q      quit");
-    println!();
-    println!("Content:");
-    println!("  Set JOHNJEONG_CONTENT_DIR to a part-of-my-brain directory.");
-    println!("  If missing, content is fetched from GitHub (requires git).");
+    #[test]
+    fn qr_codes_carry_the_spec_format_and_error_correction() {
+        // "HELLO WORLD" at 1-M, from the worked example in the QR spec tutorials.
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        let codewords = qr_add_error_correction(1, &data);
+        assert_eq!(codewords[..16], data);
+        assert_eq!(
+            codewords[16..],
+            [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]
+        );
+
+        // Level M format strings for masks 0 to 7, bit 14 first.
+        const FORMATS: [u32; 8] = [
+            0b101010000010010,
+            0b101000100100101,
+            0b101111001111100,
+            0b101101101001011,
+            0b100010111111001,
+            0b100000011001110,
+            0b100111110010111,
+            0b100101010100000,
+        ];
+        let code = QrCode::encode("https://johnjeong.com").unwrap();
+        assert_eq!(code.size, 25);
+        let size = code.size;
+        let bit = |x: usize, y: usize| u32::from(code.module(x, y));
+        // The copy around the top-left finder, skipping the timing pattern at 6...
+        let around_finder = [0, 1, 2, 3, 4, 5, 7]
+            .iter()
+            .map(|&x| (x, 8))
+            .chain([(8, 8), (8, 7)])
+            .chain([5, 4, 3, 2, 1, 0].iter().map(|&y| (8, y)));
+        // ...and the one split between the other two.
+        let split = (0..7)
+            .map(|index| (8, size - 1 - index))
+            .chain((0..8).map(|index| (size - 8 + index, 8)));
+        let read = |modules: &mut dyn Iterator<Item = (usize, usize)>| {
+            modules.fold(0, |bits, (x, y)| bits << 1 | bit(x, y))
+        };
+        let first = read(&mut around_finder.into_iter());
+        assert_eq!(read(&mut split.into_iter()), first);
+        assert!(FORMATS.contains(&first), "{:015b}", first);
+        assert!(code.module(8, size - 8), "the dark module is missing");
+
+        // Timing patterns alternate between the finders, starting dark.
+        for index in 8..size - 8 {
+            assert_eq!(code.module(index, 6), index % 2 == 0);
+            assert_eq!(code.module(6, index), index % 2 == 0);
+        }
+    }
 }